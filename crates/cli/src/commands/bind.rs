@@ -1,13 +1,51 @@
+use assemblylift_generator::context::Context;
+use assemblylift_generator::projectfs::{infra_dir, Project};
+use assemblylift_generator::toml;
 use assemblylift_tools::terraform::Terraform;
 use clap::ArgMatches;
 
 pub fn command(matches: Option<&ArgMatches>) {
-    let _matches = match matches {
+    let matches = match matches {
         Some(matches) => matches,
         _ => panic!("could not get matches for bind command"),
     };
+    let env = matches.value_of("env");
 
-    let tf = Terraform::default();
+    let cwd = std::env::current_dir().unwrap();
+    if !super::validate::validate_project(&cwd, env) {
+        return;
+    }
+
+    let targets = match matches.values_of("target") {
+        Some(services) => match service_targets(services.collect(), env) {
+            Ok(targets) => targets,
+            Err(e) => return println!("❌  {}", e),
+        },
+        None => Vec::new(),
+    };
+
+    let tf = match Terraform::try_default() {
+        Ok(tf) => tf.with_dir(infra_dir(env).to_str().unwrap()).with_targets(targets),
+        Err(e) => return println!("❌  {}", e),
+    };
     tf.init();
     tf.apply();
 }
+
+/// The Terraform modules of the named services
+fn service_targets(services: Vec<&str>, env: Option<&str>) -> Result<Vec<String>, String> {
+    let cwd = std::env::current_dir().unwrap();
+    let manifest =
+        toml::asml::Manifest::read(&cwd.join("assemblylift.toml")).map_err(|e| e.to_string())?;
+    let project = Project::new(manifest.project.name.clone(), Some(cwd));
+    let ctx = Context::from_project(project, manifest, env)?;
+
+    let mut targets = Vec::new();
+    for name in services {
+        let service = ctx
+            .service(name)
+            .ok_or(format!("no service named `{}`", name))?;
+        targets.append(&mut service.terraform_targets());
+    }
+    Ok(targets)
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+/// Content-addressed record of the inputs each function was last built from. A function whose key is unchanged,
+/// and whose artifact still exists, is not rebuilt.
+#[derive(Serialize, Deserialize, Default)]
+pub struct BuildCache {
+    #[serde(skip)]
+    path: PathBuf,
+    functions: BTreeMap<String, String>,
+}
+
+impl BuildCache {
+    /// Load the cache at `path`, or start an empty one if it is missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let functions = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<BuildCache>(&json).ok())
+            .map(|cache| cache.functions)
+            .unwrap_or_default();
+        Self { path, functions }
+    }
+
+    pub fn is_fresh(&self, function_id: &str, key: &str) -> bool {
+        self.functions
+            .get(function_id)
+            .map(|k| k == key)
+            .unwrap_or(false)
+    }
+
+    pub fn insert(&mut self, function_id: String, key: String) {
+        self.functions.insert(function_id, key);
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(self).unwrap())
+    }
+}
+
+/// Compute the cache key of a function: a SHA-256 over its source files, the WASI adapter embedded in its component,
+/// the toolchain it is built with, and the version of the runtime it targets
+pub fn build_key(sources: &[PathBuf], wasi_snapshot_preview1: &[u8], toolchain: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(toolchain.as_bytes());
+    hasher.update(wasi_snapshot_preview1);
+    for source in sources {
+        hash_path(&mut hasher, source);
+    }
+    hex(&hasher.finalize())
+}
+
+fn hash_path(hasher: &mut Sha256, path: &Path) {
+    let entries = WalkDir::new(path)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_entry(|e| {
+            !matches!(
+                e.file_name().to_str(),
+                Some("target" | "node_modules" | "__pycache__")
+            )
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+    for entry in entries {
+        if let Ok(contents) = std::fs::read(entry.path()) {
+            let relative = entry.path().strip_prefix(path).unwrap_or(entry.path());
+            hasher.update(relative.to_string_lossy().as_bytes());
+            hasher.update(&contents);
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn digest(path: &Path) -> String {
+        let mut hasher = Sha256::new();
+        hash_path(&mut hasher, path);
+        hex(&hasher.finalize())
+    }
+
+    #[test]
+    fn build_key_covers_sources_adapter_and_toolchain() {
+        let dir = tempfile::tempdir().unwrap();
+        let function = dir.path().join("function");
+        write(&function.join("src/main.rs"), "fn main() {}");
+        let sources = vec![function.clone()];
+
+        let key = build_key(&sources, b"adapter", "rust");
+        assert_eq!(key, build_key(&sources, b"adapter", "rust"));
+        assert_ne!(key, build_key(&sources, b"other adapter", "rust"));
+        assert_ne!(key, build_key(&sources, b"adapter", "ruby-3.3.0"));
+
+        write(&function.join("src/main.rs"), "fn main() { println!(); }");
+        assert_ne!(key, build_key(&sources, b"adapter", "rust"));
+    }
+
+    #[test]
+    fn build_key_ignores_build_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("handler.py"), "def main(): pass");
+        let sources = vec![dir.path().to_path_buf()];
+
+        let key = build_key(&sources, b"adapter", "python");
+        write(&dir.path().join("target/release/f.wasm"), "wasm");
+        write(&dir.path().join("node_modules/m/index.js"), "js");
+        write(&dir.path().join("__pycache__/handler.pyc"), "pyc");
+        assert_eq!(key, build_key(&sources, b"adapter", "python"));
+    }
+
+    #[test]
+    fn hash_path_is_relative_to_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("a/src/lib.rs"), "lib");
+        write(&dir.path().join("b/src/lib.rs"), "lib");
+        write(&dir.path().join("c/src/mod.rs"), "lib");
+        assert_eq!(digest(&dir.path().join("a")), digest(&dir.path().join("b")));
+        assert_ne!(digest(&dir.path().join("a")), digest(&dir.path().join("c")));
+    }
+
+    #[test]
+    fn hash_path_of_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = dir.path().join("Cargo.lock");
+        write(&lock, "version = 3");
+        let before = digest(&lock);
+        write(&lock, "version = 4");
+        assert_ne!(before, digest(&lock));
+    }
+
+    #[test]
+    fn is_fresh_only_for_the_saved_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache/build.json");
+
+        let mut cache = BuildCache::load(path.clone());
+        assert!(!cache.is_fresh("svc.fn", "key"));
+        cache.insert("svc.fn".into(), "key".into());
+        cache.save().unwrap();
+
+        let cache = BuildCache::load(path);
+        assert!(cache.is_fresh("svc.fn", "key"));
+        assert!(!cache.is_fresh("svc.fn", "other key"));
+        assert!(!cache.is_fresh("svc.other", "key"));
+    }
+
+    #[test]
+    fn unreadable_cache_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("build.json");
+        write(&path, "not json");
+        assert!(!BuildCache::load(path).is_fresh("svc.fn", "key"));
+    }
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use assemblylift_core::wasm;
+use assemblylift_generator::context::Function;
+use assemblylift_generator::projectfs::{NetDir, Project};
+use assemblylift_tools::esbuild::Esbuild;
+
+use crate::archive::unzip;
+
+use super::{copy_shared_component, precompile_component, CastableFunction, CompileStatus};
+
+static JS_RUNTIME: Mutex<()> = Mutex::new(());
+
+/// Name of the engine archive in the tools lockfile
+const ENGINE_ARTIFACT: &str = "quickjs-wasm32-wasi";
+/// Version of the engine archive, which is pinned in the tools lockfile under this version
+const ENGINE_VERSION: &str = "2023-12-09";
+
+/// Entrypoints a JavaScript function may have, in order of preference
+const ENTRYPOINTS: [&str; 4] = ["handler.ts", "handler.js", "handler.mts", "handler.mjs"];
+
+pub struct JavascriptFunction {
+    project: Arc<Project>,
+    service_name: String,
+    function_name: String,
+    net_dir: NetDir,
+    enable_precompile: bool,
+    cpu_compat_mode: String,
+    engine_version: String,
+}
+
+impl JavascriptFunction {
+    pub fn new(function: &Function, project: Arc<Project>) -> Self {
+        let service_name = function.service_name.clone();
+        let net_dir = project.net_dir();
+        let net_path = net_dir
+            .service_dir(&service_name.clone())
+            .function_dir(function.name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+        std::fs::create_dir_all(PathBuf::from(&net_path))
+            .expect(&*format!("unable to create path {}", &net_path));
+        Self {
+            project: project.clone(),
+            service_name,
+            function_name: function.name.clone(),
+            net_dir,
+            enable_precompile: function.precompiled,
+            cpu_compat_mode: function.cpu_compat_mode.clone(),
+            engine_version: ENGINE_VERSION.into(),
+        }
+    }
+
+    fn engine_path(&self) -> String {
+        format!(
+            "{}/javascript/{}",
+            self.net_dir.runtime_dir().to_str().unwrap(),
+            self.engine_version
+        )
+    }
+}
+
+impl CastableFunction for JavascriptFunction {
+    fn compile(&self, wasi_snapshot_preview1: Vec<u8>) -> Result<CompileStatus, String> {
+        let function_name = &self.function_name;
+        let service_name = &self.service_name;
+        let function_artifact_path = self
+            .net_dir
+            .service_dir(&service_name.clone())
+            .function_dir(function_name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let function_dir = self
+            .project
+            .service_dir(service_name.into())
+            .function_dir(function_name.clone());
+        let entrypoint = ENTRYPOINTS
+            .iter()
+            .map(|e| function_dir.join(e))
+            .find(|e| e.exists())
+            .ok_or(format!(
+                "function `{}` has no entrypoint; expected one of {}",
+                function_name,
+                ENTRYPOINTS.join(", ")
+            ))?;
+
+        // The `asml` module is provided by the JS engine
+        let jssrc_path = PathBuf::from(format!("{}/jssrc", function_artifact_path));
+        std::fs::create_dir_all(&jssrc_path).map_err(|e| e.to_string())?;
+        Esbuild::try_default()
+            .map_err(|e| e.to_string())?
+            .bundle(&entrypoint, &jssrc_path.join("handler.js"), &["asml"])?;
+
+        // JavaScript functions share the engine, which is fetched & componentized at most once at a time
+        let _runtime = JS_RUNTIME.lock().unwrap();
+        let mut engine_changed = false;
+
+        let engine_path = self.engine_path();
+        std::fs::create_dir_all(&engine_path).map_err(|e| e.to_string())?;
+        if !Path::new(&format!("{}/quickjs-wasm32-wasi", engine_path)).exists() {
+            let url = format!(
+                "https://public.assemblylift.akkoro.io/runtime/javascript/{}/{}.zip",
+                self.engine_version, ENGINE_ARTIFACT
+            );
+            println!("Fetching JavaScript engine archive from {}...", url);
+            let zip = assemblylift_tools::fetch_locked(ENGINE_ARTIFACT, &self.engine_version, &url)
+                .map_err(|e| format!("could not fetch JavaScript engine: {}", e))?;
+            unzip(&zip, &engine_path)
+                .map_err(|_| "could not unpack JavaScript engine".to_string())?;
+
+            engine_changed = true;
+        }
+
+        let engine_wasm = format!("{}/quickjs-wasm32-wasi/qjs.wasm", engine_path);
+        let component_wasm = format!("{}/quickjs-wasm32-wasi/qjs.component.wasm", engine_path);
+        if engine_changed || !Path::new(&component_wasm).exists() {
+            let module = std::fs::read(&engine_wasm).map_err(|e| e.to_string())?;
+            if !wasm::imports_asml_wit(&module).map_err(|e| e.to_string())? {
+                return Err(format!(
+                    "JavaScript engine {} does not bind the asml-rt & asml-io interfaces",
+                    self.engine_version
+                ));
+            }
+            let embedded = wasm::embed_asml_wit(module).map_err(|e| e.to_string())?;
+            let component = wasm::make_wasi_component(embedded, wasi_snapshot_preview1.as_slice())
+                .map_err(|e| e.to_string())?;
+            std::fs::write(&component_wasm, component).map_err(|e| e.to_string())?;
+            engine_changed = true;
+        }
+
+        let (wasm_path, copy_changed) =
+            copy_shared_component(Path::new(&component_wasm), &self.artifact_path())?;
+        Ok(CompileStatus {
+            wasm_path,
+            changed: engine_changed || copy_changed,
+        })
+    }
+
+    fn sources(&self) -> Result<Vec<PathBuf>, String> {
+        Ok(vec![self
+            .project
+            .service_dir(self.service_name.clone())
+            .function_dir(self.function_name.clone())])
+    }
+
+    fn toolchain(&self) -> String {
+        format!("javascript-{}", self.engine_version)
+    }
+
+    fn precompile(&self, component: &Path, target: Option<&str>) -> Result<(), String> {
+        println!("⚡️ > Precompiling function `{}`...", &self.function_name);
+        precompile_component(
+            component,
+            target.unwrap_or("x86_64-linux-gnu"),
+            &self.cpu_compat_mode,
+        )
+    }
+
+    fn artifact_path(&self) -> PathBuf {
+        let net_path = self
+            .net_dir
+            .service_dir(&self.service_name.clone())
+            .function_dir(self.function_name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+        match self.enable_precompile {
+            true => PathBuf::from(format!(
+                "{}/{}.component.wasm.bin",
+                net_path, &self.function_name
+            )),
+            false => PathBuf::from(format!(
+                "{}/{}.component.wasm",
+                net_path, &self.function_name
+            )),
+        }
+    }
+
+    fn extra_artifacts(&self) -> Vec<PathBuf> {
+        let mut jssrc_path = self.artifact_path();
+        jssrc_path.set_file_name("jssrc");
+        vec![jssrc_path]
+    }
+}
@@ -3,10 +3,12 @@ use std::fs;
 use std::fs::read_to_string;
 use std::io::{Read, Write};
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 use assemblylift_core::wasm;
-use assemblylift_generator::context::Context;
-use assemblylift_generator::projectfs::Project;
+use assemblylift_core::wasm::PrecompiledManifest;
+use assemblylift_generator::context::{Context, Function};
+use assemblylift_generator::projectfs::{infra_dir, Project};
 use assemblylift_generator::toml;
 use assemblylift_tools::terraform::Terraform;
 use clap::ArgMatches;
@@ -15,9 +17,17 @@ use sha2::digest::FixedOutput;
 
 use crate::archive;
 
+use self::cache::BuildCache;
+use self::javascript::JavascriptFunction;
+use self::prebuilt::PrebuiltFunction;
+use self::python::PythonFunction;
 use self::ruby::RubyFunction;
 use self::rust::RustFunction;
 
+mod cache;
+mod javascript;
+mod prebuilt;
+mod python;
 mod ruby;
 mod rust;
 
@@ -28,22 +38,60 @@ pub struct CompileStatus {
 
 pub trait CastableFunction {
     fn compile(&self, wasi_snapshot_preview1: Vec<u8>) -> Result<CompileStatus, String>;
-    fn compose(&self);
-    fn precompile(&self, target: Option<&str>);
+    /// Compose the compiled component with the shared components at `components`. The result is written to the
+    /// function's own build directory, leaving the compiled component (which may be shared, e.g. an interpreter)
+    /// as it was.
+    fn compose(&self, status: CompileStatus, components: &[PathBuf]) -> Result<CompileStatus, String> {
+        if components.is_empty() {
+            return Ok(status);
+        }
+        for component in components {
+            if !component.exists() {
+                return Err(format!("component {} not found", component.display()));
+            }
+        }
+        let composed = wasm::compose_components(&status.wasm_path, components)
+            .map_err(|e| format!("unable to compose {}: {:?}", status.wasm_path.display(), e))?;
+
+        let mut composed_path = self.artifact_path();
+        composed_path.pop();
+        composed_path.push("build/composed.component.wasm");
+        std::fs::create_dir_all(composed_path.parent().unwrap()).map_err(|e| e.to_string())?;
+        let changed = status.changed
+            || std::fs::read(&composed_path)
+                .map(|previous| previous != composed)
+                .unwrap_or(true);
+        std::fs::write(&composed_path, composed).map_err(|e| e.to_string())?;
+
+        Ok(CompileStatus { wasm_path: composed_path, changed })
+    }
+    /// Precompile `component` for `target`, writing it alongside as `<component>.<target>.bin`
+    fn precompile(&self, component: &Path, target: Option<&str>) -> Result<(), String>;
+    /// Paths whose contents the function's build depends on
+    fn sources(&self) -> Result<Vec<PathBuf>, String>;
+    /// Identifies the toolchain the function is built with, e.g. its target or runtime version
+    fn toolchain(&self) -> String;
     // FIXME should CastableFunction be responsible for constructing its net path like this?
     fn artifact_path(&self) -> PathBuf;
+    /// Paths of artifacts packaged alongside the function's component, e.g. the source of an interpreted function
+    fn extra_artifacts(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
 }
 
 pub fn command(matches: Option<&ArgMatches>) {
-    let tf = Terraform::default();
-
-    let _matches = match matches {
+    let matches = match matches {
         Some(matches) => matches,
         _ => panic!("could not get matches for cast command"),
     };
+    let env = matches.value_of("env");
 
     // Init the project structure -- panic if the project isn't in the current working dir
     let cwd = std::env::current_dir().unwrap();
+    if !super::validate::validate_project(&cwd, env) {
+        return;
+    }
+
     let mut manifest_path = cwd.clone();
     manifest_path.push("assemblylift.toml");
 
@@ -52,11 +100,27 @@ pub fn command(matches: Option<&ArgMatches>) {
     let project = Project::new(asml_manifest.project.name.clone(), Some(cwd));
 
     let ctx = Rc::new(
-        Context::from_project(project.clone(), asml_manifest)
+        Context::from_project(project.clone(), asml_manifest, env)
             .expect("could not make context from manifest"),
     );
 
+    let selection = match Selection::from_matches(matches, &ctx) {
+        Ok(selection) => selection,
+        Err(e) => return println!("❌  {}", e),
+    };
+    let tf = match Terraform::try_default() {
+        Ok(tf) => tf
+            .with_dir(infra_dir(env).to_str().unwrap())
+            .with_targets(selection.terraform_targets(&ctx)),
+        Err(e) => return println!("❌  {}", e),
+    };
+
     let wasi_snapshot_preview1 = include_bytes!("wasm/wasi_snapshot_preview1.command.wasm");
+    let jobs = match matches.value_of("jobs") {
+        // validated by clap
+        Some(jobs) => jobs.parse::<usize>().unwrap(),
+        None => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+    };
 
     // Compile WASM & package function
     let functions = ctx
@@ -64,64 +128,63 @@ pub fn command(matches: Option<&ArgMatches>) {
         .iter()
         .map(|s| s.functions.clone())
         .flatten()
+        .filter(|function| selection.includes(function))
+        .map(|function| {
+            // Function archive is only needed for Lambda at this time
+            let archive = ctx
+                .service(&function.service_name)
+                .unwrap()
+                .provider
+                .name()
+                .eq(&assemblylift_generator::providers::aws_lambda::provider_name());
+            (function, archive)
+        })
         .collect::<Vec<_>>();
-    for function in functions {
-        let castable_function: Box<dyn CastableFunction> = match function.language.clone().as_str()
-        {
-            "rust" => Box::new(RustFunction::new(&function, project.clone())),
-            "ruby" => Box::new(RubyFunction::new(&function, project.clone())),
-            lang => panic!("unsupported function language: {}", lang),
-        };
-
-        let mut function_artifact_path = castable_function.artifact_path();
-        function_artifact_path.pop();
-        let function_artifact_path = function_artifact_path.to_str().unwrap();
 
-        match castable_function.compile(wasi_snapshot_preview1.clone().to_vec()) {
-            Ok(status) => {
-                let wasm_path_precompiled = PathBuf::from(&format!("{}.bin", status.wasm_path.to_str().unwrap()));
-                if (function.precompiled && status.changed) || (function.precompiled && !wasm_path_precompiled.exists()) {
-                    // TODO set target triple
-                    castable_function.precompile(None);
-                }
-
-                let from_path = match function.precompiled {
-                    true => wasm_path_precompiled,
-                    false => status.wasm_path.clone(),
+    let cache = Mutex::new(BuildCache::load(project.net_dir().build_cache_path()));
+    let queue = Mutex::new(functions.into_iter());
+    let errors = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let next = queue.lock().unwrap().next();
+                let (function, archive) = match next {
+                    Some(next) => next,
+                    None => break,
                 };
-
-                std::fs::copy(from_path, castable_function.artifact_path()).unwrap();
-            },
-            Err(e) => return println!("Error compiling function {}: {}", &function.name, e),
+                if let Err(e) = cast_function(
+                    &function,
+                    project.clone(),
+                    wasi_snapshot_preview1,
+                    archive,
+                    &cache,
+                ) {
+                    errors
+                        .lock()
+                        .unwrap()
+                        .push(format!("Error compiling function {}: {}", &function.name, e));
+                }
+            });
         }
+    });
 
-        // Function archive is only needed for Lambda at this time
-        if ctx
-            .service(&function.service_name)
-            .unwrap()
-            .provider
-            .name()
-            .eq(&assemblylift_generator::providers::aws_lambda::provider_name())
-        {
-            let mut function_dirs = vec![castable_function.artifact_path()];
-            if "ruby" == function.language.clone().as_str() {
-                function_dirs.push(PathBuf::from(format!(
-                    "{}/rubysrc",
-                    &function_artifact_path
-                )));
-            }
-            archive::zip_dirs(
-                function_dirs,
-                format!("{}/{}.zip", function_artifact_path.clone(), &function.name),
-                Vec::new(),
-            )
-            .expect("unable to zip function artifacts");
+    if let Err(e) = cache.into_inner().unwrap().save() {
+        return println!("❌  could not write build cache: {}", e);
+    }
+    let errors = errors.into_inner().unwrap();
+    if !errors.is_empty() {
+        for e in errors {
+            println!("{}", e);
         }
+        return;
     }
 
     // Cast Context to artifacts
     {
-        let fragments = ctx.cast().expect("could not cast assemblylift context");
+        let fragments = match ctx.cast() {
+            Ok(fragments) => fragments,
+            Err(e) => return println!("❌  {}", e.0),
+        };
         for fragment in fragments {
             let path = fragment.write_path.clone();
             if path.exists() {
@@ -150,3 +213,245 @@ pub fn command(matches: Option<&ArgMatches>) {
     tf.init();
     tf.plan();
 }
+
+/// The service or function `cast` is limited to, if any. The whole context is still cast so that the root module
+/// stays consistent, but only the selected functions are built and only the selected service's modules are planned.
+enum Selection {
+    All,
+    Service(String),
+    Function(String, String),
+}
+
+impl Selection {
+    fn from_matches(matches: &ArgMatches, ctx: &Context) -> Result<Self, String> {
+        let resource: Vec<&str> = match matches.values_of("resource") {
+            Some(values) => values.collect(),
+            None => return Ok(Selection::All),
+        };
+        let selection = match resource.as_slice() {
+            ["service", service_name] => Selection::Service(service_name.to_string()),
+            ["function", name] => match name.split_once('.') {
+                Some((service_name, function_name)) => {
+                    Selection::Function(service_name.into(), function_name.into())
+                }
+                None => return Err("syntax is `cast function <service>.<function>`".into()),
+            },
+            _ => {
+                return Err(
+                    "syntax is `cast service <service>` or `cast function <service>.<function>`"
+                        .into(),
+                )
+            }
+        };
+
+        let (service_name, function_name) = match &selection {
+            Selection::Service(service_name) => (service_name, None),
+            Selection::Function(service_name, function_name) => (service_name, Some(function_name)),
+            Selection::All => unreachable!(),
+        };
+        let service = ctx
+            .service(service_name)
+            .ok_or(format!("no service named `{}`", service_name))?;
+        if let Some(function_name) = function_name {
+            if !service.functions.iter().any(|f| &f.name == function_name) {
+                return Err(format!(
+                    "no function named `{}` in service `{}`",
+                    function_name, service_name
+                ));
+            }
+        }
+        Ok(selection)
+    }
+
+    fn includes(&self, function: &Function) -> bool {
+        match self {
+            Selection::All => true,
+            Selection::Service(service_name) => &function.service_name == service_name,
+            Selection::Function(service_name, function_name) => {
+                &function.service_name == service_name && &function.name == function_name
+            }
+        }
+    }
+
+    fn terraform_targets(&self, ctx: &Context) -> Vec<String> {
+        match self {
+            Selection::All => Vec::new(),
+            Selection::Service(service_name) | Selection::Function(service_name, _) => ctx
+                .service(service_name)
+                .map(|service| service.terraform_targets())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Build a function unless the build cache shows its inputs are unchanged, then copy its artifact into `net`
+fn cast_function(
+    function: &Function,
+    project: Arc<Project>,
+    wasi_snapshot_preview1: &[u8],
+    archive: bool,
+    cache: &Mutex<BuildCache>,
+) -> Result<(), String> {
+    let components: Vec<PathBuf> = function
+        .components
+        .iter()
+        .map(|component| project.dir().join(component))
+        .collect();
+    let castable_function: Box<dyn CastableFunction> = match function.language.clone().as_str() {
+        "rust" => Box::new(RustFunction::new(function, project)),
+        "ruby" => Box::new(RubyFunction::new(function, project)),
+        "python" => Box::new(PythonFunction::new(function, project)),
+        "javascript" => Box::new(JavascriptFunction::new(function, project)),
+        _ if function.build.is_some() => Box::new(PrebuiltFunction::new(function, project)),
+        lang => return Err(format!("unsupported function language: {}", lang)),
+    };
+
+    let mut function_artifact_path = castable_function.artifact_path();
+    function_artifact_path.pop();
+    let function_artifact_path = function_artifact_path.to_str().unwrap();
+    let archive_path = format!("{}/{}.zip", function_artifact_path, &function.name);
+
+    let function_id = format!("{}.{}", &function.service_name, &function.name);
+    let key = cache::build_key(
+        &[castable_function.sources()?, components.clone()].concat(),
+        wasi_snapshot_preview1,
+        &format!(
+            "{}:{}:{}:{}",
+            castable_function.toolchain(),
+            function.precompiled,
+            function.cpu_compat_mode,
+            function.architecture
+        ),
+    );
+    if cache.lock().unwrap().is_fresh(&function_id, &key)
+        && castable_function.artifact_path().exists()
+        && (!archive || Path::new(&archive_path).exists())
+    {
+        println!("📄 > No change in function `{}`, skipping...", &function_id);
+        return Ok(());
+    }
+
+    let status = castable_function.compile(wasi_snapshot_preview1.to_vec())?;
+    let status = castable_function.compose(status, &components)?;
+    let target = function.target_triple();
+    let wasm_path_precompiled = PathBuf::from(&format!(
+        "{}.{}.bin",
+        status.wasm_path.to_str().unwrap(),
+        target
+    ));
+    if function.precompiled && !is_precompiled(&status, &wasm_path_precompiled, target, function) {
+        castable_function.precompile(&status.wasm_path, Some(target))?;
+    }
+
+    let from_path = match function.precompiled {
+        true => wasm_path_precompiled,
+        false => status.wasm_path.clone(),
+    };
+
+    std::fs::copy(&from_path, castable_function.artifact_path()).map_err(|e| e.to_string())?;
+    if function.precompiled {
+        std::fs::copy(
+            PrecompiledManifest::path(&from_path),
+            PrecompiledManifest::path(&castable_function.artifact_path()),
+        )
+        .map_err(|e| e.to_string())?;
+        // Ship the component too, so that a host of a different target can fall back to JIT compiling it
+        std::fs::copy(
+            &status.wasm_path,
+            castable_function.artifact_path().with_extension(""),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    if archive {
+        let mut function_dirs = vec![castable_function.artifact_path()];
+        if function.precompiled {
+            let artifact_path = castable_function.artifact_path();
+            function_dirs.push(PrecompiledManifest::path(&artifact_path));
+            function_dirs.push(artifact_path.with_extension(""));
+        }
+        function_dirs.append(&mut castable_function.extra_artifacts());
+        archive::zip_dirs(function_dirs, archive_path, Vec::new())
+            .map_err(|_| "unable to zip function artifacts".to_string())?;
+    }
+
+    cache.lock().unwrap().insert(function_id, key);
+    Ok(())
+}
+
+/// Precompile `component` for `target`, writing the artifact and its manifest to `<component>.<target>.bin`
+fn precompile_component(
+    component: &Path,
+    target: &str,
+    cpu_compat_mode: &str,
+) -> Result<(), String> {
+    let bytes = wasm::precompile(component, target, cpu_compat_mode)
+        .map_err(|e| format!("unable to precompile {}: {}", component.display(), e))?;
+    let out_path = PathBuf::from(format!("{}.{}.bin", component.display(), target));
+    std::fs::write(&out_path, bytes).map_err(|e| e.to_string())?;
+    let wasm = std::fs::read(component).map_err(|e| e.to_string())?;
+    PrecompiledManifest::new(&wasm, target, cpu_compat_mode)
+        .and_then(|manifest| manifest.write(&out_path))
+        .map_err(|e| e.to_string())?;
+    println!("📄 > Wrote {}", out_path.display());
+    Ok(())
+}
+
+/// Copy a component shared between functions (e.g. an interpreter) into the build directory of the function whose
+/// artifact is at `artifact_path`, so that the function is precompiled & packaged from its own copy. Returns the
+/// path of the copy, and whether it changed.
+fn copy_shared_component(component: &Path, artifact_path: &Path) -> Result<(PathBuf, bool), String> {
+    let mut to = artifact_path.to_path_buf();
+    to.set_file_name("build");
+    fs::create_dir_all(&to).map_err(|e| format!("unable to create {}: {}", to.display(), e))?;
+    to.push(component.file_name().unwrap());
+
+    let bytes = fs::read(component)
+        .map_err(|e| format!("unable to read {}: {}", component.display(), e))?;
+    let changed = fs::read(&to).map(|previous| previous != bytes).unwrap_or(true);
+    if changed {
+        fs::write(&to, bytes).map_err(|e| format!("unable to write {}: {}", to.display(), e))?;
+    }
+    Ok((to, changed))
+}
+
+/// Replace the contents of `to` with a copy of the source directory `dir`, less any entries named in `exclude`
+fn copy_source(dir: &Path, to: &Path, exclude: &[&str]) -> Result<(), String> {
+    fn copy_entries(dir: &Path, to: &Path, exclude: &[&str]) -> std::io::Result<()> {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if exclude.iter().any(|name| entry.file_name() == *name) {
+                continue;
+            }
+            let file_type = entry.file_type()?;
+            if file_type.is_file() {
+                fs::copy(entry.path(), to.join(entry.file_name()))?;
+            } else if file_type.is_dir() {
+                copy_entries(&entry.path(), &to.join(entry.file_name()), exclude)?;
+            }
+        }
+        Ok(())
+    }
+
+    if to.exists() {
+        fs::remove_dir_all(to).map_err(|e| format!("unable to clear {}: {}", to.display(), e))?;
+    }
+    copy_entries(dir, to, exclude)
+        .map_err(|e| format!("unable to copy {} to {}: {}", dir.display(), to.display(), e))
+}
+
+/// Whether the component has been precompiled as `function` requires since it last changed, according to the
+/// manifest written alongside the precompiled artifact
+fn is_precompiled(status: &CompileStatus, bin_path: &Path, target: &str, function: &Function) -> bool {
+    if status.changed {
+        return false;
+    }
+    let wasm = std::fs::read(&status.wasm_path).ok();
+    match PrecompiledManifest::read(bin_path) {
+        Ok(manifest) => manifest
+            .incompatibility(Some(target), &function.cpu_compat_mode, wasm.as_deref())
+            .is_none(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use assemblylift_core::wasm;
+use assemblylift_generator::context::{Build, Function};
+use assemblylift_generator::projectfs::Project;
+
+use super::{precompile_component, CastableFunction, CompileStatus};
+
+/// A function in any language which can target WASI, built by the command given in its manifest. The command may
+/// produce either a core module, which is componentized here, or a component of the AssemblyLift world.
+pub struct PrebuiltFunction {
+    project: Arc<Project>,
+    service_name: String,
+    function_name: String,
+    language: String,
+    build: Build,
+    net_path: String,
+    enable_precompile: bool,
+    cpu_compat_mode: String,
+}
+
+impl PrebuiltFunction {
+    pub fn new(function: &Function, project: Arc<Project>) -> Self {
+        let service_name = function.service_name.clone();
+        let net_path = project
+            .net_dir()
+            .service_dir(&service_name.clone())
+            .function_dir(function.name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+        std::fs::create_dir_all(PathBuf::from(&net_path))
+            .expect(&*format!("unable to create path {}", &net_path));
+        Self {
+            project: project.clone(),
+            service_name,
+            function_name: function.name.clone(),
+            language: function.language.clone(),
+            build: function
+                .build
+                .clone()
+                .expect("prebuilt function must have a build command"),
+            net_path,
+            enable_precompile: function.precompiled,
+            cpu_compat_mode: function.cpu_compat_mode.clone(),
+        }
+    }
+
+    fn function_dir(&self) -> PathBuf {
+        self.project
+            .service_dir(self.service_name.clone())
+            .function_dir(self.function_name.clone())
+    }
+
+    /// Where the component is built to; kept apart from the artifact, which is copied from it
+    fn component_path(&self) -> PathBuf {
+        PathBuf::from(format!(
+            "{}/build/{}.component.wasm",
+            self.net_path, self.function_name
+        ))
+    }
+}
+
+impl CastableFunction for PrebuiltFunction {
+    fn compile(&self, wasi_snapshot_preview1: Vec<u8>) -> Result<CompileStatus, String> {
+        println!("🛠️ > Building function `{}`...", self.function_name.clone());
+        let build = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.build.command)
+            .current_dir(self.function_dir())
+            .output()
+            .map_err(|e| {
+                format!(
+                    "unable to run build command of function {}: {}",
+                    &self.function_name, e
+                )
+            })?;
+        if !build.status.success() {
+            return Err(format!(
+                "Unable to build function {}:\n{}{}",
+                &self.function_name,
+                String::from_utf8_lossy(&build.stdout),
+                String::from_utf8_lossy(&build.stderr)
+            ));
+        }
+
+        let output = self.function_dir().join(&self.build.output);
+        let wasm = std::fs::read(&output).map_err(|e| {
+            format!(
+                "build of function {} did not produce {}: {}",
+                &self.function_name,
+                output.display(),
+                e
+            )
+        })?;
+        let component = match is_component(&wasm) {
+            true => wasm,
+            false => {
+                let embedded = wasm::embed_asml_wit(wasm).map_err(|e| e.to_string())?;
+                wasm::make_wasi_component(embedded, wasi_snapshot_preview1.as_slice()).map_err(
+                    |e| format!("unable to make component of {}: {}", output.display(), e),
+                )?
+            }
+        };
+
+        let component_path = self.component_path();
+        std::fs::create_dir_all(component_path.parent().unwrap()).map_err(|e| e.to_string())?;
+        let changed = std::fs::read(&component_path)
+            .map(|previous| previous != component)
+            .unwrap_or(true);
+        std::fs::write(&component_path, component).map_err(|e| e.to_string())?;
+
+        Ok(CompileStatus {
+            wasm_path: component_path,
+            changed,
+        })
+    }
+
+    fn sources(&self) -> Result<Vec<PathBuf>, String> {
+        // Everything in the function directory but the build output, which would otherwise change the key of the
+        // build that produced it
+        let output = self.function_dir().join(&self.build.output);
+        let mut sources: Vec<PathBuf> = std::fs::read_dir(self.function_dir())
+            .map_err(|e| format!("unable to read {}: {}", self.function_dir().display(), e))?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| !output.starts_with(p))
+            .collect();
+        sources.sort();
+        Ok(sources)
+    }
+
+    fn toolchain(&self) -> String {
+        format!("{}-{}", self.language, self.build.command)
+    }
+
+    fn precompile(&self, component: &Path, target: Option<&str>) -> Result<(), String> {
+        println!("⚡️ > Precompiling function `{}`...", &self.function_name);
+        precompile_component(
+            component,
+            target.unwrap_or("x86_64-linux-gnu"),
+            &self.cpu_compat_mode,
+        )
+    }
+
+    fn artifact_path(&self) -> PathBuf {
+        match self.enable_precompile {
+            true => PathBuf::from(format!(
+                "{}/{}.component.wasm.bin",
+                self.net_path, &self.function_name
+            )),
+            false => PathBuf::from(format!(
+                "{}/{}.component.wasm",
+                self.net_path, &self.function_name
+            )),
+        }
+    }
+}
+
+/// Whether `wasm` is a component rather than a core module, going by the layer field of its preamble
+fn is_component(wasm: &[u8]) -> bool {
+    wasm.len() >= 8 && wasm[0..4] == *b"\0asm" && wasm[6..8] == [1, 0]
+}
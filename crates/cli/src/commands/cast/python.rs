@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use assemblylift_core::wasm;
+use assemblylift_generator::context::Function;
+use assemblylift_generator::projectfs::{NetDir, Project};
+use assemblylift_generator::validate::DEFAULT_PYTHON_VERSION;
+
+use crate::archive::unzip;
+
+use super::{
+    copy_shared_component, copy_source, precompile_component, CastableFunction, CompileStatus,
+};
+
+static PYTHON_RUNTIME: Mutex<()> = Mutex::new(());
+
+const RUNTIME_ARTIFACT: &str = "python-wasm32-wasi";
+
+pub struct PythonFunction {
+    project: Arc<Project>,
+    service_name: String,
+    function_name: String,
+    net_dir: NetDir,
+    enable_precompile: bool,
+    cpu_compat_mode: String,
+    python_version: String,
+}
+
+impl PythonFunction {
+    pub fn new(function: &Function, project: Arc<Project>) -> Self {
+        let service_name = function.service_name.clone();
+        let net_dir = project.net_dir();
+        let net_path = net_dir
+            .service_dir(&service_name.clone())
+            .function_dir(function.name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+        std::fs::create_dir_all(PathBuf::from(&net_path))
+            .expect(&*format!("unable to create path {}", &net_path));
+        Self {
+            project: project.clone(),
+            service_name,
+            function_name: function.name.clone(),
+            net_dir,
+            enable_precompile: function.precompiled,
+            cpu_compat_mode: function.cpu_compat_mode.clone(),
+            python_version: function
+                .python_version
+                .clone()
+                .unwrap_or(DEFAULT_PYTHON_VERSION.into()),
+        }
+    }
+
+    fn runtime_path(&self) -> String {
+        format!(
+            "{}/python/{}",
+            self.net_dir.runtime_dir().to_str().unwrap(),
+            self.python_version
+        )
+    }
+}
+
+impl CastableFunction for PythonFunction {
+    fn compile(&self, wasi_snapshot_preview1: Vec<u8>) -> Result<CompileStatus, String> {
+        let function_name = &self.function_name;
+        let service_name = &self.service_name;
+        let function_artifact_path = self
+            .net_dir
+            .service_dir(&service_name.clone())
+            .function_dir(function_name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let function_dir = self
+            .project
+            .service_dir(service_name.into())
+            .function_dir(function_name.clone());
+        if !function_dir.join("handler.py").exists() {
+            return Err(format!("function `{}` has no handler.py", function_name));
+        }
+
+        let pysrc_path = PathBuf::from(format!("{}/pysrc", function_artifact_path));
+        copy_source(&function_dir, &pysrc_path, &["__pycache__"])?;
+
+        // Python functions share the runtime, which is fetched & componentized at most once at a time
+        let _runtime = PYTHON_RUNTIME.lock().unwrap();
+        let mut python_changed = false;
+
+        let runtime_path = self.runtime_path();
+        std::fs::create_dir_all(&runtime_path).map_err(|e| e.to_string())?;
+        if !Path::new(&format!("{}/{}", runtime_path, RUNTIME_ARTIFACT)).exists() {
+            let url = format!(
+                "https://public.assemblylift.akkoro.io/runtime/python/{}/{}.zip",
+                self.python_version, RUNTIME_ARTIFACT
+            );
+            println!("Fetching Python runtime archive from {}...", url);
+            let zip =
+                assemblylift_tools::fetch_locked(RUNTIME_ARTIFACT, &self.python_version, &url)
+                    .map_err(|e| format!("could not fetch Python runtime: {}", e))?;
+            std::fs::write(format!("{}/{}.zip", &runtime_path, RUNTIME_ARTIFACT), &zip)
+                .map_err(|e| e.to_string())?;
+            unzip(&zip, &runtime_path)
+                .map_err(|_| "could not unpack Python runtime".to_string())?;
+
+            python_changed = true;
+        }
+
+        let python_wasm = format!(
+            "{}/python-wasm32-wasi/usr/local/bin/python.wasm",
+            runtime_path
+        );
+        let component_wasm = format!(
+            "{}/python-wasm32-wasi/usr/local/bin/python.component.wasm",
+            runtime_path
+        );
+        if python_changed || !Path::new(&component_wasm).exists() {
+            let module = std::fs::read(&python_wasm).map_err(|e| e.to_string())?;
+            if !wasm::imports_asml_wit(&module).map_err(|e| e.to_string())? {
+                return Err(format!(
+                    "Python runtime {} does not bind the asml-rt & asml-io interfaces",
+                    self.python_version
+                ));
+            }
+            let embedded = wasm::embed_asml_wit(module).map_err(|e| e.to_string())?;
+            let component = wasm::make_wasi_component(embedded, wasi_snapshot_preview1.as_slice())
+                .map_err(|e| e.to_string())?;
+            std::fs::write(&component_wasm, component).map_err(|e| e.to_string())?;
+            python_changed = true;
+        }
+
+        let (wasm_path, copy_changed) =
+            copy_shared_component(Path::new(&component_wasm), &self.artifact_path())?;
+        Ok(CompileStatus {
+            wasm_path,
+            changed: python_changed || copy_changed,
+        })
+    }
+
+    fn sources(&self) -> Result<Vec<PathBuf>, String> {
+        Ok(vec![self
+            .project
+            .service_dir(self.service_name.clone())
+            .function_dir(self.function_name.clone())])
+    }
+
+    fn toolchain(&self) -> String {
+        format!("python-{}", self.python_version)
+    }
+
+    fn precompile(&self, component: &Path, target: Option<&str>) -> Result<(), String> {
+        println!("⚡️ > Precompiling function `{}`...", &self.function_name);
+        precompile_component(
+            component,
+            target.unwrap_or("x86_64-linux-gnu"),
+            &self.cpu_compat_mode,
+        )
+    }
+
+    fn artifact_path(&self) -> PathBuf {
+        let net_path = self
+            .net_dir
+            .service_dir(&self.service_name.clone())
+            .function_dir(self.function_name.clone())
+            .to_str()
+            .unwrap()
+            .to_string();
+        match self.enable_precompile {
+            true => PathBuf::from(format!(
+                "{}/{}.component.wasm.bin",
+                net_path, &self.function_name
+            )),
+            false => PathBuf::from(format!(
+                "{}/{}.component.wasm",
+                net_path, &self.function_name
+            )),
+        }
+    }
+
+    fn extra_artifacts(&self) -> Vec<PathBuf> {
+        let mut pysrc_path = self.artifact_path();
+        pysrc_path.set_file_name("pysrc");
+        vec![pysrc_path]
+    }
+}
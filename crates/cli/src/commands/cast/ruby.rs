@@ -1,22 +1,30 @@
 use std::fs::File;
-use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
 
 use path_abs::PathInfo;
 
 use assemblylift_core::wasm;
 use assemblylift_generator::context::Function;
 use assemblylift_generator::projectfs::{NetDir, Project};
+use assemblylift_generator::validate::DEFAULT_RUBY_VERSION;
 use sha2::{Digest, Sha256};
 use sha2::digest::FixedOutput;
+use walkdir::WalkDir;
 
 use crate::archive::unzip;
 
-use super::{CastableFunction, CompileStatus};
+use super::{
+    copy_shared_component, copy_source, precompile_component, CastableFunction, CompileStatus,
+};
+
+static RUBY_RUNTIME: Mutex<()> = Mutex::new(());
+
+const RUNTIME_ARTIFACT: &str = "ruby-wasm32-wasi";
 
 pub struct RubyFunction {
-    project: Rc<Project>,
+    project: Arc<Project>,
     service_name: String,
     function_name: String,
     net_dir: NetDir,
@@ -26,7 +34,7 @@ pub struct RubyFunction {
 }
 
 impl RubyFunction {
-    pub fn new(function: &Function, project: Rc<Project>) -> Self {
+    pub fn new(function: &Function, project: Arc<Project>) -> Self {
         let service_name = function.service_name.clone();
         let net_dir = project.net_dir();
         let net_path = net_dir
@@ -44,11 +52,86 @@ impl RubyFunction {
             net_dir,
             enable_precompile: function.precompiled,
             cpu_compat_mode: function.cpu_compat_mode.clone(),
-            ruby_version: "3.3.0-dev".into(),
+            ruby_version: function
+                .ruby_version
+                .clone()
+                .unwrap_or(DEFAULT_RUBY_VERSION.to_string()),
         }
     }
 }
 
+impl RubyFunction {
+    /// Install the gems of `gemfile` into `rubysrc/vendor/bundle` with Bundler's standalone mode, so that the handler
+    /// can `require_relative 'vendor/bundle/bundler/setup'` without Bundler itself. Gems are installed for the `ruby`
+    /// platform, and any which build a native extension are rejected, as it could not be loaded under WASI.
+    fn vendor_gems(&self, gemfile: &Path, rubysrc: &Path) -> Result<(), String> {
+        println!("💎 > Vendoring gems of function `{}`...", &self.function_name);
+        let bundle_path = rubysrc.join("vendor/bundle");
+        let mut bundle = Command::new("bundle");
+        bundle
+            .arg("install")
+            .arg("--standalone")
+            .env("BUNDLE_GEMFILE", gemfile)
+            .env("BUNDLE_PATH", &bundle_path)
+            .env("BUNDLE_FORCE_RUBY_PLATFORM", "true")
+            .env("BUNDLE_WITHOUT", "development:test");
+        if gemfile.with_file_name("Gemfile.lock").exists() {
+            bundle.env("BUNDLE_FROZEN", "true");
+        }
+        let output = bundle
+            .output()
+            .map_err(|e| format!("unable to run bundle (is Bundler installed?): {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Unable to vendor gems of function {}:\n{}{}",
+                &self.function_name,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        // Bundler installs under the ABI version of the host's Ruby, but the runtime looks under its own
+        let abi_version = ruby_abi_version(&self.ruby_version);
+        let ruby_dir = bundle_path.join("ruby");
+        for entry in std::fs::read_dir(&ruby_dir).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            if entry.file_name().to_string_lossy() != abi_version {
+                let to = ruby_dir.join(&abi_version);
+                if to.exists() {
+                    std::fs::remove_dir_all(&to).map_err(|e| e.to_string())?;
+                }
+                std::fs::rename(entry.path(), to).map_err(|e| e.to_string())?;
+            }
+        }
+
+        let mut native: Vec<String> = WalkDir::new(&ruby_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| ext == "so" || ext == "bundle")
+                    .unwrap_or(false)
+            })
+            .filter_map(|e| {
+                let relative = e.path().strip_prefix(&ruby_dir).ok()?;
+                let mut components = relative.components().skip_while(|c| c.as_os_str() != "gems");
+                components.nth(1).map(|c| c.as_os_str().to_string_lossy().to_string())
+            })
+            .collect();
+        native.sort();
+        native.dedup();
+        if !native.is_empty() {
+            return Err(format!(
+                "function {} depends on gems with native extensions, which cannot run on WASI: {}",
+                &self.function_name,
+                native.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl CastableFunction for RubyFunction {
     fn compile(&self, wasi_snapshot_preview1: Vec<u8>) -> Result<CompileStatus, String> {
         let function_name = &self.function_name;
@@ -62,49 +145,41 @@ impl CastableFunction for RubyFunction {
             .to_string();
 
         let rubysrc_path = format!("{}/rubysrc", function_artifact_path);
-        if !Path::new(&rubysrc_path).exists() {
-            std::fs::create_dir(&rubysrc_path).unwrap();
-        }
 
         let function_dir = self
             .project
             .service_dir(service_name.into())
             .function_dir(function_name.clone());
 
-        fn copy_entries(dir: &PathBuf, to: &PathBuf) {
-            for entry in std::fs::read_dir(dir).unwrap() {
-                let entry = entry.unwrap();
-                if entry.file_type().unwrap().is_file() {
-                    let copy_to = format!(
-                        "{}/{}",
-                        to.to_str().unwrap(),
-                        entry.file_name().to_str().unwrap()
-                    );
-                    std::fs::copy(entry.path(), copy_to).unwrap();
-                } else if entry.file_type().unwrap().is_dir() {
-                    let mut copy_to = PathBuf::from(to);
-                    copy_to.push(entry.path().iter().last().unwrap());
-                    std::fs::create_dir_all(&copy_to).unwrap();
-                    copy_entries(&entry.path(), &copy_to);
-                }
-            }
+        // Local Bundler config would point the vendored gems elsewhere
+        copy_source(&function_dir, Path::new(&rubysrc_path), &[".bundle"])?;
+
+        let gemfile = function_dir.join("Gemfile");
+        if gemfile.exists() {
+            self.vendor_gems(&gemfile, Path::new(&rubysrc_path))?;
         }
-        copy_entries(&function_dir, &PathBuf::from(rubysrc_path));
 
+        // Ruby functions share the runtime, which is fetched & componentized at most once at a time
+        let _runtime = RUBY_RUNTIME.lock().unwrap();
         let mut ruby_changed = false;
 
         let ruby_runtime_path = format!("{}/ruby/{}", self.project.net_dir().runtime_dir().to_str().unwrap(), self.ruby_version);
         std::fs::create_dir_all(&ruby_runtime_path).unwrap();
-        if !Path::new(&format!("{}/ruby-wasm32-wasi", ruby_runtime_path)).exists() {
-            let mut zip = Vec::new();
-            let url =
-                format!("http://public.assemblylift.akkoro.io/runtime/ruby/{}/ruby-wasm32-wasi.zip", self.ruby_version);
+        if !Path::new(&format!("{}/{}", ruby_runtime_path, RUNTIME_ARTIFACT)).exists() {
+            let url = format!(
+                "https://public.assemblylift.akkoro.io/runtime/ruby/{}/{}.zip",
+                self.ruby_version, RUNTIME_ARTIFACT
+            );
             println!("Fetching Ruby runtime archive from {}...", url);
-            let mut response =
-                reqwest::blocking::get(url).expect("could not fetch ruby runtime zip");
-            response.read_to_end(&mut zip).unwrap();
-            std::fs::write(format!("{}/ruby-wasm32-wasi.zip", &ruby_runtime_path), &zip).unwrap();
-            unzip(&zip, &ruby_runtime_path).unwrap();
+            let zip = assemblylift_tools::fetch_locked(RUNTIME_ARTIFACT, &self.ruby_version, &url)
+                .map_err(|e| format!("could not fetch Ruby runtime: {}", e))?;
+            std::fs::write(
+                format!("{}/{}.zip", &ruby_runtime_path, RUNTIME_ARTIFACT),
+                &zip,
+            )
+            .map_err(|e| e.to_string())?;
+            unzip(&zip, &ruby_runtime_path)
+                .map_err(|_| "could not unpack Ruby runtime".to_string())?;
 
             ruby_changed = true;
         }
@@ -150,33 +225,30 @@ impl CastableFunction for RubyFunction {
             std::fs::write(component_wasm.clone(), component.clone()).unwrap();
         }
 
-        Ok(CompileStatus { wasm_path: PathBuf::from(&component_wasm), changed: ruby_changed })
+        let (wasm_path, copy_changed) =
+            copy_shared_component(Path::new(&component_wasm), &self.artifact_path())?;
+        Ok(CompileStatus { wasm_path, changed: ruby_changed || copy_changed })
+    }
+
+    fn sources(&self) -> Result<Vec<PathBuf>, String> {
+        Ok(vec![self
+            .project
+            .service_dir(self.service_name.clone())
+            .function_dir(self.function_name.clone())])
     }
 
-    fn compose(&self) {
-        todo!()
+    fn toolchain(&self) -> String {
+        format!("ruby-{}", self.ruby_version)
     }
 
     // TODO projectfs should handle mapping the precompiled bin path
-    fn precompile(&self, target: Option<&str>) {
+    fn precompile(&self, component: &Path, target: Option<&str>) -> Result<(), String> {
         println!("⚡️ > Precompiling function `{}`...", &self.function_name);
-        let net_path = self
-            .net_dir
-            .runtime_dir()
-            .to_str()
-            .unwrap()
-            .to_string();
-        let ruby_runtime_path = format!("{}/ruby/{}", net_path, self.ruby_version);
-        let path = format!("{}/ruby-wasm32-wasi/usr/local/bin/ruby.component.wasm", &ruby_runtime_path);
-        let bytes = wasm::precompile(
-            Path::new(&path),
-            &target.unwrap_or("x86_64-linux-gnu"),
-            &self.cpu_compat_mode.clone(),
+        precompile_component(
+            component,
+            target.unwrap_or("x86_64-linux-gnu"),
+            &self.cpu_compat_mode,
         )
-        .unwrap();
-        let out_path = format!("{}.bin", path);
-        std::fs::write(&out_path, bytes).unwrap();
-        println!("📄 > Wrote {}", &out_path);
     }
 
     fn artifact_path(&self) -> PathBuf {
@@ -200,4 +272,31 @@ impl CastableFunction for RubyFunction {
             )),
         }
     }
+    fn extra_artifacts(&self) -> Vec<PathBuf> {
+        let mut rubysrc_path = self.artifact_path();
+        rubysrc_path.set_file_name("rubysrc");
+        vec![rubysrc_path]
+    }
+}
+
+/// The ABI version of Ruby `version`, which names the directory gems are installed under; e.g. `3.3.0` for `3.3.0-dev`
+fn ruby_abi_version(version: &str) -> String {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    format!(
+        "{}.{}.0",
+        parts.next().unwrap_or("3"),
+        parts.next().unwrap_or("0")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abi_version_drops_the_patch_level_and_suffix() {
+        assert_eq!(ruby_abi_version("3.3.0-dev"), "3.3.0");
+        assert_eq!(ruby_abi_version("3.2.2"), "3.2.0");
+        assert_eq!(ruby_abi_version("3.4"), "3.4.0");
+    }
 }
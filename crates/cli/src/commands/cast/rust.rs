@@ -1,6 +1,7 @@
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::sync::Arc;
 
 use assemblylift_core::wasm;
 use assemblylift_generator::context::Function;
@@ -8,10 +9,10 @@ use assemblylift_generator::projectfs::Project;
 
 use crate::commands::cast::CastableFunction;
 
-use super::CompileStatus;
+use super::{precompile_component, CompileStatus};
 
 pub struct RustFunction {
-    project: Rc<Project>,
+    project: Arc<Project>,
     service_name: String,
     function_name: String,
     net_path: String,
@@ -22,7 +23,7 @@ pub struct RustFunction {
 }
 
 impl RustFunction {
-    pub fn new(function: &Function, project: Rc<Project>) -> Self {
+    pub fn new(function: &Function, project: Arc<Project>) -> Self {
         let service_name = function.service_name.clone();
         let net_path = project
             .net_dir()
@@ -72,6 +73,64 @@ impl RustFunction {
     }
 }
 
+/// The directories of the local (`path = ...`) crates the package at `manifest_path` depends on, directly or
+/// transitively, as resolved by `cargo metadata`
+fn path_dependencies(manifest_path: &Path) -> Result<Vec<PathBuf>, String> {
+    let output = std::process::Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--manifest-path"])
+        .arg(manifest_path)
+        .output()
+        .map_err(|e| format!("unable to run cargo metadata: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "unable to read the dependencies of {}:\n{}",
+            manifest_path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("unable to parse cargo metadata: {}", e))?;
+
+    // Walk the resolve graph from the root so that other members of a shared workspace are left out
+    let nodes: HashMap<&str, Vec<&str>> = metadata["resolve"]["nodes"]
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter_map(|node| {
+                    let deps = node["dependencies"]
+                        .as_array()?
+                        .iter()
+                        .filter_map(|dep| dep.as_str())
+                        .collect();
+                    Some((node["id"].as_str()?, deps))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<&str> = metadata["resolve"]["root"].as_str().into_iter().collect();
+    while let Some(id) = pending.pop() {
+        if reachable.insert(id) {
+            pending.extend(nodes.get(id).into_iter().flatten());
+        }
+    }
+
+    Ok(metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|package| package["source"].is_null())
+        .filter(|package| {
+            package["id"]
+                .as_str()
+                .is_some_and(|id| reachable.contains(id))
+        })
+        .filter_map(|package| package["manifest_path"].as_str())
+        .filter_map(|manifest| Path::new(manifest).parent().map(Path::to_path_buf))
+        .collect())
+}
+
 impl CastableFunction for RustFunction {
     fn compile(&self, wasi_snapshot_preview1: Vec<u8>) -> Result<CompileStatus, String> {
         let manifest_path = PathBuf::from(format!(
@@ -93,45 +152,59 @@ impl CastableFunction for RustFunction {
             .arg("--target")
             .arg(self.target.clone())
             .output()
-            .unwrap();
+            .map_err(|e| format!("unable to run cargo: {}", e))?;
 
-        let build_log = std::str::from_utf8(&cargo_build.stderr).unwrap();
-        if cargo_build.status.code().unwrap() != 0 {
+        if !cargo_build.status.success() {
             return Err(format!(
                 "Unable to compile function {}:\n{}",
-                &self.function_name, build_log
+                &self.function_name,
+                String::from_utf8_lossy(&cargo_build.stderr)
             ));
         }
 
         let mut component_path = PathBuf::from(self.source_wasm_path());
         component_path.set_extension("component.wasm");
-        {
-            let module = std::fs::read(self.source_wasm_path()).unwrap();
-            let component = wasm::make_wasi_component(module, wasi_snapshot_preview1.as_slice())
-                .expect("unable to make component of the provided module");
-            std::fs::write(component_path.clone(), component).unwrap();
-        }
+        let module = std::fs::read(self.source_wasm_path()).map_err(|e| e.to_string())?;
+        let component = wasm::make_wasi_component(module, wasi_snapshot_preview1.as_slice())
+            .map_err(|e| format!("unable to make component of the provided module: {}", e))?;
+        let changed = std::fs::read(&component_path)
+            .map(|previous| previous != component)
+            .unwrap_or(true);
+        std::fs::write(&component_path, component).map_err(|e| e.to_string())?;
+
+        Ok(CompileStatus { wasm_path: component_path, changed })
+    }
 
-        Ok(CompileStatus { wasm_path: component_path.clone(), changed: true })
+    fn sources(&self) -> Result<Vec<PathBuf>, String> {
+        let function_dir = self
+            .project
+            .service_dir(self.service_name.clone())
+            .function_dir(self.function_name.clone());
+        let mut sources = vec![function_dir.clone()];
+        // Functions in a Cargo workspace share the lockfile at the project root
+        let workspace_lock = self.project.dir().join("Cargo.lock");
+        if workspace_lock.exists() {
+            sources.push(workspace_lock);
+        }
+        for dir in path_dependencies(&function_dir.join("Cargo.toml"))? {
+            if !dir.starts_with(&function_dir) {
+                sources.push(dir);
+            }
+        }
+        Ok(sources)
     }
 
-    fn compose(&self) {
-        todo!()
+    fn toolchain(&self) -> String {
+        format!("rust-{}-{}", self.target, self.mode)
     }
 
-    fn precompile(&self, target: Option<&str>) {
+    fn precompile(&self, component: &Path, target: Option<&str>) -> Result<(), String> {
         println!("⚡️ > Precompiling function `{}`...", &self.function_name);
-        let mut path = PathBuf::from(self.source_wasm_path());
-        path.set_extension("component.wasm");
-        let bytes = wasm::precompile(
-            Path::new(&path),
-            &target.unwrap_or("x86_64-linux-gnu"),
-            &self.cpu_compat_mode.clone(),
+        precompile_component(
+            component,
+            target.unwrap_or("x86_64-linux-gnu"),
+            &self.cpu_compat_mode,
         )
-        .unwrap();
-        path.set_extension("wasm.bin");
-        std::fs::write(&path, bytes).unwrap();
-        println!("📄 > Wrote {}", path.to_str().unwrap());
     }
 
     fn artifact_path(&self) -> PathBuf {
@@ -5,7 +5,8 @@ use handlebars::to_json;
 use serde_json::value::{Map, Value as Json};
 
 use crate::templates::project::{
-    RUBY_FUNCTION_DOCUMENTS, RUST_FUNCTION_DOCUMENTS, SERVICE_DOCUMENTS,
+    JAVASCRIPT_FUNCTION_DOCUMENTS, PYTHON_FUNCTION_DOCUMENTS, RUBY_FUNCTION_DOCUMENTS,
+    RUST_FUNCTION_DOCUMENTS, SERVICE_DOCUMENTS,
 };
 use crate::templates::write_documents;
 
@@ -57,7 +58,11 @@ pub fn command(matches: Option<&ArgMatches>) {
         }
 
         Some("function") => {
-            let language = matches.value_of("language").unwrap_or("rust");
+            let language = match matches.value_of("language").unwrap_or("rust") {
+                "py" => "python",
+                "js" | "ts" | "typescript" => "javascript",
+                lang => lang,
+            };
             let resource_name = resource_name.unwrap().to_string();
             let function_name: Vec<&str> = resource_name.split(".").collect();
             if function_name.len() != 2 {
@@ -90,6 +95,24 @@ pub fn command(matches: Option<&ArgMatches>) {
                         &mut Map::<String, Json>::new(),
                     );
                 }
+                "python" => {
+                    let path = project
+                        .service_dir(String::from(function_name[0]))
+                        .function_dir(String::from(function_name[1]));
+                    write_documents(
+                        &path,
+                        (*PYTHON_FUNCTION_DOCUMENTS).clone().as_ref(),
+                        &mut Map::<String, Json>::new(),
+                    );
+                }
+                "javascript" => {
+                    let data = &mut Map::<String, Json>::new();
+                    data.insert("function_name".to_string(), to_json(function_name[1]));
+                    let path = project
+                        .service_dir(String::from(function_name[0]))
+                        .function_dir(String::from(function_name[1]));
+                    write_documents(&path, (*JAVASCRIPT_FUNCTION_DOCUMENTS).clone().as_ref(), data);
+                }
                 lang => panic!("function language `{}` is not supported", lang),
             }
         }
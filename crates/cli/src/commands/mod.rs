@@ -7,5 +7,7 @@ pub mod make;
 pub mod r#move;
 pub mod nuke;
 pub mod pack;
+pub mod providers;
 pub mod push;
 pub mod user;
+pub mod validate;
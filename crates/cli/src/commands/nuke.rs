@@ -1,8 +1,10 @@
+use assemblylift_generator::projectfs::infra_dir;
 use assemblylift_tools::terraform;
 use clap::ArgMatches;
 use dialoguer::Confirm;
 
-pub fn command(_matches: Option<&ArgMatches>) {
+pub fn command(matches: Option<&ArgMatches>) {
+    let env = matches.and_then(|m| m.value_of("env"));
     if Confirm::new()
         .with_prompt(
             "Are you sure you want to destroy ALL provisioned infrastructure?\nThis is PERMANENT!",
@@ -10,7 +12,9 @@ pub fn command(_matches: Option<&ArgMatches>) {
         .interact()
         .unwrap()
     {
-        let tf = terraform::Terraform::default();
-        tf.destroy();
+        match terraform::Terraform::try_default() {
+            Ok(tf) => tf.with_dir(infra_dir(env).to_str().unwrap()).destroy(),
+            Err(e) => println!("❌  {}", e),
+        }
     }
 }
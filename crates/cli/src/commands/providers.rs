@@ -0,0 +1,70 @@
+use assemblylift_generator::providers::{OptionSpec, ProviderFactory};
+use assemblylift_generator::Options;
+use clap::ArgMatches;
+
+pub fn command(matches: Option<&ArgMatches>) {
+    let matches = match matches {
+        Some(matches) => matches,
+        _ => panic!("could not get matches for providers command"),
+    };
+
+    let names = match matches.value_of("provider") {
+        Some(name) => vec![name.to_string()],
+        None => ProviderFactory::provider_names(),
+    };
+
+    for name in names {
+        let provider = match ProviderFactory::new_provider(&name, Options::new(), None) {
+            Ok(provider) => provider,
+            Err(e) => {
+                eprintln!("❌  {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let mut roles = Vec::new();
+        if provider.as_service_provider().is_ok() {
+            roles.push("service");
+        }
+        if provider.as_function_provider().is_ok() {
+            roles.push("function");
+        }
+        if provider.as_gateway_provider().is_ok() {
+            roles.push("gateway");
+        }
+        if provider.as_dns_provider().is_ok() {
+            roles.push("dns");
+        }
+        if provider.as_container_registry_provider().is_ok() {
+            roles.push("registry");
+        }
+
+        println!("{}", provider.name());
+        println!("  roles: {}", roles.join(", "));
+        println!(
+            "  platforms: {}",
+            provider.compatible_platforms().join(", ")
+        );
+        print_specs("options", &provider.option_specs());
+        print_specs("platform options", &provider.platform_option_specs());
+        println!();
+    }
+}
+
+fn print_specs(heading: &str, specs: &[OptionSpec]) {
+    if specs.is_empty() {
+        return;
+    }
+    println!("  {}:", heading);
+    for spec in specs {
+        let mut attributes = vec![spec.r#type.describe()];
+        if spec.required {
+            attributes.push("required".into());
+        }
+        if let Some(default) = &spec.default {
+            attributes.push(format!("default {}", default));
+        }
+        println!("    {} ({})", spec.name, attributes.join("; "));
+        println!("        {}", spec.description);
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use assemblylift_generator::projectfs::Project;
+use assemblylift_generator::toml;
+use clap::ArgMatches;
+
+pub fn command(matches: Option<&ArgMatches>) {
+    let matches = match matches {
+        Some(matches) => matches,
+        _ => panic!("could not get matches for validate command"),
+    };
+
+    let cwd = std::env::current_dir().unwrap();
+    if !validate_project(&cwd, matches.value_of("env")) {
+        std::process::exit(1);
+    }
+    println!("✅  No problems found");
+}
+
+/// Validate the project in `dir`, printing each problem found. Returns true if the project is valid.
+pub fn validate_project(dir: &Path, env: Option<&str>) -> bool {
+    let manifest = match toml::asml::Manifest::read(&dir.join("assemblylift.toml")) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("❌  {}", e);
+            return false;
+        }
+    };
+    let project = Project::new(manifest.project.name.clone(), Some(dir.to_path_buf()));
+
+    let errors = assemblylift_generator::validate::validate(&project, env);
+    for e in &errors {
+        eprintln!("❌  {}", e);
+    }
+    errors.is_empty()
+}
@@ -2,12 +2,32 @@ extern crate serde_json;
 
 use clap::{crate_version, App, AppSettings, Arg};
 
-use crate::commands::{bind, burn, cast, host, init, make, nuke, pack, push, r#move, user};
+use crate::commands::{bind, burn, cast, host, init, make, nuke, pack, providers, push, r#move, user, validate};
 
 mod archive;
 mod commands;
 mod templates;
 
+fn env_arg() -> Arg<'static, 'static> {
+    Arg::with_name("env")
+        .long("env")
+        .help("Name of an environment defined in assemblylift.toml")
+        .takes_value(true)
+}
+
+fn update_lock_arg() -> Arg<'static, 'static> {
+    Arg::with_name("update-lock")
+        .long("update-lock")
+        .help("Fetch tools & runtimes not yet pinned in asml-tools.lock, recording their SHA-256")
+}
+
+fn positive_integer(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(format!("`{}` is not a positive integer", value)),
+    }
+}
+
 fn main() {
     let app = App::new("asml")
         .version(crate_version!())
@@ -52,11 +72,50 @@ fn main() {
                     .required(true)
             )
         )
-        .subcommand(App::new("cast").about("Build the AssemblyLift application"))
+        .subcommand(
+            App::new("cast")
+                .about("Build the AssemblyLift application")
+                .after_help("RESOURCE SYNTAX:\n    asml cast service <service-name>\n    asml cast function <service-name>.<function-name>")
+                .arg(env_arg())
+                .arg(update_lock_arg())
+                .arg(
+                    Arg::with_name("resource")
+                        .multiple(true)
+                        .help("Build & plan only this service or function")
+                )
+                .arg(
+                    Arg::with_name("jobs")
+                        .short("j")
+                        .long("jobs")
+                        .help("Number of functions to build in parallel; defaults to the number of CPUs")
+                        .takes_value(true)
+                        .validator(positive_integer)
+                ),
+        )
+        .subcommand(
+            App::new("validate")
+                .about("Check the project & service manifests for errors")
+                .arg(env_arg()),
+        )
+        .subcommand(
+            App::new("providers")
+                .about("List the available providers & the options they accept")
+                .arg(Arg::with_name("provider").help("Show only this provider")),
+        )
         .subcommand(
             App::new("bind")
                 .about("Bind the application to the cloud backend")
-                .alias("sync"),
+                .alias("sync")
+                .arg(env_arg())
+                .arg(update_lock_arg())
+                .arg(
+                    Arg::with_name("target")
+                        .long("target")
+                        .help("Apply only the infrastructure of this service; may be repeated")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                ),
         )
         .subcommand(
             App::new("burn")
@@ -70,7 +129,9 @@ fn main() {
         )
         .subcommand(
             App::new("nuke")
-                .about("Destroy/de-provision ALL deployed infrastructure"),
+                .about("Destroy/de-provision ALL deployed infrastructure")
+                .arg(env_arg())
+                .arg(update_lock_arg()),
         )
         .subcommand(
             App::new("pack")
@@ -125,10 +186,17 @@ fn main() {
                 .about("Spawn a local development server")
         );
     let matches = app.setting(AppSettings::ArgRequiredElseHelp).get_matches();
+    if let (_, Some(sub_matches)) = matches.subcommand() {
+        if sub_matches.is_present("update-lock") {
+            assemblylift_tools::set_update_lock();
+        }
+    }
 
     match matches.subcommand() {
         ("init", matches) => init::command(matches),
         ("cast", matches) => cast::command(matches),
+        ("validate", matches) => validate::command(matches),
+        ("providers", matches) => providers::command(matches),
         ("bind", matches) => bind::command(matches),
         ("burn", matches) => burn::command(matches),
         ("make", matches) => make::command(matches),
@@ -0,0 +1,29 @@
+// Type declarations for the `asml` module, which the AssemblyLift JavaScript engine provides to functions.
+// It is a thin binding of the `asml-rt` & `asml-io` interfaces of the AssemblyLift WIT.
+
+declare module "asml" {
+    export type LogLevel = "debug" | "trace" | "info" | "warn" | "error";
+
+    /** The `asml-rt` interface */
+    export namespace rt {
+        /** The input the function was invoked with, as a UTF-8 string */
+        function getInput(): string;
+        /** Complete the invocation successfully with `response` */
+        function success(response: string): void;
+        /** Complete the invocation with an error `response` */
+        function failure(response: string): void;
+        function log(level: LogLevel, context: string, message: string): void;
+    }
+
+    /** The `asml-io` interface */
+    export namespace io {
+        type Ioid = number;
+
+        /** Invoke the IOmod call at `path` (e.g. `akkoro.std.http.request`), returning a handle to poll */
+        function invoke(path: string, input: string): Ioid;
+        /** The response of call `ioid`, or `null` if it is not ready yet */
+        function poll(ioid: Ioid): Uint8Array | null;
+        /** Invoke the IOmod call at `path` and resolve with its response once it is ready */
+        function call(path: string, input: string): Promise<Uint8Array>;
+    }
+}
@@ -58,9 +58,11 @@ provider = { name = "{{service_name}}" }
 
 #[[functions]]
 #name = "rusty-fn" # Must correspond to a function in the service's functions/ directory
-#language = "rust" # Kind of source code for function; one of "rust" or "ruby"
+#language = "rust" # Kind of source code for function; "rust", "ruby", "python", "javascript", or any other with a build
+#build = { command = "make", output = "handler.wasm" } # Required for any language but the above; how to build the function
 #http = { verb = "GET", path = "/rustyfn" } # HTTP route to the function from the Service's Gateway
 #environment = { var1 = "val1" } # Map of environment variables to pass to the function
+#components = ["components/shared.wasm"] # Components to compose into the function, relative to the project root
 "#;
 
 pub static SERVICE_DOCUMENTS: Lazy<Arc<Vec<Document>>> = Lazy::new(|| {
@@ -86,14 +88,12 @@ assemblylift-core-io-guest = { version = "0.4.0-beta" }
 static FUNCTION_MAIN_RS: &str = r#"use assemblylift_core_guest::*;
 
 #[handler]
-async fn main() {
-    // `ctx` is a value injected by the `handler` attribute macro
-    let event: serde_json::Value = match serde_json::from_slice(&ctx.input) {
-        Ok(val) => val,
-        Err(err) => return FunctionContext::failure(format!("could not parse function input as JSON: {}", err.to_string()));
-    };
-
-    FunctionContext::success("\"Function returned OK!\"".to_string());
+async fn main(ctx: FunctionContext) -> Result<serde_json::Value, String> {
+    // Returning `Ok` responds with the value serialized as JSON; `Err` responds with a failure
+    let _event: serde_json::Value = serde_json::from_slice(&ctx.input)
+        .map_err(|err| format!("could not parse function input as JSON: {}", err))?;
+
+    Ok(serde_json::json!("Function returned OK!"))
 }
 "#;
 
@@ -109,6 +109,55 @@ end
 main(JSON.parse(Asml.get_function_input()))
 "#;
 
+static FUNCTION_HANDLER_PY: &str = r#"import json
+
+import asml
+
+
+def main(input):
+    # TODO implement your function code here!
+    asml.success(json.dumps(input))
+
+
+main(json.loads(asml.get_function_input()))
+"#;
+
+static FUNCTION_ASML_PYI: &str = include_str!("python/asml.pyi");
+
+static FUNCTION_HANDLER_TS: &str = r#"import { rt } from "asml";
+
+function main(input: unknown) {
+    // TODO implement your function code here!
+    rt.success(JSON.stringify(input));
+}
+
+main(JSON.parse(rt.getInput()));
+"#;
+
+static FUNCTION_PACKAGE_JSON: &str = r#"{
+  "name": "{{function_name}}",
+  "private": true,
+  "type": "module",
+  "devDependencies": {
+    "typescript": "^5.3.0"
+  }
+}
+"#;
+
+static FUNCTION_TSCONFIG_JSON: &str = r#"{
+  "compilerOptions": {
+    "target": "es2020",
+    "module": "es2020",
+    "moduleResolution": "node",
+    "strict": true,
+    "noEmit": true
+  },
+  "include": ["*.ts"]
+}
+"#;
+
+static FUNCTION_ASML_D_TS: &str = include_str!("javascript/asml.d.ts");
+
 pub static RUST_FUNCTION_DOCUMENTS: Lazy<Arc<Vec<Document>>> = Lazy::new(|| {
     Arc::new(Vec::from([
         Document {
@@ -128,3 +177,37 @@ pub static RUBY_FUNCTION_DOCUMENTS: Lazy<Arc<Vec<Document>>> = Lazy::new(|| {
         document: String::from(FUNCTION_HANDLER_RB),
     }]))
 });
+
+pub static PYTHON_FUNCTION_DOCUMENTS: Lazy<Arc<Vec<Document>>> = Lazy::new(|| {
+    Arc::new(Vec::from([
+        Document {
+            file_name: "handler.py",
+            document: String::from(FUNCTION_HANDLER_PY),
+        },
+        Document {
+            file_name: "asml.pyi",
+            document: String::from(FUNCTION_ASML_PYI),
+        },
+    ]))
+});
+
+pub static JAVASCRIPT_FUNCTION_DOCUMENTS: Lazy<Arc<Vec<Document>>> = Lazy::new(|| {
+    Arc::new(Vec::from([
+        Document {
+            file_name: "handler.ts",
+            document: String::from(FUNCTION_HANDLER_TS),
+        },
+        Document {
+            file_name: "package.json",
+            document: String::from(FUNCTION_PACKAGE_JSON),
+        },
+        Document {
+            file_name: "tsconfig.json",
+            document: String::from(FUNCTION_TSCONFIG_JSON),
+        },
+        Document {
+            file_name: "asml.d.ts",
+            document: String::from(FUNCTION_ASML_D_TS),
+        },
+    ]))
+});
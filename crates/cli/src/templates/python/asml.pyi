@@ -0,0 +1,36 @@
+# Type stubs for the `asml` module, which is built into the AssemblyLift Python runtime.
+# It is a thin binding of the `asml-rt` & `asml-io` interfaces of the AssemblyLift WIT.
+
+from typing import Literal, Optional
+
+LogLevel = Literal["debug", "trace", "info", "warn", "error"]
+
+# asml-rt
+
+def get_function_input() -> str:
+    """The input the function was invoked with, as a UTF-8 string"""
+    ...
+
+def success(response: str) -> None:
+    """Complete the invocation successfully with `response`"""
+    ...
+
+def failure(response: str) -> None:
+    """Complete the invocation with an error `response`"""
+    ...
+
+def log(level: LogLevel, context: str, message: str) -> None: ...
+
+# asml-io
+
+def io_invoke(path: str, input: str) -> int:
+    """Invoke the IOmod call at `path` (e.g. `akkoro.std.http.request`), returning a handle to poll"""
+    ...
+
+def io_poll(ioid: int) -> Optional[bytes]:
+    """The response of call `ioid`, or `None` if it is not ready yet"""
+    ...
+
+def io_call(path: str, input: str) -> bytes:
+    """Invoke the IOmod call at `path` and block until its response is ready"""
+    ...
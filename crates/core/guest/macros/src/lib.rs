@@ -1,5 +1,5 @@
 use quote::{quote, quote_spanned};
-use syn::{parse2, ItemFn};
+use syn::{parse2, parse_macro_input, AttributeArgs, ItemFn, Lit, Meta, NestedMeta};
 
 #[proc_macro_attribute]
 pub fn handler(
@@ -9,7 +9,6 @@ pub fn handler(
     let input: ItemFn = parse2(stream.into()).expect("could not parse token stream");
     let block_statements = &input.block.stmts;
     let name = &input.sig.ident;
-    let _ret = &input.sig.output;
 
     if name != "main" {
         return proc_macro::TokenStream::from(quote_spanned! { name.span() =>
@@ -17,27 +16,112 @@ pub fn handler(
         });
     }
 
-    proc_macro::TokenStream::from(quote! {
+    let prelude = quote! {
         use assemblylift_core_guest::asml_rt;
-        use assemblylift_core_guest::direct_executor;
-        // use assemblylift_core_guest::export_command;
         use assemblylift_core_guest::FunctionContext;
-        // use assemblylift_core_guest::command::Command;
         use assemblylift_core_guest::wit_bindgen;
-        // struct Cmd;
-        // impl Command for Cmd {
-        //     fn run() -> Result<(), ()> {
-        //         Ok(__handler(FunctionContext { input: asml_rt::get_input() }))
-        //     }
-        // }
-        // export_command!(Cmd);
+    };
+
+    match input.sig.inputs.len() {
+        // `fn main()` or `async fn main()`, with `ctx` injected into the body
+        0 => (),
+        // `async fn main(ctx: FunctionContext) -> Result<T, E>`
+        1 => {
+            if input.sig.asyncness.is_none() {
+                return proc_macro::TokenStream::from(quote_spanned! { name.span() =>
+                    compile_error!("#[handler] main must be async when it takes a FunctionContext"),
+                });
+            }
+            let inputs = &input.sig.inputs;
+            let output = &input.sig.output;
+            let block = &input.block;
+            return proc_macro::TokenStream::from(quote! {
+                #prelude
+                async fn __handler(#inputs) #output #block
+                fn main() {
+                    assemblylift_core_guest::executor::install_panic_hook();
+                    let result = assemblylift_core_guest::executor::block_on(
+                        __handler(FunctionContext { input: asml_rt::get_input() })
+                    );
+                    assemblylift_core_guest::FunctionResult::respond(result);
+                }
+            });
+        }
+        _ => {
+            return proc_macro::TokenStream::from(quote_spanned! { name.span() =>
+                compile_error!("#[handler] main takes at most one argument, the FunctionContext"),
+            });
+        }
+    }
+
+    proc_macro::TokenStream::from(quote! {
+        #prelude
         fn __handler(ctx: FunctionContext) {
-            direct_executor::run_spinning(async {
+            let result = assemblylift_core_guest::executor::block_on(async {
                 #(#block_statements)*
             });
+            assemblylift_core_guest::FunctionResult::respond(result);
         }
         fn main() {
+            assemblylift_core_guest::executor::install_panic_hook();
             __handler(FunctionContext { input: asml_rt::get_input() })
         }
     })
 }
+
+/// Turn a function taking a `TestHarness` into a native test which runs the function component built
+/// by the current crate. The component path can be given with `#[asml_test(component = "...")]`.
+#[proc_macro_attribute]
+pub fn asml_test(
+    args: proc_macro::TokenStream,
+    stream: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let input: ItemFn = parse2(stream.into()).expect("could not parse token stream");
+    let attrs = &input.attrs;
+    let name = &input.sig.ident;
+    let inputs = &input.sig.inputs;
+    let ret = &input.sig.output;
+    let block = &input.block;
+
+    if inputs.len() != 1 {
+        return proc_macro::TokenStream::from(quote_spanned! { name.span() =>
+            compile_error!("#[asml_test] functions must take exactly one argument, the TestHarness"),
+        });
+    }
+
+    let mut component: Option<String> = None;
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("component") => {
+                if let Lit::Str(s) = nv.lit {
+                    component = Some(s.value());
+                }
+            }
+            _ => {
+                return proc_macro::TokenStream::from(quote_spanned! { name.span() =>
+                    compile_error!("unknown #[asml_test] argument; expected `component = \"...\"`"),
+                });
+            }
+        }
+    }
+
+    let harness = match component {
+        Some(path) => quote! { ::assemblylift_test_harness::TestHarness::new(#path) },
+        None => quote! {
+            ::assemblylift_test_harness::TestHarness::for_crate(
+                env!("CARGO_MANIFEST_DIR"),
+                env!("CARGO_PKG_NAME"),
+            )
+        },
+    };
+
+    proc_macro::TokenStream::from(quote! {
+        #[test]
+        #(#attrs)*
+        fn #name() #ret {
+            fn __asml_test(#inputs) #ret #block
+            __asml_test(#harness)
+        }
+    })
+}
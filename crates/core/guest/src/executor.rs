@@ -0,0 +1,217 @@
+//! A single-threaded executor for function handlers, and the reactor which drives IOmod calls.
+//!
+//! An `Io` future which isn't ready registers its waker with the reactor. Whenever the handler future
+//! is pending, the executor turns the reactor, which polls the host for each in-flight call and wakes
+//! the task waiting on any call which has completed. Only woken tasks are polled again, so any number
+//! of `Io` futures can be awaited concurrently (e.g. with `futures::join!`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use crate::asml_io;
+use crate::asml_io::PollError;
+
+thread_local! {
+    static REACTOR: RefCell<Reactor> = RefCell::new(Reactor::default());
+}
+
+#[derive(Default)]
+struct Reactor {
+    pending: HashMap<u32, Waker>,
+    ready: HashMap<u32, Result<Vec<u8>, PollError>>,
+}
+
+/// Wake `waker` once the IOmod call `ioid` completes
+pub fn register_io(ioid: u32, waker: Waker) {
+    REACTOR.with(|r| r.borrow_mut().pending.insert(ioid, waker));
+}
+
+/// Take the result of IOmod call `ioid`, if the reactor has seen it complete or fail
+pub fn take_io(ioid: u32) -> Option<Result<Vec<u8>, PollError>> {
+    REACTOR.with(|r| r.borrow_mut().ready.remove(&ioid))
+}
+
+/// Poll the host for every in-flight IOmod call, waking the tasks of those which have completed.
+/// Returns the number of calls which completed.
+fn turn() -> usize {
+    turn_with(asml_io::poll)
+}
+
+fn turn_with<P>(poll: P) -> usize
+where
+    P: Fn(u32) -> Result<Vec<u8>, PollError>,
+{
+    let pending: Vec<u32> = REACTOR.with(|r| r.borrow().pending.keys().cloned().collect());
+    let mut completed = 0;
+    for ioid in pending {
+        // Anything but `NotReady` completes the call; an error (e.g. an unknown ioid) will never resolve
+        let result = match poll(ioid) {
+            Err(PollError::NotReady) => continue,
+            result => result,
+        };
+        let waker = REACTOR.with(|r| {
+            let mut r = r.borrow_mut();
+            r.ready.insert(ioid, result);
+            r.pending.remove(&ioid)
+        });
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        completed += 1;
+    }
+    completed
+}
+
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Run `future` to completion on the current thread
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    block_on_with(future, turn)
+}
+
+fn block_on_with<F, T>(future: F, turn: T) -> F::Output
+where
+    F: Future,
+    T: Fn() -> usize,
+{
+    let mut future = Box::pin(future);
+    let task = Arc::new(TaskWaker {
+        woken: AtomicBool::new(true),
+    });
+    let waker = Waker::from(task.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if task.woken.swap(false, Ordering::SeqCst) {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+
+        let waiting_on_io = REACTOR.with(|r| !r.borrow().pending.is_empty());
+        if waiting_on_io {
+            if turn() == 0 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        } else if !task.woken.load(Ordering::SeqCst) {
+            // There are no other threads or timers in a function, so nothing is left to wake the task
+            panic!("handler is pending, but is not waiting on an IOmod call");
+        }
+    }
+}
+
+/// Report panics to the runtime as a function failure, and exit rather than trapping
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = format!("function panicked: {}", info);
+        crate::asml_rt::log(crate::asml_rt::LogLevel::Error, "panic", &message);
+        crate::asml_rt::failure(&message.into_bytes());
+        std::process::exit(1);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use super::*;
+
+    /// Pending until `ioid` completes, then resolves to its result
+    struct TestIo(u32);
+
+    impl Future for TestIo {
+        type Output = Result<Vec<u8>, PollError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            match take_io(self.0) {
+                Some(result) => Poll::Ready(result),
+                None => {
+                    register_io(self.0, cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    /// Pending once, waking itself
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            match self.0 {
+                true => Poll::Ready(()),
+                false => {
+                    self.0 = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    /// Pending forever, without waking
+    struct Stuck;
+
+    impl Future for Stuck {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn completed_call_wakes_task() {
+        let output = block_on_with(TestIo(1), || turn_with(|_| Ok(b"done".to_vec())));
+        assert_eq!(output.unwrap(), b"done");
+    }
+
+    #[test]
+    fn calls_complete_independently() {
+        let polls = RefCell::new(0);
+        let output = block_on_with(async { (TestIo(1).await, TestIo(2).await) }, || {
+            turn_with(|ioid| {
+                *polls.borrow_mut() += 1;
+                match (ioid, *polls.borrow() > 2) {
+                    (2, false) => Err(PollError::NotReady),
+                    _ => Ok(vec![ioid as u8]),
+                }
+            })
+        });
+        assert_eq!(output.0.unwrap(), vec![1]);
+        assert_eq!(output.1.unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn failed_call_is_not_pending() {
+        let output = block_on_with(TestIo(7), || turn_with(|_| Err(PollError::InvalidIoid)));
+        assert!(matches!(output, Err(PollError::InvalidIoid)));
+        assert!(REACTOR.with(|r| r.borrow().pending.is_empty()));
+    }
+
+    #[test]
+    fn self_waking_future_is_polled_again() {
+        block_on_with(YieldOnce(false), || 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "not waiting on an IOmod call")]
+    fn future_pending_on_nothing_panics() {
+        block_on_with(Stuck, || 0);
+    }
+}
@@ -9,11 +9,12 @@ pub use wit_bindgen;
 
 pub use assemblylift::akkoro::assemblylift::asml_io;
 pub use assemblylift::akkoro::assemblylift::asml_rt;
-pub use assemblylift_core_guest_macros::handler;
+pub use assemblylift_core_guest_macros::{asml_test, handler};
 // pub use command::wasi;
 
 pub mod assemblylift;
 // pub mod command;
+pub mod executor;
 pub mod jwt;
 pub mod opa;
 pub mod secrets;
@@ -37,6 +38,30 @@ impl FunctionContext {
     }
 }
 
+/// The return type of an `async fn main` handler, which is reported to the runtime when the handler completes
+pub trait FunctionResult {
+    fn respond(self);
+}
+
+impl FunctionResult for () {
+    fn respond(self) {}
+}
+
+/// `Ok` is serialized as JSON and sent via `success`; `Err` is sent via `failure` as its message
+impl<T: Serialize, E: fmt::Display> FunctionResult for Result<T, E> {
+    fn respond(self) {
+        match self {
+            Ok(response) => match serde_json::to_vec(&response) {
+                Ok(bytes) => asml_rt::success(&bytes),
+                Err(why) => asml_rt::failure(
+                    format!("could not serialize function response: {}", why).as_bytes(),
+                ),
+            },
+            Err(err) => asml_rt::failure(err.to_string().as_bytes()),
+        }
+    }
+}
+
 pub type StatusCode = u16;
 #[derive(Serialize, Deserialize)]
 pub struct HttpResponse {
@@ -0,0 +1,260 @@
+//! The machine-readable interface an IOmod publishes in the `[interface]` section of its `iomod.toml`.
+//!
+//! ```toml
+//! [interface.calls.get_secret_value]
+//! input = "GetSecretValueRequest"
+//! output = "GetSecretValueResponse"
+//!
+//! [interface.types.GetSecretValueRequest]
+//! secret_id = "string"
+//! version_id = "string?"
+//!
+//! [interface.types.GetSecretValueResponse]
+//! secret_string = "string?"
+//! version_stages = "[string]"
+//! ```
+//!
+//! Field types are one of `string`, `bool`, `i64`, `u64`, `f64`, `bytes`, `json` (any JSON value), or the
+//! name of another type in `[interface.types]`. A type can be made a list with `[T]`, or optional with `T?`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Interface {
+    #[serde(default)]
+    pub calls: BTreeMap<String, CallSpec>,
+    #[serde(default)]
+    pub types: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CallSpec {
+    pub input: String,
+    pub output: String,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeRef {
+    String,
+    Bool,
+    I64,
+    U64,
+    F64,
+    Bytes,
+    Json,
+    List(Box<TypeRef>),
+    Optional(Box<TypeRef>),
+    Named(String),
+}
+
+impl FromStr for TypeRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(inner) = s.strip_suffix('?') {
+            return Ok(TypeRef::Optional(Box::new(inner.parse()?)));
+        }
+        if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            return Ok(TypeRef::List(Box::new(inner.parse()?)));
+        }
+        match s {
+            "string" => Ok(TypeRef::String),
+            "bool" => Ok(TypeRef::Bool),
+            "i64" => Ok(TypeRef::I64),
+            "u64" => Ok(TypeRef::U64),
+            "f64" => Ok(TypeRef::F64),
+            "bytes" => Ok(TypeRef::Bytes),
+            "json" => Ok(TypeRef::Json),
+            name if is_name(name) => Ok(TypeRef::Named(name.to_string())),
+            other => Err(format!("invalid type `{}`", other)),
+        }
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeRef::String => write!(f, "string"),
+            TypeRef::Bool => write!(f, "bool"),
+            TypeRef::I64 => write!(f, "i64"),
+            TypeRef::U64 => write!(f, "u64"),
+            TypeRef::F64 => write!(f, "f64"),
+            TypeRef::Bytes => write!(f, "bytes"),
+            TypeRef::Json => write!(f, "json"),
+            TypeRef::List(inner) => write!(f, "[{}]", inner),
+            TypeRef::Optional(inner) => write!(f, "{}?", inner),
+            TypeRef::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Interface {
+    /// Check that every call, type and field has a valid name and refers to a valid type
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |context: &str, ty: &str| -> Result<(), String> {
+            let ty: TypeRef = ty.parse().map_err(|e| format!("{}: {}", context, e))?;
+            match ty.named() {
+                Some(name) if !self.types.contains_key(name) => Err(format!(
+                    "{}: type `{}` is not defined in [interface.types]",
+                    context, name
+                )),
+                _ => Ok(()),
+            }
+        };
+
+        let check_name = |context: &str, name: &str| -> Result<(), String> {
+            match is_name(name) {
+                true => Ok(()),
+                false => Err(format!("{}: `{}` is not a valid name", context, name)),
+            }
+        };
+
+        for (name, call) in &self.calls {
+            check_name("call", name)?;
+            check(&format!("call `{}` input", name), &call.input)?;
+            check(&format!("call `{}` output", name), &call.output)?;
+        }
+        for (name, fields) in &self.types {
+            check_name("type", name)?;
+            for (field, ty) in fields {
+                check_name(&format!("field of `{}`", name), field)?;
+                check(&format!("field `{}.{}`", name, field), ty)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TypeRef {
+    /// The name of the interface type this refers to, if any
+    pub fn named(&self) -> Option<&str> {
+        match self {
+            TypeRef::List(inner) | TypeRef::Optional(inner) => inner.named(),
+            TypeRef::Named(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `name` can name a call, type or field. Rust keywords such as `type` are allowed, as the
+/// generated code uses raw identifiers for them, except for those which cannot be raw.
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && !matches!(name, "_" | "self" | "Self" | "super" | "crate")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Interface {
+        #[derive(Deserialize)]
+        struct Manifest {
+            interface: Interface,
+        }
+        toml::from_str::<Manifest>(toml).unwrap().interface
+    }
+
+    #[test]
+    fn parse_type_refs() {
+        assert_eq!("string".parse(), Ok(TypeRef::String));
+        assert_eq!(" u64 ".parse(), Ok(TypeRef::U64));
+        assert_eq!(
+            "[string]".parse(),
+            Ok(TypeRef::List(Box::new(TypeRef::String)))
+        );
+        assert_eq!(
+            "Item?".parse(),
+            Ok(TypeRef::Optional(Box::new(TypeRef::Named("Item".into()))))
+        );
+        assert_eq!(
+            "[Item]?".parse(),
+            Ok(TypeRef::Optional(Box::new(TypeRef::List(Box::new(
+                TypeRef::Named("Item".into())
+            )))))
+        );
+        assert_eq!(
+            "[[bytes]]".parse::<TypeRef>().unwrap().to_string(),
+            "[[bytes]]"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_type_refs() {
+        for ty in ["", "[string", "1Item", "Item-Name", "map<string>", "self"] {
+            assert!(ty.parse::<TypeRef>().is_err(), "{:?} should not parse", ty);
+        }
+    }
+
+    #[test]
+    fn named_type() {
+        assert_eq!("[Item]?".parse::<TypeRef>().unwrap().named(), Some("Item"));
+        assert_eq!("[string]".parse::<TypeRef>().unwrap().named(), None);
+    }
+
+    #[test]
+    fn validate_interface() {
+        let interface = parse(
+            r#"
+            [interface.calls.get_item]
+            input = "GetItem"
+            output = "Item?"
+
+            [interface.types.GetItem]
+            type = "string"
+
+            [interface.types.Item]
+            tags = "[string]"
+            "#,
+        );
+        assert_eq!(interface.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_undefined_type() {
+        let interface = parse(
+            r#"
+            [interface.calls.get_item]
+            input = "GetItem"
+            output = "string"
+            "#,
+        );
+        assert_eq!(
+            interface.validate(),
+            Err("call `get_item` input: type `GetItem` is not defined in [interface.types]".into())
+        );
+    }
+
+    #[test]
+    fn validate_rejects_invalid_field() {
+        let interface = parse(
+            r#"
+            [interface.types.Item]
+            self = "string"
+            "#,
+        );
+        assert_eq!(
+            interface.validate(),
+            Err("field of `Item`: `self` is not a valid name".into())
+        );
+
+        let interface = parse(
+            r#"
+            [interface.types.Item]
+            tags = "[strin]"
+            "#,
+        );
+        assert!(interface
+            .validate()
+            .unwrap_err()
+            .starts_with("field `Item.tags`"));
+    }
+}
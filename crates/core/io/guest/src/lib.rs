@@ -1,18 +1,17 @@
 use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
-use std::task::{Context, Poll, Waker};
+use std::task::{Context, Poll};
 
 use serde::{de::DeserializeOwned, Deserialize};
 
 use assemblylift_core_guest::asml_rt::LogLevel;
-use assemblylift_core_guest::{asml_io, asml_rt};
+use assemblylift_core_guest::{asml_io, asml_rt, executor};
 
 #[derive(Clone)]
 /// A handle implementing `std::future::Future` for an in-flight IOmod call
 pub struct Io<'a, R> {
     pub id: u32,
-    waker: Box<Option<Waker>>,
     _phantom: PhantomData<&'a R>,
 }
 
@@ -20,7 +19,6 @@ impl<'a, R: Deserialize<'a>> Io<'_, R> {
     pub fn new(id: u32) -> Self {
         Io {
             id,
-            waker: Box::new(None),
             _phantom: PhantomData,
         }
     }
@@ -32,15 +30,22 @@ where
 {
     type Output = R;
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match asml_io::poll(self.id) {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let response = match executor::take_io(self.id) {
+            Some(res) => res,
+            None => asml_io::poll(self.id),
+        };
+        match response {
             Ok(res) => Poll::Ready(
                 read_response::<Self::Output>(std::str::from_utf8(&*res).unwrap()).unwrap(),
             ),
-            _ => {
-                self.waker = Box::new(Some(cx.waker().clone()));
+            Err(asml_io::PollError::NotReady) => {
+                // the executor's reactor wakes us once the call completes
+                executor::register_io(self.id, cx.waker().clone());
                 Poll::Pending
             }
+            // Reported to the runtime as a function failure by the panic hook
+            Err(err) => panic!("IOmod call {} failed: {}", self.id, err),
         }
     }
 }
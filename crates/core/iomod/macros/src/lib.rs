@@ -0,0 +1,275 @@
+//! Generate both ends of an IOmod from the `[interface]` it publishes in its `iomod.toml`:
+//! `iomod_client!` produces typed guest functions over `asml_io::invoke`, and `iomod_dispatch!` produces
+//! the host-side `CallMap` for the `iomod!` macro. Both share the same generated `structs` module, so a
+//! change to the interface which isn't reflected on either end fails to compile.
+
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use serde::Deserialize;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Ident, LitStr, Path, Token};
+
+use assemblylift_core_io_common::interface::{Interface, TypeRef};
+
+#[derive(Deserialize)]
+struct Manifest {
+    iomod: Header,
+    interface: Option<Interface>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    coordinates: String,
+}
+
+struct ManifestArgs {
+    path: LitStr,
+    handlers: Option<Path>,
+}
+
+impl Parse for ManifestArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let handlers = match input.parse::<Option<Token![,]>>()? {
+            Some(_) => Some(input.parse()?),
+            None => None,
+        };
+        Ok(Self { path, handlers })
+    }
+}
+
+/// Generate a typed guest client for the IOmod described by the `iomod.toml` at the given path
+/// (relative to the crate's `Cargo.toml`).
+///
+/// ```ignore
+/// iomod_client!("iomod.toml");
+///
+/// let response = get_secret_value(structs::GetSecretValueRequest { .. }).await;
+/// ```
+#[proc_macro]
+pub fn iomod_client(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(input as ManifestArgs);
+    let (manifest, path) = match read_manifest(&args.path) {
+        Ok(m) => m,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let interface = manifest.interface.unwrap_or_default();
+    let structs = match structs(&interface) {
+        Ok(s) => s,
+        Err(err) => return syn::Error::new(args.path.span(), err).to_compile_error().into(),
+    };
+    let coordinates = &manifest.iomod.coordinates;
+
+    let mut calls = Vec::new();
+    for (name, call) in &interface.calls {
+        let ident = to_ident(name);
+        let input_ty = rust_type(&call.input.parse().unwrap());
+        let output_ty = rust_type(&call.output.parse().unwrap());
+        let doc = call.description.clone().unwrap_or_default();
+        calls.push(quote! {
+            #[doc = #doc]
+            pub fn #ident<'a>(input: #input_ty) -> ::assemblylift_core_io_guest::Io<'a, #output_ty> {
+                let method_path = format!("{}.{}", COORDINATES, #name);
+                let input = ::serde_json::to_string(&input).expect("could not serialize IOmod call input");
+                match ::assemblylift_core_guest::asml_io::invoke(&method_path, &input) {
+                    Ok(ioid) => ::assemblylift_core_io_guest::Io::new(ioid),
+                    Err(err) => panic!("unable to invoke {}: {:?}", method_path, err),
+                }
+            }
+        });
+    }
+
+    proc_macro::TokenStream::from(quote! {
+        const _: &str = include_str!(#path);
+        pub const COORDINATES: &str = #coordinates;
+        #structs
+        #(#calls)*
+    })
+}
+
+/// Generate the host-side dispatch table for the IOmod described by the `iomod.toml` at the given path.
+/// Each call in the interface is routed to the async function of the same name in the `handlers` module,
+/// which must take the call's input type and return its output type.
+///
+/// ```ignore
+/// iomod_dispatch!("iomod.toml", handlers);
+///
+/// iomod!("127.0.0.1", akkoro.aws.secretsmanager => call_map);
+/// ```
+#[proc_macro]
+pub fn iomod_dispatch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(input as ManifestArgs);
+    let handlers = match args.handlers {
+        Some(h) => h,
+        None => {
+            return syn::Error::new(
+                args.path.span(),
+                "expected the path of the module implementing the calls, e.g. `iomod_dispatch!(\"iomod.toml\", handlers)`",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+    let (manifest, path) = match read_manifest(&args.path) {
+        Ok(m) => m,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let interface = manifest.interface.unwrap_or_default();
+    let structs = match structs(&interface) {
+        Ok(s) => s,
+        Err(err) => return syn::Error::new(args.path.span(), err).to_compile_error().into(),
+    };
+
+    let mut wrappers = Vec::new();
+    let mut inserts = Vec::new();
+    for (name, call) in &interface.calls {
+        let ident = to_ident(name);
+        let wrapper = format_ident!("__dispatch_{}", name);
+        let input_ty = rust_type(&call.input.parse().unwrap());
+        wrappers.push(quote! {
+            fn #wrapper<'a>(input: Vec<u8>) -> ::futures::future::BoxFuture<'a, Vec<u8>> {
+                Box::pin(async move {
+                    let input: #input_ty = match ::serde_json::from_slice(&input) {
+                        Ok(input) => input,
+                        Err(err) => {
+                            return ::serde_json::to_vec(&::serde_json::json!({
+                                "error": format!("invalid input for {}: {}", #name, err)
+                            }))
+                            .unwrap()
+                        }
+                    };
+                    let output = #handlers::#ident(input).await;
+                    ::serde_json::to_vec(&output).expect("could not serialize IOmod call output")
+                })
+            }
+        });
+        inserts.push(quote! {
+            call_map.map.insert(#name, ::assemblylift_core_iomod::CallPtr::new(#wrapper));
+        });
+    }
+
+    proc_macro::TokenStream::from(quote! {
+        const _: &str = include_str!(#path);
+        #structs
+        #(#wrappers)*
+        pub fn call_map<'a>() -> ::assemblylift_core_iomod::CallMap<'a> {
+            let mut call_map = ::assemblylift_core_iomod::CallMap::new();
+            #(#inserts)*
+            call_map
+        }
+    })
+}
+
+fn read_manifest(path: &LitStr) -> syn::Result<(Manifest, String)> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+    let contents = std::fs::read_to_string(&full_path).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("could not read {}: {}", full_path.display(), e),
+        )
+    })?;
+    let manifest: Manifest = toml::from_str(&contents).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("could not parse {}: {}", full_path.display(), e),
+        )
+    })?;
+    Ok((manifest, full_path.display().to_string()))
+}
+
+/// The identifier for a name from a validated interface, raw if the name is a keyword (e.g. `r#type`)
+fn to_ident(name: &str) -> Ident {
+    match syn::parse_str::<Ident>(name) {
+        Ok(ident) => ident,
+        Err(_) => Ident::new_raw(name, Span::call_site()),
+    }
+}
+
+/// The `structs` module holding a struct for each type in the interface
+fn structs(interface: &Interface) -> Result<TokenStream, String> {
+    interface.validate()?;
+    let mut structs = Vec::new();
+    for (name, fields) in &interface.types {
+        let ident = to_ident(name);
+        let fields = fields.iter().map(|(field, ty)| {
+            let field_ident = to_ident(field);
+            let ty: TypeRef = ty.parse().unwrap();
+            let rust_ty = rust_type_in(&ty, quote! { super::structs });
+            match ty {
+                TypeRef::Optional(_) => quote! {
+                    #[serde(default, skip_serializing_if = "Option::is_none")]
+                    pub #field_ident: #rust_ty
+                },
+                _ => quote! { pub #field_ident: #rust_ty },
+            }
+        });
+        structs.push(quote! {
+            #[derive(::serde::Serialize, ::serde::Deserialize, Clone, Debug)]
+            pub struct #ident {
+                #(#fields),*
+            }
+        });
+    }
+    Ok(quote! {
+        pub mod structs {
+            #(#structs)*
+        }
+    })
+}
+
+fn rust_type(ty: &TypeRef) -> TokenStream {
+    rust_type_in(ty, quote! { structs })
+}
+
+/// The Rust type for `ty`, with interface types referred to through `structs`
+fn rust_type_in(ty: &TypeRef, structs: TokenStream) -> TokenStream {
+    match ty {
+        TypeRef::String => quote! { String },
+        TypeRef::Bool => quote! { bool },
+        TypeRef::I64 => quote! { i64 },
+        TypeRef::U64 => quote! { u64 },
+        TypeRef::F64 => quote! { f64 },
+        TypeRef::Bytes => quote! { Vec<u8> },
+        TypeRef::Json => quote! { ::serde_json::Value },
+        TypeRef::List(inner) => {
+            let inner = rust_type_in(inner, structs);
+            quote! { Vec<#inner> }
+        }
+        TypeRef::Optional(inner) => {
+            let inner = rust_type_in(inner, structs);
+            quote! { Option<#inner> }
+        }
+        TypeRef::Named(name) => {
+            let ident = to_ident(name);
+            quote! { #structs::#ident }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_are_raw_identifiers() {
+        assert_eq!(to_ident("secret_id").to_string(), "secret_id");
+        assert_eq!(to_ident("type").to_string(), "r#type");
+        assert_eq!(to_ident("match").to_string(), "r#match");
+    }
+
+    #[test]
+    fn structs_with_keyword_fields() {
+        let interface: Interface = toml::from_str(
+            r#"
+            [types.Item]
+            type = "string"
+            "#,
+        )
+        .unwrap();
+        let structs = structs(&interface).unwrap().to_string();
+        assert!(structs.contains("pub r#type : String"), "{}", structs);
+    }
+}
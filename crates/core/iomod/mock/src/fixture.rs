@@ -0,0 +1,273 @@
+//! Fixture files hold the calls a mock IOmod answers, as a list of request matches and canned responses:
+//!
+//! ```json
+//! {
+//!   "calls": [
+//!     {
+//!       "coordinates": "akkoro.aws.secretsmanager",
+//!       "method": "get_secret_value",
+//!       "request": { "secret_id": "my-secret" },
+//!       "response": { "secret_string": "hunter2" }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! A call matches when the coordinates & method are equal, and the request is equal to the call input
+//! when both are parsed as JSON. A call with no `request` matches any input. Payloads which aren't JSON
+//! are stored as strings with `"encoding": "text"`.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Fixture {
+    #[serde(default)]
+    pub calls: Vec<FixtureCall>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FixtureCall {
+    pub coordinates: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    pub response: Value,
+    #[serde(default, skip_serializing_if = "Encoding::is_json")]
+    pub encoding: Encoding,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    Text,
+}
+
+impl Encoding {
+    fn is_json(&self) -> bool {
+        *self == Encoding::Json
+    }
+}
+
+impl Fixture {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("could not parse fixture {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("could not read fixture {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("could not write fixture {}: {}", path.display(), e))
+    }
+
+    /// The distinct IOmod coordinates which have calls in this fixture
+    pub fn coordinates(&self) -> BTreeSet<String> {
+        self.calls.iter().map(|c| c.coordinates.clone()).collect()
+    }
+
+    /// Find the response for a call to `method` of the IOmod at `coordinates` with `input`
+    pub fn find(&self, coordinates: &str, method: &str, input: &[u8]) -> Option<Vec<u8>> {
+        self.calls
+            .iter()
+            .filter(|c| c.coordinates == coordinates && c.method == method)
+            .find(|c| match &c.request {
+                None => true,
+                Some(request) => *request == decode(input, c.encoding),
+            })
+            .map(|c| encode(&c.response, c.encoding))
+    }
+
+    /// Add a recorded call, replacing any previous call with the same request
+    pub fn record(&mut self, coordinates: &str, method: &str, input: &[u8], output: &[u8]) {
+        let encoding = match serde_json::from_slice::<Value>(input).is_ok()
+            && serde_json::from_slice::<Value>(output).is_ok()
+        {
+            true => Encoding::Json,
+            false => Encoding::Text,
+        };
+        let call = FixtureCall {
+            coordinates: coordinates.to_string(),
+            method: method.to_string(),
+            request: Some(decode(input, encoding)),
+            response: decode(output, encoding),
+            encoding,
+        };
+        self.calls.retain(|c| {
+            !(c.coordinates == call.coordinates
+                && c.method == call.method
+                && c.request == call.request)
+        });
+        self.calls.push(call);
+    }
+}
+
+/// The response to a call which no fixture matches; a JSON error naming the un-mocked call, so that the function
+/// fails with a useful message rather than on an empty body
+pub fn unmatched(coordinates: &str, method: &str, input: &[u8]) -> Vec<u8> {
+    let request = match serde_json::from_slice::<Value>(input) {
+        Ok(request) => request,
+        Err(_) => Value::String(String::from_utf8_lossy(input).to_string()),
+    };
+    serde_json::to_vec(&serde_json::json!({
+        "error": format!("no fixture matches call to {}.{}", coordinates, method),
+        "coordinates": coordinates,
+        "method": method,
+        "request": request,
+    }))
+    .unwrap()
+}
+
+fn decode(payload: &[u8], encoding: Encoding) -> Value {
+    match encoding {
+        Encoding::Json => serde_json::from_slice(payload).unwrap_or(Value::Null),
+        Encoding::Text => Value::String(String::from_utf8_lossy(payload).to_string()),
+    }
+}
+
+fn encode(value: &Value, encoding: Encoding) -> Vec<u8> {
+    match (encoding, value) {
+        (Encoding::Text, Value::String(s)) => s.as_bytes().to_vec(),
+        _ => serde_json::to_vec(value).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Fixture {
+        serde_json::from_str(
+            r#"{
+                "calls": [
+                    {
+                        "coordinates": "akkoro.aws.secretsmanager",
+                        "method": "get_secret_value",
+                        "request": { "secret_id": "my-secret" },
+                        "response": { "secret_string": "hunter2" }
+                    },
+                    {
+                        "coordinates": "akkoro.aws.secretsmanager",
+                        "method": "get_secret_value",
+                        "response": { "secret_string": "fallback" }
+                    },
+                    {
+                        "coordinates": "akkoro.std.http",
+                        "method": "request",
+                        "request": "ping",
+                        "response": "pong",
+                        "encoding": "text"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn find_matches_request_as_json() {
+        let found = fixture().find(
+            "akkoro.aws.secretsmanager",
+            "get_secret_value",
+            br#"{ "secret_id":"my-secret" }"#,
+        );
+        assert_eq!(found.unwrap(), br#"{"secret_string":"hunter2"}"#);
+    }
+
+    #[test]
+    fn find_falls_back_to_call_without_request() {
+        let found = fixture().find(
+            "akkoro.aws.secretsmanager",
+            "get_secret_value",
+            br#"{"secret_id":"other"}"#,
+        );
+        assert_eq!(found.unwrap(), br#"{"secret_string":"fallback"}"#);
+    }
+
+    #[test]
+    fn find_text_call() {
+        let fixture = fixture();
+        assert_eq!(
+            fixture.find("akkoro.std.http", "request", b"ping").unwrap(),
+            b"pong"
+        );
+        assert!(fixture
+            .find("akkoro.std.http", "request", b"pang")
+            .is_none());
+        assert!(fixture.find("akkoro.std.http", "get", b"ping").is_none());
+    }
+
+    #[test]
+    fn unmatched_names_the_call() {
+        let response: Value = serde_json::from_slice(&unmatched(
+            "akkoro.aws.dynamodb",
+            "get_item",
+            br#"{"key":"a"}"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            response["error"],
+            "no fixture matches call to akkoro.aws.dynamodb.get_item"
+        );
+        assert_eq!(response["coordinates"], "akkoro.aws.dynamodb");
+        assert_eq!(response["method"], "get_item");
+        assert_eq!(response["request"]["key"], "a");
+
+        let response: Value =
+            serde_json::from_slice(&unmatched("akkoro.std.http", "request", b"ping")).unwrap();
+        assert_eq!(response["request"], "ping");
+    }
+
+    #[test]
+    fn record_replaces_same_request() {
+        let mut fixture = Fixture::default();
+        fixture.record(
+            "akkoro.std.http",
+            "request",
+            br#"{"url":"a"}"#,
+            br#"{"status":200}"#,
+        );
+        fixture.record(
+            "akkoro.std.http",
+            "request",
+            br#"{"url":"b"}"#,
+            br#"{"status":404}"#,
+        );
+        fixture.record(
+            "akkoro.std.http",
+            "request",
+            br#"{"url":"a"}"#,
+            br#"{"status":500}"#,
+        );
+        assert_eq!(fixture.calls.len(), 2);
+        assert_eq!(
+            fixture
+                .find("akkoro.std.http", "request", br#"{"url":"a"}"#)
+                .unwrap(),
+            br#"{"status":500}"#
+        );
+    }
+
+    #[test]
+    fn record_non_json_as_text() {
+        let mut fixture = Fixture::default();
+        fixture.record("akkoro.std.http", "request", b"ping", b"not json");
+        assert!(fixture.calls[0].encoding == Encoding::Text);
+        assert_eq!(
+            fixture.find("akkoro.std.http", "request", b"ping").unwrap(),
+            b"not json"
+        );
+
+        let json = serde_json::to_value(&fixture).unwrap();
+        assert_eq!(json["calls"][0]["encoding"], "text");
+    }
+}
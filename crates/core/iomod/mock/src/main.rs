@@ -0,0 +1,167 @@
+//! A mock IOmod for running functions without the real IOmods or cloud access.
+//!
+//! The mock registers itself with the runtime at every coordinate found in its fixture file (plus any
+//! given with `--coordinates`), and answers calls with the matching canned response. In record mode
+//! calls are instead proxied to the real IOmods, which register with a registry run by the mock, and
+//! each request & response is written to the fixture file.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use clap::{crate_version, Arg, ArgAction, Command};
+use futures::future::BoxFuture;
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::sync::mpsc;
+
+use assemblylift_core_iomod::iomod;
+use assemblylift_core_iomod::registry::{
+    registry_channel, spawn_registry_at, RegistryChannelMessage, RegistryTx,
+};
+
+use crate::fixture::Fixture;
+
+mod fixture;
+
+static FIXTURE_PATH: OnceCell<PathBuf> = OnceCell::new();
+static FIXTURE: Lazy<Mutex<Fixture>> = Lazy::new(|| Mutex::new(Fixture::default()));
+/// Set in record mode; calls are forwarded to the real IOmods via this registry
+static UPSTREAM: OnceCell<RegistryTx> = OnceCell::new();
+
+fn main() {
+    let matches = Command::new("assemblylift-iomod-mock")
+        .version(crate_version!())
+        .about("Answers IOmod calls from a fixture file")
+        .arg(
+            Arg::new("fixture")
+                .short('f')
+                .long("fixture")
+                .required(true)
+                .help("Path to the fixture file"),
+        )
+        .arg(
+            Arg::new("coordinates")
+                .short('c')
+                .long("coordinates")
+                .action(ArgAction::Append)
+                .help("Additional IOmod coordinates to register, e.g. akkoro.aws.secretsmanager"),
+        )
+        .arg(
+            Arg::new("registry")
+                .long("registry")
+                .default_value("127.0.0.1")
+                .help("Address of the runtime's IOmod registry"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .action(ArgAction::SetTrue)
+                .help("Proxy calls to the real IOmods and record them to the fixture file"),
+        )
+        .arg(
+            Arg::new("upstream-port")
+                .long("upstream-port")
+                .default_value("13556")
+                .help("In record mode, the port real IOmods register on (run them with ASML_REGISTRY_PORT set to this)"),
+        )
+        .get_matches();
+
+    let fixture_path = PathBuf::from(matches.get_one::<String>("fixture").unwrap());
+    let fixture = match Fixture::read(&fixture_path) {
+        Ok(fixture) => fixture,
+        Err(why) => exit_with(&why),
+    };
+
+    let mut coordinates = fixture.coordinates();
+    if let Some(coords) = matches.get_many::<String>("coordinates") {
+        coordinates.extend(coords.cloned());
+    }
+    if coordinates.is_empty() {
+        exit_with("no IOmod coordinates to register; add calls to the fixture or use --coordinates");
+    }
+
+    FIXTURE_PATH.set(fixture_path).unwrap();
+    *FIXTURE.lock().unwrap() = fixture;
+
+    if matches.get_flag("record") {
+        let port = matches.get_one::<String>("upstream-port").unwrap();
+        let (registry_tx, registry_rx) = registry_channel(32);
+        spawn_registry_at(registry_rx, format!("0.0.0.0:{}", port)).unwrap();
+        UPSTREAM.set(registry_tx).unwrap();
+        println!("Recording; real IOmods should register on port {}", port);
+    }
+
+    let registry = matches.get_one::<String>("registry").unwrap().clone();
+    let threads: Vec<_> = coordinates
+        .into_iter()
+        .map(|coords| {
+            let registry = registry.clone();
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                rt.block_on(async move {
+                    iomod!(registry.as_str(), coordinates = coords => {
+                        _ => answer
+                    });
+                });
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+fn answer(method_path: String, input: Vec<u8>) -> BoxFuture<'static, Vec<u8>> {
+    Box::pin(async move {
+        let (coordinates, method) = match method_path.rsplit_once('.') {
+            Some(parts) => parts,
+            None => exit_with(&format!("malformed method path {}", method_path)),
+        };
+
+        match UPSTREAM.get() {
+            Some(upstream) => {
+                let output = proxy(upstream.clone(), coordinates, method, input.clone()).await;
+                let mut fixture = FIXTURE.lock().unwrap();
+                fixture.record(coordinates, method, &input, &output);
+                if let Err(why) = fixture.write(FIXTURE_PATH.get().unwrap()) {
+                    eprintln!("ERROR {}", why);
+                }
+                output
+            }
+            None => match FIXTURE.lock().unwrap().find(coordinates, method, &input) {
+                Some(output) => output,
+                None => {
+                    eprintln!(
+                        "ERROR no fixture matches call to {} with input {}",
+                        method_path,
+                        String::from_utf8_lossy(&input)
+                    );
+                    fixture::unmatched(coordinates, method, &input)
+                }
+            },
+        }
+    })
+}
+
+fn exit_with(why: &str) -> ! {
+    eprintln!("ERROR {}", why);
+    std::process::exit(1)
+}
+
+async fn proxy(upstream: RegistryTx, coordinates: &str, method: &str, input: Vec<u8>) -> Vec<u8> {
+    let (local_tx, mut local_rx) = mpsc::channel(1);
+    upstream
+        .send(RegistryChannelMessage {
+            iomod_coords: coordinates.to_string(),
+            method_name: method.to_string(),
+            payload_type: "IOMOD_REQUEST",
+            payload: input,
+            responder: Some(local_tx),
+        })
+        .await
+        .unwrap();
+    match local_rx.recv().await {
+        Some(response) => response.payload,
+        None => Vec::new(),
+    }
+}
@@ -12,6 +12,8 @@ use tokio::sync::mpsc;
 
 use crate::iomod_capnp::{agent, iomod};
 
+pub use assemblylift_core_iomod_macros::iomod_dispatch;
+
 pub mod iomod_capnp;
 pub mod macros;
 pub mod package;
@@ -48,20 +50,31 @@ where
     }
 }
 
+/// A call which handles any method not in the `CallMap`, given the full method path
+/// (e.g. `akkoro.aws.dynamodb.put_item`) and the call input
+pub type FallbackCall<'a> = fn(String, Vec<u8>) -> BoxFuture<'a, Vec<u8>>;
+
 pub struct CallMap<'a> {
     pub map: HashMap<&'a str, CallPtr<BoxFuture<'a, Vec<u8>>>>,
+    pub fallback: Option<FallbackCall<'a>>,
+    pub coordinates: String,
 }
 
 impl<'a> CallMap<'a> {
     pub fn new() -> Self {
         Self {
             map: HashMap::default(),
+            fallback: None,
+            coordinates: String::new(),
         }
     }
 
     pub fn get(&self, coords: String, with_input: Vec<u8>) -> BoxFuture<'a, Vec<u8>> {
-        let call = self.map[coords.as_str()].call;
-        call(with_input)
+        match (self.map.get(coords.as_str()), self.fallback) {
+            (Some(ptr), _) => (ptr.call)(with_input),
+            (None, Some(fallback)) => fallback(format!("{}.{}", self.coordinates, coords), with_input),
+            (None, None) => panic!("no call {} in IOmod {}", coords, self.coordinates),
+        }
     }
 }
 
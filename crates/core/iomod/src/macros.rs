@@ -4,6 +4,16 @@ pub static RUSTC_VERSION: &str = env!("RUSTC_VERSION");
 #[macro_export]
 macro_rules! iomod {
     ($ip:expr, $org:ident.$ns:ident.$name:ident => $calls:tt) => {
+        let coordinates = format!(
+            "{}.{}.{}",
+            stringify!($org),
+            stringify!($ns),
+            stringify!($name)
+        );
+        $crate::iomod!($ip, coordinates = coordinates => $calls);
+    };
+
+    ($ip:expr, coordinates = $coords:expr => $calls:tt) => {
         use assemblylift_core_iomod::iomod_capnp::*;
         use assemblylift_core_iomod::{
             Call, CallChannel, CallMap, CallPtr, CallRequest, CallResponse, Iomod,
@@ -13,17 +23,16 @@ macro_rules! iomod {
         use tokio::net::TcpStream;
         use tokio::sync::mpsc;
 
-        let org = stringify!($org);
-        let ns = stringify!($ns);
-        let name = stringify!($name);
-
-        let iomod_coords = format!("{}.{}.{}", org, ns, name);
+        let iomod_coords: String = $coords;
         println!("Starting AssemblyLift IO module {}", iomod_coords);
 
         let mut call_map: CallMap = $crate::__calls!($calls);
+        call_map.coordinates = iomod_coords.clone();
         let mut call_channel: CallChannel = mpsc::channel(100);
 
-        let stream = TcpStream::connect(format!("{}:13555", $ip)).await.unwrap();
+        let stream = TcpStream::connect(format!("{}:{}", $ip, $crate::registry::registry_port()))
+            .await
+            .unwrap();
         stream.set_nodelay(true).unwrap();
 
         let (reader, writer) = tokio_util::compat::TokioAsyncReadCompatExt::compat(stream).split();
@@ -87,4 +96,19 @@ macro_rules! __calls {
         )*
         call_map
     }};
+
+    ({ $( $call_name:ident => $call:expr, )* _ => $fallback:expr $(,)? }) => {{
+        let mut call_map = CallMap::new();
+        $(
+            let call_name = stringify!($call_name);
+            call_map.map.insert(call_name, CallPtr::new($call));
+        )*
+        call_map.fallback = Some($fallback);
+        call_map
+    }};
+
+    // A function returning the CallMap, e.g. the `call_map` generated by `iomod_dispatch!`
+    ($call_map:ident) => {{
+        $call_map()
+    }};
 }
@@ -1,9 +1,13 @@
 use serde::Deserialize;
 
+pub use assemblylift_core_io_common::interface::Interface;
+
 #[derive(Deserialize)]
 pub struct IomodManifest {
     pub iomod: ManifestHeader,
     pub process: Process,
+    /// The calls the IOmod serves, used to generate typed clients
+    pub interface: Option<Interface>,
 }
 
 impl IomodManifest {
@@ -62,8 +62,21 @@ pub struct RegistryChannelMessage {
 
 pub type ModuleMap = Arc<Box<RefCell<HashMap<String, agent::Client>>>>;
 
-pub fn spawn_registry(mut rx: RegistryRx) -> Result<(), RegistryError> {
-    std::thread::spawn(|| {
+/// The port the registry listens on; `13555` unless set by `ASML_REGISTRY_PORT`
+pub fn registry_port() -> u16 {
+    std::env::var("ASML_REGISTRY_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(13555)
+}
+
+pub fn spawn_registry(rx: RegistryRx) -> Result<(), RegistryError> {
+    spawn_registry_at(rx, format!("0.0.0.0:{}", registry_port()))
+}
+
+/// Spawn the registry listening for IOmods at `addr`
+pub fn spawn_registry_at(mut rx: RegistryRx, addr: String) -> Result<(), RegistryError> {
+    std::thread::spawn(move || {
         let mut rt = tokio::runtime::Runtime::new().unwrap();
 
         tokio::task::LocalSet::new().block_on(&mut rt, async {
@@ -71,7 +84,7 @@ pub fn spawn_registry(mut rx: RegistryRx) -> Result<(), RegistryError> {
 
             let rpc_modules = modules.clone();
             let rpc_task = tokio::task::spawn_local(async move {
-                let listener = TcpListener::bind("0.0.0.0:13555").await.unwrap();
+                let listener = TcpListener::bind(addr).await.unwrap();
                 let registry_client: registry::Client =
                     capnp_rpc::new_client(Registry::new(rpc_modules));
 
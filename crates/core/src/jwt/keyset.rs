@@ -96,6 +96,21 @@ impl KeyStore {
         Ok(key_store)
     }
 
+    /// Create a key store from a JWKS document without fetching anything, e.g. for tests
+    pub fn new_from_jwks(jwks: &str) -> Result<KeyStore, Error> {
+        #[derive(Deserialize)]
+        pub struct JwtKeys {
+            pub keys: Vec<JwtKey>,
+        }
+
+        let mut key_store = KeyStore::new();
+        let jwks = serde_json::from_str::<JwtKeys>(jwks).map_err(|_| err_int("Failed to parse keys"))?;
+        jwks.keys.iter().for_each(|k| key_store.add_key(k));
+        key_store.load_time = Some(SystemTime::now());
+
+        Ok(key_store)
+    }
+
     pub fn clear_keys(&mut self) {
         self.keys.clear();
     }
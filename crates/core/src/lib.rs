@@ -12,6 +12,18 @@ where
 {
     fn success(status_tx: crate::wasm::StatusTx<S>, response: Vec<u8>, request_id: Option<String>);
     fn failure(status_tx: crate::wasm::StatusTx<S>, response: Vec<u8>, request_id: Option<String>);
+
+    /// Handle a log message emitted by a function; by default it is forwarded to `tracing`
+    fn log(log_level: crate::wasm::asml_rt::LogLevel, context: String, message: String) {
+        use crate::wasm::asml_rt::LogLevel;
+        match log_level {
+            LogLevel::Debug => tracing::debug!("Function:{}: {}", context, message),
+            LogLevel::Trace => tracing::trace!("Function:{}: {}", context, message),
+            LogLevel::Info => tracing::info!("Function:{}: {}", context, message),
+            LogLevel::Warn => tracing::warn!("Function:{}: {}", context, message),
+            LogLevel::Error => tracing::error!("Function:{}: {}", context, message),
+        }
+    }
 }
 
 pub trait SecretsAbi: KeysAbi {
@@ -42,13 +42,14 @@ where
     }
 
     /// Poll the runtime for the completion status of call associated with `ioid`
-    pub fn poll(&mut self, ioid: IoId) -> Option<Vec<u8>> {
+    pub fn poll(&mut self, ioid: IoId) -> Result<Vec<u8>, asml_io::PollError> {
         match self.io_memory.clone().lock() {
             Ok(memory) => match memory.poll(ioid) {
-                true => Some(memory.buffer.get(ioid as usize)),
-                false => None,
+                Some(true) => Ok(memory.buffer.get(ioid as usize)),
+                Some(false) => Err(asml_io::PollError::NotReady),
+                None => Err(asml_io::PollError::InvalidIoid),
             },
-            Err(_) => None,
+            Err(_) => Err(asml_io::PollError::NotReady),
         }
     }
 
@@ -122,11 +123,9 @@ impl IoMemory {
         Some(next_id)
     }
 
-    fn poll(&self, ioid: IoId) -> bool {
-        match self.io_status.get(&ioid) {
-            Some(status) => *status,
-            None => false,
-        }
+    /// Whether the call `ioid` has completed, or `None` if there is no such call
+    fn poll(&self, ioid: IoId) -> Option<bool> {
+        self.io_status.get(&ioid).copied()
     }
 
     fn handle_response(&mut self, response: Vec<u8>, ioid: IoId) {
@@ -1,8 +1,9 @@
 mod cache;
+mod precompiled;
 
 use std::borrow::Cow;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::string::ToString;
 use std::sync::{Arc, Mutex};
 
@@ -33,12 +34,16 @@ use crate::jwt::keyset::KeyStore as JwtKeyStore;
 use crate::policy_manager::PolicyManager;
 use crate::threader::Threader;
 use crate::wasm::cache::Cache;
+pub use crate::wasm::precompiled::PrecompiledManifest;
 use crate::RuntimeAbi;
 
 // pub type State<R, S> = AsmlFunctionState<R, S>;
 pub type StatusTx<S> = crossbeam_channel::Sender<S>;
 pub type StatusRx<S> = crossbeam_channel::Receiver<S>;
 
+/// Host directory mapped to /tmp inside the function, unless set with `Wasmtime::set_tmp_dir`
+pub const TMP_DIR: &str = "/tmp/asmltmp";
+
 pub static CPU_COMPAT_MODE: Lazy<String> =
     Lazy::new(|| std::env::var("ASML_CPU_COMPAT_MODE").unwrap_or("default".to_string()));
 
@@ -55,6 +60,7 @@ where
     engine: Engine,
     component: Component,
     cache: Arc<Mutex<Cache>>,
+    tmp_dir: PathBuf,
     _phantom_r: std::marker::PhantomData<R>,
     _phantom_s: std::marker::PhantomData<S>,
 }
@@ -68,10 +74,23 @@ where
         match path.extension().unwrap().to_str().unwrap() {
             "bin" => {
                 let target = Self::get_target();
+                // Artifacts precompiled before manifests were written have none; those are only checked by
+                // deserializing them
+                if let Ok(manifest) = PrecompiledManifest::read(path) {
+                    let wasm = std::fs::read(path.with_extension("")).ok();
+                    if let Some(reason) =
+                        manifest.incompatibility(target, CPU_COMPAT_MODE.as_str(), wasm.as_deref())
+                    {
+                        return Self::jit_fallback(path, reason);
+                    }
+                }
+
                 let engine = new_engine(target, None)?;
-                let component = unsafe { Component::deserialize_file(&engine, path) }
-                    .expect("could not deserialize component");
-                Ok((engine, component))
+                // Deserializing also checks that the component was precompiled for this host's target & CPU features
+                match unsafe { Component::deserialize_file(&engine, path) } {
+                    Ok(component) => Ok((engine, component)),
+                    Err(err) => Self::jit_fallback(path, err.to_string()),
+                }
             }
             "wasm" => {
                 let engine = new_engine(None, None)?;
@@ -87,10 +106,33 @@ where
         }
     }
 
+    /// Compile the component shipped alongside the precompiled one at `path`, which this host cannot load
+    fn jit_fallback(path: &Path, reason: String) -> anyhow::Result<(Engine, Component)> {
+        let jit_path = path.with_extension("");
+        if !jit_path.exists() {
+            return Err(anyhow!(
+                "could not load precompiled component {}: {}",
+                path.display(),
+                reason
+            ));
+        }
+        tracing::warn!(
+            "could not load precompiled component {} ({}); falling back to JIT from {}",
+            path.display(),
+            reason,
+            jit_path.display()
+        );
+        let engine = new_engine(None, None)?;
+        let component = Component::from_file(&engine, &jit_path)?;
+        Ok((engine, component))
+    }
+
     fn get_target() -> Option<&'static str> {
-        match std::env::consts::OS {
-            "macos" => Some("x86_64-apple-darwin"),
-            "linux" => Some("x86_64-linux-gnu"),
+        match (std::env::consts::ARCH, std::env::consts::OS) {
+            ("x86_64", "macos") => Some("x86_64-apple-darwin"),
+            ("aarch64", "macos") => Some("aarch64-apple-darwin"),
+            ("x86_64", "linux") => Some("x86_64-linux-gnu"),
+            ("aarch64", "linux") => Some("aarch64-linux-gnu"),
             _ => None,
         }
     }
@@ -108,6 +150,7 @@ where
                 engine: ec.0,
                 component: ec.1,
                 cache: Arc::new(Mutex::new(Cache::new())),
+                tmp_dir: PathBuf::from(TMP_DIR),
                 _phantom_r: Default::default(),
                 _phantom_s: Default::default(),
             }),
@@ -115,6 +158,18 @@ where
         }
     }
 
+    /// Seed the JWT key set used by `decode_verify` from a JWKS document, instead of fetching it
+    /// from the URL provided by the function
+    pub fn seed_jwks(&mut self, jwks: &str) -> anyhow::Result<()> {
+        let key_set = JwtKeyStore::new_from_jwks(jwks).map_err(|e| anyhow!(e.to_string()))?;
+        self.cache.lock().unwrap().put("jwt.keyset", &key_set)
+    }
+
+    /// Map `path` to /tmp inside the function, instead of `TMP_DIR`
+    pub fn set_tmp_dir(&mut self, path: &Path) {
+        self.tmp_dir = path.to_path_buf();
+    }
+
     pub async fn link_wasi_component(
         &mut self,
         registry_tx: RegistryTx,
@@ -149,6 +204,8 @@ where
 
         match runtime_environment.as_str() {
             "ruby" => builder = builder.inherit_stdio().env("RUBY_PLATFORM", "wasm32-wasi").args(&["ruby", "/src/handler.rb"]),
+            "python" => builder = builder.inherit_stdio().env("PYTHONHOME", "/usr/local").env("PYTHONPATH", "/src").args(&["python", "/src/handler.py"]),
+            "javascript" => builder = builder.inherit_stdio().args(&["qjs", "/src/handler.js"]),
             _ => (),
         }
 
@@ -165,7 +222,7 @@ where
         }
         builder = builder.preopened_dir(
             Dir::from_std_file(
-                File::open("/tmp/asmltmp").unwrap(),
+                File::open(&self.tmp_dir).unwrap(),
             ),
             DirPerms::all(), 
             FilePerms::all(),
@@ -288,10 +345,7 @@ where
     }
 
     fn poll(&mut self, ioid: asml_io::Ioid) -> anyhow::Result<Result<Vec<u8>, asml_io::PollError>> {
-        match self.threader.clone().lock().unwrap().poll(ioid) {
-            Some(response) => Ok(Ok(response)),
-            None => Ok(Err(asml_io::PollError::NotReady)),
-        }
+        Ok(self.threader.clone().lock().unwrap().poll(ioid))
     }
 }
 
@@ -322,14 +376,7 @@ where
         context: String,
         message: String,
     ) -> anyhow::Result<()> {
-        use asml_rt::LogLevel;
-        match log_level {
-            LogLevel::Debug => tracing::debug!("Function:{}: {}", context, message),
-            LogLevel::Trace => tracing::trace!("Function:{}: {}", context, message),
-            LogLevel::Info => tracing::info!("Function:{}: {}", context, message),
-            LogLevel::Warn => tracing::warn!("Function:{}: {}", context, message),
-            LogLevel::Error => tracing::error!("Function:{}: {}", context, message),
-        }
+        R::log(log_level, context, message);
         Ok(())
     }
 
@@ -500,6 +547,18 @@ pub fn make_wasi_component(module: Vec<u8>, preview1: &[u8]) -> anyhow::Result<V
     Ok(bytes)
 }
 
+/// Compose `component` with the components at `definitions`, satisfying its imports with their exports
+pub fn compose_components(component: &Path, definitions: &[PathBuf]) -> anyhow::Result<Vec<u8>> {
+    println!("Composing {} with {} component(s)...", component.display(), definitions.len());
+    let config = wasm_compose::config::Config {
+        definitions: definitions.to_vec(),
+        ..Default::default()
+    };
+    wasm_compose::composer::ComponentComposer::new(component, &config)
+        .compose()
+        .context("failed to compose components")
+}
+
 macro_rules! parse_wit {
     ($path:expr) => {
         {
@@ -518,6 +577,21 @@ macro_rules! parse_wit {
     };
 }
 
+/// Whether the core `module` imports any interface of the AssemblyLift WIT, i.e. whether it was built with the guest
+/// bindings for `asml-rt` & `asml-io`
+pub fn imports_asml_wit(module: &[u8]) -> anyhow::Result<bool> {
+    for payload in wasmparser::Parser::new(0).parse_all(module) {
+        if let wasmparser::Payload::ImportSection(imports) = payload? {
+            for import in imports {
+                if import?.module.starts_with("akkoro:assemblylift/") {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
 pub fn embed_asml_wit(module: Vec<u8>) -> anyhow::Result<Vec<u8>> {
     println!("Embedding WIT in module...");
     let mut wasm = module.clone();
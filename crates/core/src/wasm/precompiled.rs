@@ -0,0 +1,174 @@
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use super::new_engine;
+
+/// Describes how a precompiled component was built. It is written next to the component as `<component>.bin.json`,
+/// so that a host can tell whether it is able to load the component before trying to deserialize it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PrecompiledManifest {
+    /// Identifies the Wasmtime build & engine configuration the artifact can be loaded by; see [`engine_hash`]
+    pub engine_hash: String,
+    pub target: String,
+    pub cpu_compat_mode: String,
+    /// SHA-256 of the component the artifact was precompiled from
+    pub wasm_sha256: String,
+}
+
+impl PrecompiledManifest {
+    pub fn new(wasm: &[u8], target: &str, cpu_compat_mode: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            engine_hash: engine_hash(target, cpu_compat_mode)?,
+            target: target.into(),
+            cpu_compat_mode: cpu_compat_mode.into(),
+            wasm_sha256: sha256(wasm),
+        })
+    }
+
+    /// The path of the manifest of the precompiled component at `bin_path`
+    pub fn path(bin_path: &Path) -> PathBuf {
+        PathBuf::from(format!("{}.json", bin_path.display()))
+    }
+
+    pub fn read(bin_path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(Self::path(bin_path))?;
+        serde_json::from_str(&json).map_err(|e| anyhow!(e))
+    }
+
+    pub fn write(&self, bin_path: &Path) -> anyhow::Result<()> {
+        std::fs::write(Self::path(bin_path), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Why a host with the given target & CPU compatibility mode cannot load the component, if it can't. `wasm` is
+    /// the component shipped alongside the precompiled artifact, if any; an artifact precompiled from a different
+    /// component is stale.
+    pub fn incompatibility(
+        &self,
+        target: Option<&str>,
+        cpu_compat_mode: &str,
+        wasm: Option<&[u8]>,
+    ) -> Option<String> {
+        let target = match target {
+            Some(target) if target != self.target => {
+                return Some(format!(
+                    "precompiled for target {}, but the host is {}",
+                    self.target, target
+                ))
+            }
+            Some(target) => target,
+            None => {
+                return Some(format!(
+                    "precompiled for target {}, but the host target is unknown",
+                    self.target
+                ))
+            }
+        };
+        if cpu_compat_mode != self.cpu_compat_mode {
+            return Some(format!(
+                "precompiled with cpu_compat_mode {}, but the host uses {}",
+                self.cpu_compat_mode, cpu_compat_mode
+            ));
+        }
+        match engine_hash(target, cpu_compat_mode) {
+            Ok(hash) if hash != self.engine_hash => {
+                return Some("precompiled by a different build of Wasmtime than the host's".into())
+            }
+            Err(err) => return Some(format!("could not configure the host's engine: {}", err)),
+            _ => {}
+        }
+        if let Some(wasm) = wasm {
+            if sha256(wasm) != self.wasm_sha256 {
+                return Some(
+                    "precompiled from a different component than the one shipped with it".into(),
+                );
+            }
+        }
+        None
+    }
+}
+
+/// A digest of everything Wasmtime requires to match between the engine that precompiles a component for `target`
+/// and the engine that loads it, i.e. its version, compiler settings and enabled features
+fn engine_hash(target: &str, cpu_compat_mode: &str) -> anyhow::Result<String> {
+    let engine = new_engine(Some(target), Some(cpu_compat_mode))?;
+    let mut hasher = Sha256Hasher(ring::digest::Context::new(&ring::digest::SHA256));
+    engine.precompile_compatibility_hash().hash(&mut hasher);
+    Ok(hex(hasher.0.finish().as_ref()))
+}
+
+/// Feeds [`Hash`] implementations into SHA-256, which unlike the std hashers is stable between builds
+struct Sha256Hasher(ring::digest::Context);
+
+impl Hasher for Sha256Hasher {
+    fn finish(&self) -> u64 {
+        let digest = self.0.clone().finish();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest.as_ref()[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    hex(ring::digest::digest(&ring::digest::SHA256, bytes).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{engine_hash, PrecompiledManifest};
+
+    const TARGET: &str = "x86_64-linux-gnu";
+
+    #[test]
+    fn compatible() {
+        let manifest = PrecompiledManifest::new(b"component", TARGET, "default").unwrap();
+        assert_eq!(
+            manifest.incompatibility(Some(TARGET), "default", Some(b"component")),
+            None
+        );
+        assert_eq!(
+            manifest.incompatibility(Some(TARGET), "default", None),
+            None
+        );
+    }
+
+    #[test]
+    fn incompatible() {
+        let manifest = PrecompiledManifest::new(b"component", TARGET, "default").unwrap();
+        assert!(manifest
+            .incompatibility(Some("aarch64-linux-gnu"), "default", None)
+            .is_some());
+        assert!(manifest.incompatibility(None, "default", None).is_some());
+        assert!(manifest
+            .incompatibility(Some(TARGET), "high", None)
+            .is_some());
+        assert!(manifest
+            .incompatibility(Some(TARGET), "default", Some(b"changed"))
+            .is_some());
+
+        let mut manifest = manifest;
+        manifest.engine_hash = "0".repeat(64);
+        assert!(manifest
+            .incompatibility(Some(TARGET), "default", None)
+            .is_some());
+    }
+
+    #[test]
+    fn engine_hash_depends_on_configuration() {
+        let hash = engine_hash(TARGET, "default").unwrap();
+        assert_eq!(hash, engine_hash(TARGET, "default").unwrap());
+        assert_ne!(hash, engine_hash("aarch64-linux-gnu", "default").unwrap());
+        assert_ne!(hash, engine_hash(TARGET, "high").unwrap());
+    }
+}
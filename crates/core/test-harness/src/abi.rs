@@ -0,0 +1,118 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use tracing::error;
+
+use assemblylift_core::wasm::{asml_rt, StatusTx};
+use assemblylift_core::{KeysAbi, RuntimeAbi, SecretsAbi};
+use assemblylift_wasi_secrets_in_memory::InMemorySecrets;
+
+use crate::{LogRecord, Status};
+
+// The component runs on the thread which called `TestHarness::run`, so capturing per-thread keeps
+// the logs & secrets of tests running in parallel apart.
+thread_local! {
+    static LOGS: RefCell<Vec<LogRecord>> = const { RefCell::new(Vec::new()) };
+    static SECRETS: RefCell<BTreeMap<String, (String, Vec<u8>)>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub(crate) fn take_logs() -> Vec<LogRecord> {
+    LOGS.with(|logs| logs.borrow_mut().drain(..).collect())
+}
+
+pub(crate) fn clear_secrets() {
+    SECRETS.with(|secrets| secrets.borrow_mut().clear());
+}
+
+pub struct Abi;
+
+impl KeysAbi for Abi {
+    fn encrypt(id: String, plaintext: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        InMemorySecrets::encrypt(id, plaintext)
+    }
+
+    fn decrypt(id: String, ciphertext: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        InMemorySecrets::decrypt(id, ciphertext)
+    }
+}
+
+impl SecretsAbi for Abi {
+    fn get_secret(id: String) -> anyhow::Result<Vec<u8>> {
+        let (key_id, ciphertext) = SECRETS
+            .with(|secrets| secrets.borrow().get(&id).cloned())
+            .ok_or_else(|| anyhow!("no secret with id {}", id))?;
+        Self::decrypt(key_id, ciphertext)
+    }
+
+    fn set_secret(id: String, value: Vec<u8>, key_id: Option<String>) -> anyhow::Result<()> {
+        let key_id = key_id.unwrap_or("default".to_string());
+        let ciphertext = Self::encrypt(key_id.clone(), value)?;
+        SECRETS.with(|secrets| secrets.borrow_mut().insert(id, (key_id, ciphertext)));
+        Ok(())
+    }
+}
+
+impl RuntimeAbi<Status> for Abi {
+    fn success(status_tx: StatusTx<Status>, response: Vec<u8>, _request_id: Option<String>) {
+        if let Err(e) = status_tx.send(Status::Success(response)) {
+            error!("could not send status: {:?}", e.to_string())
+        }
+    }
+
+    fn failure(status_tx: StatusTx<Status>, response: Vec<u8>, _request_id: Option<String>) {
+        if let Err(e) = status_tx.send(Status::Failure(response)) {
+            error!("could not send status: {:?}", e.to_string())
+        }
+    }
+
+    fn log(log_level: asml_rt::LogLevel, context: String, message: String) {
+        LOGS.with(|logs| {
+            logs.borrow_mut().push(LogRecord {
+                level: format!("{:?}", log_level).to_lowercase(),
+                context,
+                message,
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_round_trip() {
+        clear_secrets();
+        Abi::set_secret("api-key".into(), b"hunter2".to_vec(), None).unwrap();
+        assert_eq!(Abi::get_secret("api-key".into()).unwrap(), b"hunter2");
+    }
+
+    #[test]
+    fn secrets_are_cleared_between_runs() {
+        Abi::set_secret("api-key".into(), b"hunter2".to_vec(), None).unwrap();
+        clear_secrets();
+        assert!(Abi::get_secret("api-key".into()).is_err());
+    }
+
+    #[test]
+    fn secrets_are_not_shared_between_threads() {
+        clear_secrets();
+        Abi::set_secret("api-key".into(), b"hunter2".to_vec(), None).unwrap();
+        let other = std::thread::spawn(|| Abi::get_secret("api-key".into()).is_err())
+            .join()
+            .unwrap();
+        assert!(other);
+    }
+
+    #[test]
+    fn logs_are_captured() {
+        take_logs();
+        Abi::log(asml_rt::LogLevel::Warn, "handler".into(), "careful".into());
+        let logs = take_logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].level, "warn");
+        assert_eq!(logs[0].message, "careful");
+        assert!(take_logs().is_empty());
+    }
+}
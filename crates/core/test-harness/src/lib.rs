@@ -0,0 +1,344 @@
+//! A harness for running AssemblyLift functions natively, so that handlers can be unit tested with
+//! `cargo test`. The function component is hosted in-process by the same Wasmtime runtime used by the
+//! AssemblyLift runtimes, with IOmod calls answered by mocks instead of the IOmod registry.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use assemblylift_core::wasm::{status_channel, Wasmtime};
+use assemblylift_core::SecretsAbi;
+use assemblylift_core_iomod::registry::registry_channel;
+
+use crate::abi::Abi;
+use crate::mock::MockMap;
+
+pub mod abi;
+pub mod mock;
+
+#[derive(Debug, Clone)]
+pub enum Status {
+    Success(Vec<u8>),
+    Failure(Vec<u8>),
+}
+
+/// A message logged by the function under test
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: String,
+    pub context: String,
+    pub message: String,
+}
+
+/// An IOmod call made by the function under test
+#[derive(Debug, Clone)]
+pub struct IomodCall {
+    pub coordinates: String,
+    pub method: String,
+    pub input: Vec<u8>,
+    /// False if there was no mock for the call, in which case it was answered with an empty response
+    pub mocked: bool,
+}
+
+pub struct TestHarness {
+    component_path: PathBuf,
+    input: Vec<u8>,
+    env_vars: Vec<(String, String)>,
+    bind_paths: Vec<(String, String)>,
+    mocks: MockMap,
+    secrets: Vec<(String, Vec<u8>)>,
+    jwks: Option<String>,
+}
+
+impl TestHarness {
+    /// Create a harness for the function component at `component_path`
+    pub fn new<P: AsRef<Path>>(component_path: P) -> Self {
+        Self {
+            component_path: component_path.as_ref().to_path_buf(),
+            input: Vec::new(),
+            env_vars: Vec::new(),
+            bind_paths: Vec::new(),
+            mocks: Default::default(),
+            secrets: Vec::new(),
+            jwks: None,
+        }
+    }
+
+    /// Create a harness for the function built by the crate at `manifest_dir`, as used by `#[asml_test]`.
+    /// The component path can be overridden with the `ASML_TEST_COMPONENT` environment variable.
+    pub fn for_crate(manifest_dir: &str, function_name: &str) -> Self {
+        if let Ok(path) = std::env::var("ASML_TEST_COMPONENT") {
+            return Self::new(path);
+        }
+
+        let target_dir = std::env::var("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or(PathBuf::from(manifest_dir).join("target"));
+        let candidates: Vec<PathBuf> = ["debug", "release"]
+            .iter()
+            .map(|mode| {
+                target_dir
+                    .join("wasm32-wasi")
+                    .join(mode)
+                    .join(format!("{}.component.wasm", function_name))
+            })
+            .collect();
+
+        // Prefer whichever build is most recent
+        let newest = candidates
+            .iter()
+            .filter_map(|p| {
+                std::fs::metadata(p)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(|t| (t, p.clone()))
+            })
+            .max_by_key(|(t, _)| *t)
+            .map(|(_, p)| p);
+
+        Self::new(newest.unwrap_or(candidates[0].clone()))
+    }
+
+    /// Set the function input to `input`
+    pub fn with_input(mut self, input: Vec<u8>) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Set the function input to `input`, serialized as JSON
+    pub fn with_json_input<T: Serialize>(mut self, input: &T) -> Self {
+        self.input = serde_json::to_vec(input).expect("could not serialize input");
+        self
+    }
+
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.env_vars.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Bind the host directory `host_path` to `guest_path` in the function's filesystem
+    pub fn with_bind_path(mut self, host_path: &str, guest_path: &str) -> Self {
+        self.bind_paths
+            .push((host_path.to_string(), guest_path.to_string()));
+        self
+    }
+
+    /// Answer calls to `method` of the IOmod at `coordinates` (e.g. `akkoro.aws.dynamodb`) with `response`
+    pub fn mock_iomod(mut self, coordinates: &str, method: &str, response: Vec<u8>) -> Self {
+        self.mocks
+            .insert((coordinates.to_string(), method.to_string()), response);
+        self
+    }
+
+    /// Store a secret before the function runs
+    pub fn with_secret(mut self, id: &str, value: Vec<u8>) -> Self {
+        self.secrets.push((id.to_string(), value));
+        self
+    }
+
+    /// Use the JWKS document `jwks` to verify tokens, instead of fetching it from the function-provided URL
+    pub fn with_jwks(mut self, jwks: &str) -> Self {
+        self.jwks = Some(jwks.to_string());
+        self
+    }
+
+    /// Run the function to completion
+    pub fn run(self) -> anyhow::Result<TestOutcome> {
+        if !self.component_path.exists() {
+            return Err(anyhow!(
+                "no function component at {}; has the function been built?",
+                self.component_path.display()
+            ));
+        }
+
+        // Mapped to /tmp inside the WASM module
+        let tmp_dir = tempfile::tempdir()?;
+
+        abi::clear_secrets();
+        for (id, value) in self.secrets {
+            Abi::set_secret(id, value, None)?;
+        }
+
+        // Multi-threaded so that IOmod tasks make progress while the function blocks on them
+        let runtime = tokio::runtime::Runtime::new()?;
+        let iomod_calls = Arc::new(Mutex::new(Vec::new()));
+        let (registry_tx, registry_rx) = registry_channel(32);
+        runtime.spawn(mock::serve_mocks(
+            registry_rx,
+            self.mocks,
+            iomod_calls.clone(),
+        ));
+
+        let (status_tx, status_rx) = status_channel(8);
+        let mut wasmtime = Wasmtime::<Abi, Status>::new_from_path(&self.component_path)?;
+        wasmtime.set_tmp_dir(tmp_dir.path());
+        if let Some(jwks) = &self.jwks {
+            wasmtime.seed_jwks(jwks)?;
+        }
+
+        abi::take_logs();
+        let input = self.input;
+        let env_vars = self.env_vars;
+        let bind_paths = self.bind_paths;
+        let result = runtime.block_on(async {
+            let (command, mut store) = wasmtime
+                .link_wasi_component(
+                    registry_tx,
+                    status_tx,
+                    env_vars,
+                    "default".to_string(),
+                    bind_paths,
+                    None,
+                    &input,
+                )
+                .await?;
+            wasmtime.run_component(command, &mut store).await
+        });
+
+        let status = status_rx.try_recv().ok();
+        let iomod_calls = iomod_calls.lock().unwrap().clone();
+        Ok(TestOutcome {
+            status,
+            exit_error: result.err().map(|e| e.to_string()),
+            logs: abi::take_logs(),
+            iomod_calls,
+        })
+    }
+}
+
+/// The result of running a function with [`TestHarness::run`]
+#[derive(Debug)]
+pub struct TestOutcome {
+    /// The first response sent by the function via `success` or `failure`, if any
+    pub status: Option<Status>,
+    /// Set if the function exited in error (e.g. it panicked)
+    pub exit_error: Option<String>,
+    pub logs: Vec<LogRecord>,
+    pub iomod_calls: Vec<IomodCall>,
+}
+
+impl TestOutcome {
+    /// Assert that the function succeeded, returning its response
+    pub fn assert_success(&self) -> &[u8] {
+        match &self.status {
+            Some(Status::Success(response)) => response,
+            Some(Status::Failure(response)) => panic!(
+                "expected function to succeed, but it failed with: {}",
+                String::from_utf8_lossy(response)
+            ),
+            None => panic!(
+                "expected function to succeed, but it did not respond{}",
+                self.exit_message()
+            ),
+        }
+    }
+
+    /// Assert that the function failed, returning its response
+    pub fn assert_failure(&self) -> &[u8] {
+        match &self.status {
+            Some(Status::Failure(response)) => response,
+            Some(Status::Success(response)) => panic!(
+                "expected function to fail, but it succeeded with: {}",
+                String::from_utf8_lossy(response)
+            ),
+            None => panic!(
+                "expected function to fail, but it did not respond{}",
+                self.exit_message()
+            ),
+        }
+    }
+
+    /// Assert that the function succeeded, deserializing its response from JSON
+    pub fn success_json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(self.assert_success())
+            .expect("could not deserialize function response")
+    }
+
+    /// Assert that the function logged a message containing `text`
+    pub fn assert_logged(&self, text: &str) {
+        if !self.logs.iter().any(|l| l.message.contains(text)) {
+            panic!(
+                "expected a log message containing {:?}, got {:?}",
+                text,
+                self.logs.iter().map(|l| &l.message).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    /// The calls made to `method` of the IOmod at `coordinates`
+    pub fn calls_to(&self, coordinates: &str, method: &str) -> Vec<&IomodCall> {
+        self.iomod_calls
+            .iter()
+            .filter(|c| c.coordinates == coordinates && c.method == method)
+            .collect()
+    }
+
+    fn exit_message(&self) -> String {
+        match &self.exit_error {
+            Some(err) => format!(" ({})", err),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(status: Option<Status>) -> TestOutcome {
+        TestOutcome {
+            status,
+            exit_error: None,
+            logs: vec![LogRecord {
+                level: "info".into(),
+                context: "handler".into(),
+                message: "hello, world".into(),
+            }],
+            iomod_calls: vec![IomodCall {
+                coordinates: "akkoro.std.http".into(),
+                method: "request".into(),
+                input: Vec::new(),
+                mocked: true,
+            }],
+        }
+    }
+
+    #[test]
+    fn run_without_component_fails() {
+        let err = TestHarness::new("/nonexistent/fn.component.wasm")
+            .run()
+            .unwrap_err();
+        assert!(err.to_string().contains("has the function been built?"));
+    }
+
+    #[test]
+    fn outcome_assertions() {
+        let outcome = outcome(Some(Status::Success(br#"{"ok":true}"#.to_vec())));
+        let response: serde_json::Value = outcome.success_json();
+        assert_eq!(response["ok"], true);
+        outcome.assert_logged("hello");
+        assert_eq!(outcome.calls_to("akkoro.std.http", "request").len(), 1);
+        assert!(outcome.calls_to("akkoro.std.http", "get").is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected function to fail")]
+    fn assert_failure_on_success_panics() {
+        outcome(Some(Status::Success(Vec::new()))).assert_failure();
+    }
+
+    #[test]
+    #[should_panic(expected = "did not respond")]
+    fn assert_success_without_response_panics() {
+        outcome(None).assert_success();
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a log message")]
+    fn assert_logged_missing_message_panics() {
+        outcome(None).assert_logged("goodbye");
+    }
+}
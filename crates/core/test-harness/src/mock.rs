@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tracing::{info, warn};
+
+use assemblylift_core_iomod::registry::{RegistryChannelMessage, RegistryRx};
+
+use crate::IomodCall;
+
+/// Canned IOmod responses, keyed by IOmod coordinates & method name
+pub type MockMap = HashMap<(String, String), Vec<u8>>;
+
+/// Stand in for the IOmod registry, answering each call with its mocked response.
+/// Calls with no mock are answered with an empty response, and recorded like any other call.
+pub(crate) async fn serve_mocks(
+    mut rx: RegistryRx,
+    mocks: MockMap,
+    calls: Arc<Mutex<Vec<IomodCall>>>,
+) {
+    while let Some(msg) = rx.recv().await {
+        let key = (msg.iomod_coords.clone(), msg.method_name.clone());
+        let response = match mocks.get(&key) {
+            Some(response) => {
+                info!("answering call @ {}.{} with mock", key.0, key.1);
+                response.clone()
+            }
+            None => {
+                warn!("no mock registered for call @ {}.{}", key.0, key.1);
+                Vec::new()
+            }
+        };
+
+        calls.lock().unwrap().push(IomodCall {
+            coordinates: key.0.clone(),
+            method: key.1.clone(),
+            input: msg.payload.clone(),
+            mocked: mocks.contains_key(&key),
+        });
+
+        if let Some(responder) = msg.responder {
+            let _ = responder
+                .send(RegistryChannelMessage {
+                    iomod_coords: key.0,
+                    method_name: key.1,
+                    payload_type: "IOMOD_RESPONSE",
+                    payload: response,
+                    responder: None,
+                })
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assemblylift_core_iomod::registry::registry_channel;
+
+    use super::*;
+
+    async fn call(
+        tx: &assemblylift_core_iomod::registry::RegistryTx,
+        coordinates: &str,
+        method: &str,
+    ) -> Vec<u8> {
+        let (responder, mut response) = registry_channel(1);
+        tx.send(RegistryChannelMessage {
+            iomod_coords: coordinates.to_string(),
+            method_name: method.to_string(),
+            payload_type: "IOMOD_REQUEST",
+            payload: b"{}".to_vec(),
+            responder: Some(responder),
+        })
+        .await
+        .unwrap();
+        response.recv().await.unwrap().payload
+    }
+
+    #[tokio::test]
+    async fn answers_with_mocks_and_records_calls() {
+        let mut mocks = MockMap::new();
+        mocks.insert(
+            ("akkoro.std.http".to_string(), "request".to_string()),
+            b"pong".to_vec(),
+        );
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = registry_channel(8);
+        tokio::spawn(serve_mocks(rx, mocks, calls.clone()));
+
+        assert_eq!(call(&tx, "akkoro.std.http", "request").await, b"pong");
+        assert!(call(&tx, "akkoro.aws.dynamodb", "get_item")
+            .await
+            .is_empty());
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].mocked);
+        assert_eq!(calls[0].input, b"{}");
+        assert!(!calls[1].mocked);
+        assert_eq!(calls[1].method, "get_item");
+    }
+}
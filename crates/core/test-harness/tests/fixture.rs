@@ -0,0 +1,56 @@
+//! Runs the `echo` fixture function, a real `#[handler]` component, in the harness. Building the fixture needs the
+//! `wasm32-wasi` target (`rustup target add wasm32-wasi`).
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use assemblylift_test_harness::TestHarness;
+
+const WASI_ADAPTER: &[u8] =
+    include_bytes!("../../../cli/src/commands/cast/wasm/wasi_snapshot_preview1.command.wasm");
+
+/// Build the fixture as `asml cast` builds a Rust function, returning the path of its component
+fn build_fixture(name: &str) -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixture_dir = manifest_dir.join("tests/fixtures").join(name);
+    let target_dir = manifest_dir.join("../../../target/test-harness-fixtures");
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "build",
+            "--release",
+            "--target",
+            "wasm32-wasi",
+            "--manifest-path",
+        ])
+        .arg(fixture_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .output()
+        .expect("could not run cargo");
+    assert!(
+        output.status.success(),
+        "could not build fixture {}:\n{}",
+        name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let release_dir = target_dir.join("wasm32-wasi/release");
+    let module = std::fs::read(release_dir.join(format!("{}.wasm", name))).unwrap();
+    let component = assemblylift_core::wasm::make_wasi_component(module, WASI_ADAPTER).unwrap();
+    let component_path = release_dir.join(format!("{}.component.wasm", name));
+    std::fs::write(&component_path, component).unwrap();
+    component_path
+}
+
+#[test]
+fn runs_a_handler_component() {
+    let outcome = TestHarness::new(build_fixture("echo"))
+        .with_input(br#"{"name":"world"}"#.to_vec())
+        .run()
+        .unwrap();
+
+    outcome.assert_success();
+    let response: serde_json::Value = outcome.success_json();
+    assert_eq!(response["echo"]["name"], "world");
+    outcome.assert_logged("echoing \"world\"");
+}
@@ -0,0 +1,9 @@
+use assemblylift_core_guest::*;
+
+#[handler]
+async fn main() {
+    let input: serde_json::Value =
+        serde_json::from_slice(&ctx.input).expect("could not parse function input as JSON");
+    FunctionContext::log(format!("echoing {}", input["name"]));
+    FunctionContext::success(serde_json::json!({ "echo": input }).to_string());
+}
@@ -1,14 +1,17 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use anyhow::anyhow;
 use handlebars::Handlebars;
 use serde::{Deserialize, Serialize};
 
-use super::projectfs::Project as ProjectFs;
-use crate::providers::{Platform, Provider, ProviderFactory};
+use super::projectfs::{infra_dir, Project as ProjectFs};
+use crate::providers::{kubernetes, Platform, Provider, ProviderFactory, HAS_ARM64_OPTION};
+use crate::validate::{ARCHITECTURES, DEFAULT_PYTHON_VERSION, DEFAULT_RUBY_VERSION, LANGUAGES};
 use crate::{
-    concat, concat_cast, snake_case, toml, CastError, CastResult, ContentType, Fragment, StringMap,
+    concat, concat_cast, quote, snake_case, to_snake_case, toml, CastError, CastResult,
+    ContentType, Fragment, StringMap,
 };
 
 // NOTE The Context structure should provide everything at each level needed to cast to HCL, meaning
@@ -23,7 +26,9 @@ use crate::{
 #[derive(Serialize, Deserialize)]
 pub struct Context {
     pub project: Project,
-    pub terraform: Option<Terraform>,
+    pub terraform: Terraform,
+    /// The environment selected with `--env`, if any
+    pub environment: Option<String>,
     pub platforms: Vec<Platform>,
     pub domains: Vec<Domain>,
     pub services: Vec<Service>,
@@ -34,12 +39,35 @@ pub struct Context {
 
 impl Context {
     pub fn from_project(
-        project: Rc<ProjectFs>,
+        project: Arc<ProjectFs>,
         manifest: toml::asml::Manifest,
+        environment: Option<&str>,
     ) -> Result<Self, String> {
         let mut ctx_services: Vec<Service> = Vec::new();
         let mut ctx_iomods: Vec<Iomod> = Vec::new();
 
+        let environment = match environment {
+            Some(name) => Some(
+                manifest
+                    .environment(name)
+                    .ok_or(format!(
+                        "environment `{}` not found in assemblylift.toml manifest",
+                        name
+                    ))?
+                    .clone(),
+            ),
+            None => None,
+        };
+        let manifest = match &environment {
+            Some(environment) => manifest.with_environment(environment)?,
+            None => manifest,
+        };
+        // Resource names are all derived from the project name, so suffixing it keeps each environment's resources apart
+        let project_name = match &environment {
+            Some(environment) => format!("{}-{}", project.name, environment.name),
+            None => project.name.clone(),
+        };
+
         let ctx_platforms: Vec<Platform> = manifest.platforms.iter().map(Platform::from).collect();
 
         fn find_platform(platforms: Vec<Platform>, platform_id: &str, resource_id: &str) -> Result<Platform, String> {
@@ -84,7 +112,7 @@ impl Context {
                     reg.provider.options.clone(),
                     platform.clone(),
                 )
-                .unwrap();
+                .map_err(|e| e.to_string())?;
 
                 validate_provider_platform(&provider, platform)?;
 
@@ -109,7 +137,7 @@ impl Context {
                     domain.provider.options.clone(),
                     platform.clone(),
                 )
-                .unwrap();
+                .map_err(|e| e.to_string())?;
 
                 validate_provider_platform(&provider, platform)?;
 
@@ -126,30 +154,33 @@ impl Context {
         let ctx_authorizers: Vec<Authorizer> = manifest
             .authorizers()
             .iter()
-            .map(|authorizer| Authorizer {
-                id: authorizer.id.clone(),
-                r#type: authorizer.auth_type.clone(),
-                scopes: authorizer.scopes.clone().unwrap_or(Vec::<String>::new()),
-                jwt_config: match authorizer.auth_type.clone().to_lowercase().as_str() {
-                    "jwt" => Some(AuthorizerJwt {
-                        audience: authorizer
-                            .audience
-                            .clone()
-                            .expect("JWT authorizer requires audience field"),
-                        issuer: authorizer
-                            .issuer
-                            .clone()
-                            .expect("JWT authorizer requires issuer field"),
-                    }),
-                    _ => None,
-                },
+            .map(|authorizer| {
+                Ok(Authorizer {
+                    id: authorizer.id.clone(),
+                    r#type: authorizer.auth_type.clone(),
+                    scopes: authorizer.scopes.clone().unwrap_or(Vec::<String>::new()),
+                    jwt_config: match authorizer.auth_type.clone().to_lowercase().as_str() {
+                        "jwt" => Some(AuthorizerJwt {
+                            audience: authorizer.audience.clone().ok_or(format!(
+                                "JWT authorizer `{}` requires audience field",
+                                authorizer.id
+                            ))?,
+                            issuer: authorizer.issuer.clone().ok_or(format!(
+                                "JWT authorizer `{}` requires issuer field",
+                                authorizer.id
+                            ))?,
+                        }),
+                        _ => None,
+                    },
+                })
             })
-            .collect();
+            .collect::<Result<Vec<_>, String>>()?;
 
         for service_ref in manifest.services {
             let mut service_path = project.service_dir(service_ref.name.clone()).dir();
             service_path.push("service.toml");
-            let service_manifest = toml::service::Manifest::read(&service_path).unwrap();
+            let service_manifest =
+                toml::service::Manifest::read(&service_path).map_err(|e| e.to_string())?;
 
             // let service = service_manifest.service();
             let functions = service_manifest.functions.clone();
@@ -168,10 +199,56 @@ impl Context {
                 };
                 // FIXME language should not be optional at context level
                 let language = function.language.clone().unwrap_or("rust".to_string());
-                let environment_variables = function
-                    .environment
-                    .clone()
-                    .unwrap_or(StringMap::<String>::new())
+                let architecture = function.architecture.clone().unwrap_or("x86_64".to_string());
+                if !ARCHITECTURES.contains(&architecture.as_str()) {
+                    return Err(format!(
+                        "function `{}` has unknown architecture `{}`; expected x86_64 or arm64",
+                        function.name, architecture
+                    ));
+                }
+                let ruby_version = match (language.as_str(), &function.ruby_version) {
+                    ("ruby", version) => Some(
+                        version
+                            .clone()
+                            .unwrap_or(DEFAULT_RUBY_VERSION.to_string()),
+                    ),
+                    (_, None) => None,
+                    (_, Some(_)) => {
+                        return Err(format!(
+                            "function `{}` sets a ruby_version, but its language is not ruby",
+                            function.name
+                        ))
+                    }
+                };
+                let python_version = match (language.as_str(), &function.python_version) {
+                    ("python", version) => Some(
+                        version
+                            .clone()
+                            .unwrap_or(DEFAULT_PYTHON_VERSION.to_string()),
+                    ),
+                    (_, None) => None,
+                    (_, Some(_)) => {
+                        return Err(format!(
+                            "function `{}` sets a python_version, but its language is not python",
+                            function.name
+                        ))
+                    }
+                };
+                let function_override = environment
+                    .as_ref()
+                    .and_then(|e| e.function_override(&service_ref.name, &function.name));
+                let mut environment_variables = function.environment.clone().unwrap_or_default();
+                if let Some(variables) = environment.as_ref().and_then(|e| e.environment.clone()) {
+                    environment_variables.extend(variables);
+                }
+                if let Some(variables) = function_override.and_then(|f| f.environment.clone()) {
+                    environment_variables.extend(variables);
+                }
+                let size = function_override
+                    .and_then(|f| f.size_mb)
+                    .or(function.size_mb)
+                    .unwrap_or(1024u16);
+                let environment_variables = environment_variables
                     .iter()
                     .map(|e| (format!("__ASML_{}", e.0.clone()), e.1.clone()))
                     .collect::<StringMap<String>>();
@@ -179,7 +256,7 @@ impl Context {
                 ctx_functions.push(Function {
                     name: function.name.clone(),
                     service_name: service_ref.name.clone(),
-                    project_name: project.name.clone(),
+                    project_name: project_name.clone(),
                     coordinates: format!(
                         "{}.{}.{}",
                         &project.name, &service_ref.name, &function.name
@@ -196,18 +273,22 @@ impl Context {
                     runtime_environment: match language.as_str() {
                         "rust" => "native",
                         "ruby" => "ruby",
+                        "python" => "python",
+                        "javascript" => "javascript",
+                        _ if function.build.is_some() => "native",
                         _ => return Err("default".into()),
                     }
                     .into(),
                     // FIXME don't hardcode
                     runtime_version: "0.4.0-beta.0".into(),
-                    size: function.size_mb.unwrap_or(1024u16),
+                    size,
                     timeout: function.timeout_seconds.unwrap_or(5u16),
                     cpu_compat_mode: function
                         .cpu_compat_mode
                         .clone()
                         .unwrap_or("default".to_string()),
                     precompiled: precompile,
+                    architecture,
                     http: match &function.clone().http.as_ref() {
                         Some(http) => Some(Http {
                             verb: http.verb.clone(),
@@ -227,6 +308,21 @@ impl Context {
                         },
                         None => None,
                     },
+                    cpu_millicores: cpu_millicores(size),
+                    autoscaling: match &function.autoscaling {
+                        Some(autoscaling) => {
+                            Some(Autoscaling::from_manifest(autoscaling, &function.name)?)
+                        }
+                        None => None,
+                    },
+                    build: Build::from_manifest(function.build.as_ref(), &language, &function.name)?,
+                    components: function.components.clone().unwrap_or_default(),
+                    ruby_version,
+                    python_version,
+                    triggers: Triggers::from_manifest(
+                        &function.triggers.clone().unwrap_or_default(),
+                        &function.name,
+                    )?,
                     environment_variables,
                 });
             }
@@ -244,12 +340,18 @@ impl Context {
                 &service_ref.name
             )?);
 
+            let mut service_provider_options = service_provider.options;
+            // So that the provider boots only the runtimes the service's functions need
+            if ctx_functions.iter().any(|f| f.architecture == "arm64") {
+                service_provider_options.insert(HAS_ARM64_OPTION.into(), "true".into());
+            }
+
             let service_provider = ProviderFactory::new_provider(
                 &service_provider.name,
-                service_provider.options,
+                service_provider_options,
                 service_platform.clone(),
             )
-            .unwrap();
+            .map_err(|e| e.to_string())?;
 
             validate_provider_platform(&service_provider, service_platform)?;
 
@@ -267,7 +369,7 @@ impl Context {
                 gateway_provider.options,
                 gateway_platform.clone(),
             )
-            .unwrap();
+            .map_err(|e| e.to_string())?;
 
             validate_provider_platform(&gateway_provider, gateway_platform)?;
 
@@ -287,7 +389,7 @@ impl Context {
             ctx_services.push(Service {
                 id: format!("{}_{}", service_ref.name.clone(), service_provider.platform().unwrap().id),
                 name: service_ref.name.clone(),
-                project_name: project.name.clone(),
+                project_name: project_name.clone(),
                 provider: service_provider,
                 gateway: Gateway {
                     provider: gateway_provider,
@@ -315,6 +417,7 @@ impl Context {
                 },
                 is_root: service_ref.is_root,
                 has_ruby: ctx_functions.clone().iter().find(|&f| f.language.eq("ruby")).is_some(),
+                has_python: ctx_functions.iter().any(|f| f.language.eq("python")),
             });
 
             for iomod in iomods {
@@ -333,16 +436,15 @@ impl Context {
 
         Ok(Context {
             project: Project {
-                name: manifest.project.name.clone(),
+                name: project_name.clone(),
                 path: (*project.dir()).to_str().unwrap().into(),
             },
-            terraform: match manifest.terraform {
-                Some(tf) => Some(Terraform {
-                    state_bucket_name: tf.state_bucket_name,
-                    lock_table_name: tf.lock_table_name,
-                }),
-                None => None,
-            },
+            terraform: Terraform::from_manifest(
+                manifest.terraform.as_ref(),
+                &project_name,
+                environment.as_ref().map(|e| e.name.as_str()),
+            )?,
+            environment: environment.map(|e| e.name),
             platforms: ctx_platforms,
             domains: ctx_domains,
             services: ctx_services,
@@ -442,8 +544,10 @@ impl Context {
             .unwrap_or(Ok(Vec::new()))?;
 
         let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
         hbs.register_helper("concat", Box::new(concat));
         hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_helper("quote", Box::new(quote));
         hbs.register_template_string("context", include_str!("templates/context.tf.handlebars"))
             .unwrap();
         hbs.register_template_string(
@@ -456,6 +560,16 @@ impl Context {
             include_str!("providers/api_gateway/templates/api_inst.tf.handlebars"),
         )
         .unwrap();
+        hbs.register_template_string(
+            &crate::providers::api_gateway_rest::provider_name(),
+            include_str!("providers/api_gateway/templates/api_inst.tf.handlebars"),
+        )
+        .unwrap();
+        hbs.register_template_string(
+            &crate::providers::lambda_url::provider_name(),
+            include_str!("providers/api_gateway/templates/api_inst.tf.handlebars"),
+        )
+        .unwrap();
         hbs.register_template_string(
             &format!("{}-root", crate::providers::route53::provider_name()),
             include_str!("providers/route53/templates/dns_inst_root.tf.handlebars"),
@@ -481,11 +595,26 @@ impl Context {
             include_str!("providers/kubernetes/templates/service_inst.tf.handlebars"),
         )
         .unwrap();
+        hbs.register_template_string(
+            &crate::providers::gateway_api::provider_name(),
+            include_str!("providers/gateway_api/templates/api_inst.tf.handlebars"),
+        )
+        .unwrap();
         hbs.register_template_string(
             &crate::providers::gloo::provider_name(),
             include_str!("providers/gloo/templates/api_inst.tf.handlebars"),
         )
         .unwrap();
+        hbs.register_template_string(
+            &crate::providers::knative::provider_name(),
+            include_str!("providers/knative/templates/service_inst.tf.handlebars"),
+        )
+        .unwrap();
+        hbs.register_template_string(
+            &crate::providers::knative_ingress::provider_name(),
+            include_str!("providers/knative_ingress/templates/api_inst.tf.handlebars"),
+        )
+        .unwrap();
 
         let mut ctx_out = vec![Fragment {
             content_type: ContentType::HCL,
@@ -493,9 +622,24 @@ impl Context {
             write_path: PathBuf::from(format!("net/{}.tf", self.project.name)),
         }];
 
+        let mut kustomize_out = kustomizations(&svc_out);
+
         fragments.append(&mut svc_out);
         fragments.append(&mut dns_out);
         fragments.append(&mut ctx_out);
+        fragments.append(&mut kustomize_out);
+
+        // Infrastructure is cast per environment, while build artifacts (e.g. Dockerfiles) are shared between them
+        if let Some(environment) = &self.environment {
+            for fragment in fragments
+                .iter_mut()
+                .filter(|f| f.content_type != ContentType::Dockerfile)
+            {
+                if let Ok(path) = fragment.write_path.strip_prefix("net") {
+                    fragment.write_path = infra_dir(Some(environment)).join(path);
+                }
+            }
+        }
 
         Ok(fragments)
     }
@@ -505,6 +649,61 @@ impl Context {
     }
 }
 
+/// Build a Kustomize base for each directory YAML manifests were cast into, and a project base at `net/`
+/// which includes all of them, so that environments can be deployed as overlays.
+fn kustomizations(fragments: &[Fragment]) -> Vec<Fragment> {
+    let mut bases: std::collections::BTreeMap<PathBuf, Vec<String>> = Default::default();
+    for fragment in fragments
+        .iter()
+        .filter(|f| f.content_type == ContentType::YAML)
+    {
+        if let (Some(dir), Some(file)) = (
+            fragment.write_path.parent(),
+            fragment.write_path.file_name(),
+        ) {
+            bases
+                .entry(dir.to_path_buf())
+                .or_default()
+                .push(file.to_string_lossy().to_string());
+        }
+    }
+    if bases.is_empty() {
+        return Vec::new();
+    }
+
+    let kustomization = |resources: Vec<String>| {
+        let mut content = String::from(
+            "apiVersion: kustomize.config.k8s.io/v1beta1\nkind: Kustomization\nresources:\n",
+        );
+        for resource in resources {
+            content.push_str(&format!("  - {}\n", resource));
+        }
+        content
+    };
+
+    let project_resources = bases
+        .keys()
+        .map(|dir| dir.strip_prefix("net").unwrap_or(dir).display().to_string())
+        .collect();
+    let mut out: Vec<Fragment> = bases
+        .into_iter()
+        .map(|(dir, mut resources)| {
+            resources.sort();
+            Fragment {
+                content_type: ContentType::YAML,
+                content: kustomization(resources),
+                write_path: dir.join("kustomization.yaml"),
+            }
+        })
+        .collect();
+    out.push(Fragment {
+        content_type: ContentType::YAML,
+        content: kustomization(project_resources),
+        write_path: PathBuf::from("net/kustomization.yaml"),
+    });
+    out
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Project {
     pub name: String,
@@ -514,8 +713,72 @@ pub struct Project {
 
 #[derive(Serialize, Deserialize)]
 pub struct Terraform {
-    pub state_bucket_name: String,
-    pub lock_table_name: String,
+    pub backend: String,
+    pub config: BTreeMap<String, String>,
+}
+
+impl Terraform {
+    /// Resolve the backend block from the `[terraform]` manifest table. Without one, state is kept locally at
+    /// `terraform.tfstate` (or `terraform.<env>.tfstate`) in the project root; the backend path is relative to the
+    /// infrastructure directory.
+    pub fn from_manifest(
+        manifest: Option<&toml::asml::Terraform>,
+        project_name: &str,
+        environment: Option<&str>,
+    ) -> Result<Self, String> {
+        let manifest = manifest.cloned().unwrap_or_default();
+
+        let backend = manifest.backend.clone().unwrap_or(
+            match manifest.state_bucket_name.is_some() {
+                true => "s3".into(),
+                false => "local".into(),
+            },
+        );
+
+        let mut config: BTreeMap<String, String> = BTreeMap::new();
+        match backend.as_str() {
+            "s3" => {
+                let bucket = manifest
+                    .state_bucket_name
+                    .clone()
+                    .ok_or("the s3 terraform backend requires state_bucket_name".to_string())?;
+                config.insert("bucket".into(), bucket);
+                config.insert(
+                    "key".into(),
+                    format!("assemblylift/{}/terraform.tfstate", project_name),
+                );
+                if let Some(table) = manifest.lock_table_name.clone() {
+                    config.insert("dynamodb_table".into(), table);
+                }
+            }
+            "local" => {
+                let path = match environment {
+                    Some(environment) => format!("../../terraform.{}.tfstate", environment),
+                    None => "../terraform.tfstate".into(),
+                };
+                config.insert("path".into(), path);
+            }
+            "http" => {
+                if !manifest.options.as_ref().map_or(false, |o| o.contains_key("address")) {
+                    return Err("the http terraform backend requires the address option".into());
+                }
+            }
+            "kubernetes" => {
+                config.insert("secret_suffix".into(), format!("asml-{}", project_name));
+            }
+            other => {
+                return Err(format!(
+                    "unsupported terraform backend `{}`; expected one of s3, local, http, or kubernetes",
+                    other
+                ))
+            }
+        }
+        for (key, value) in manifest.options.unwrap_or_default() {
+            config.insert(key, value);
+        }
+
+        Ok(Self { backend, config })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -583,12 +846,46 @@ pub struct Service {
     pub domain: Option<Domain>,
     pub is_root: Option<bool>,
     pub has_ruby: bool,
+    pub has_python: bool,
 }
 
 impl Service {
     pub fn as_json(&self) -> anyhow::Result<serde_json::Value> {
         serde_json::to_value(self).map_err(|e| anyhow!(e))
     }
+
+    /// Addresses of the Terraform modules cast for the service, for plans & applies targeting only this service
+    pub fn terraform_targets(&self) -> Vec<String> {
+        let name = to_snake_case(&self.name);
+        let mut targets = Vec::new();
+        // Services cast as YAML manifests have no service or gateway module
+        if !kubernetes::manifests_output(&self.provider.options()).unwrap_or(false) {
+            targets.push(format!("module.{}_service", name));
+            targets.push(format!("module.{}_api", name));
+        }
+        if self.container_registry.is_some() {
+            targets.push(format!("module.{}_container_registry", name));
+        }
+        if self.domain.is_some() {
+            targets.push(format!("module.{}_dns", name));
+        }
+        targets
+    }
+
+    /// The fully-qualified domain name the service is served at, if it has a domain
+    pub fn domain_name(&self) -> Option<String> {
+        self.domain.as_ref().map(|domain| {
+            let mut name = String::new();
+            if !self.is_root.unwrap_or(false) {
+                name.push_str(&format!("{}.", self.name));
+            }
+            if !domain.map_to_root {
+                name.push_str(&format!("{}.", self.project_name));
+            }
+            name.push_str(&domain.dns_name);
+            name
+        })
+    }
 }
 
 impl From<&Service> for Service {
@@ -622,6 +919,7 @@ impl From<&Service> for Service {
             },
             is_root: value.is_root,
             has_ruby: value.has_ruby,
+            has_python: value.has_python,
         }
     }
 }
@@ -643,12 +941,33 @@ pub struct Function {
     pub timeout: u16,
     pub cpu_compat_mode: String,
     pub precompiled: bool,
+    /// The instruction set the function runs on: `x86_64` or `arm64`
+    pub architecture: String,
+    /// CPU share in proportion to `size`, as Lambda allocates it
+    pub cpu_millicores: u32,
+    pub autoscaling: Option<Autoscaling>,
+    pub triggers: Triggers,
+    pub build: Option<Build>,
+    /// Paths, relative to the project root, of components whose exports satisfy the function's imports
+    pub components: Vec<String>,
+    /// Version of the Ruby runtime, for `ruby` functions
+    pub ruby_version: Option<String>,
+    /// Version of the Python runtime, for `python` functions
+    pub python_version: Option<String>,
 }
 
 impl Function {
     pub fn as_json(&self) -> anyhow::Result<serde_json::Value> {
         serde_json::to_value(self).map_err(|e| anyhow!(e))
     }
+
+    /// The target triple the function is precompiled for
+    pub fn target_triple(&self) -> &'static str {
+        match self.architecture.as_str() {
+            "arm64" => "aarch64-linux-gnu",
+            _ => "x86_64-linux-gnu",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -657,6 +976,205 @@ pub struct Http {
     pub path: String,
 }
 
+/// Autoscaling as given in the function manifest. Defaults, such as the minimum replicas and which metric to scale
+/// on, are left to the service provider.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Autoscaling {
+    pub min_replicas: Option<u16>,
+    pub max_replicas: u16,
+    pub target_cpu_percent: Option<u16>,
+    pub target_rps: Option<u32>,
+    pub target_concurrency: Option<u16>,
+}
+
+impl Autoscaling {
+    pub(crate) fn from_manifest(
+        autoscaling: &toml::service::Autoscaling,
+        function_name: &str,
+    ) -> Result<Self, String> {
+        if autoscaling.max_replicas == 0
+            || autoscaling.min_replicas.unwrap_or(0u16) > autoscaling.max_replicas
+        {
+            return Err(format!(
+                "autoscaling for function `{}` requires min_replicas <= max_replicas, and max_replicas >= 1",
+                function_name
+            ));
+        }
+        Ok(Self {
+            min_replicas: autoscaling.min_replicas,
+            max_replicas: autoscaling.max_replicas,
+            target_cpu_percent: autoscaling.target_cpu_percent,
+            target_rps: autoscaling.target_rps,
+            target_concurrency: autoscaling.target_concurrency,
+        })
+    }
+}
+
+/// The command a function in a language other than the built-in [LANGUAGES] is built with
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Build {
+    pub command: String,
+    pub output: String,
+}
+
+impl Build {
+    pub(crate) fn from_manifest(
+        build: Option<&toml::service::Build>,
+        language: &str,
+        function_name: &str,
+    ) -> Result<Option<Self>, String> {
+        match (build, LANGUAGES.contains(&language)) {
+            (Some(build), false) => Ok(Some(Self {
+                command: build.command.clone(),
+                output: build.output.clone(),
+            })),
+            (None, false) => Err(format!(
+                "function `{}` in language `{}` requires a `build` with the command to build it",
+                function_name, language
+            )),
+            (Some(_), true) => Err(format!(
+                "function `{}` in language `{}` does not take a `build`; the CLI builds {} itself",
+                function_name,
+                language,
+                LANGUAGES.join(", ")
+            )),
+            (None, true) => Ok(None),
+        }
+    }
+}
+
+/// Lambda allocates one vCPU per 1769 MB of memory
+fn cpu_millicores(size_mb: u16) -> u32 {
+    (size_mb as u32 * 1000 / 1769).max(50)
+}
+
+/// A function's event-source triggers, grouped by kind so that templates can iterate each separately
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Triggers {
+    pub schedules: Vec<ScheduleTrigger>,
+    pub queues: Vec<QueueTrigger>,
+    pub topics: Vec<TopicTrigger>,
+    pub buckets: Vec<BucketTrigger>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleTrigger {
+    /// Five-field cron expression, as used by Kubernetes CronJobs
+    pub cron: String,
+    /// The same schedule as an EventBridge `cron()` expression
+    pub aws_expression: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QueueTrigger {
+    pub queue_arn: String,
+    pub batch_size: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TopicTrigger {
+    pub topic_arn: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BucketTrigger {
+    pub bucket: String,
+    pub events: Vec<String>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+}
+
+impl Triggers {
+    pub(crate) fn from_manifest(
+        triggers: &[toml::service::Trigger],
+        function_name: &str,
+    ) -> Result<Self, String> {
+        use toml::service::Trigger;
+
+        let mut ctx_triggers = Self::default();
+        for trigger in triggers {
+            match trigger {
+                Trigger::Schedule { cron } => ctx_triggers.schedules.push(ScheduleTrigger {
+                    cron: cron.clone(),
+                    aws_expression: aws_cron_expression(cron).map_err(|e| {
+                        format!("schedule trigger for function `{}`: {}", function_name, e)
+                    })?,
+                }),
+                Trigger::Sqs {
+                    queue_arn,
+                    batch_size,
+                } => ctx_triggers.queues.push(QueueTrigger {
+                    queue_arn: queue_arn.clone(),
+                    batch_size: batch_size.unwrap_or(10u16),
+                }),
+                Trigger::Sns { topic_arn } => ctx_triggers.topics.push(TopicTrigger {
+                    topic_arn: topic_arn.clone(),
+                }),
+                Trigger::S3 {
+                    bucket,
+                    events,
+                    prefix,
+                    suffix,
+                } => ctx_triggers.buckets.push(BucketTrigger {
+                    bucket: bucket.clone(),
+                    events: events
+                        .clone()
+                        .unwrap_or(vec!["s3:ObjectCreated:*".to_string()]),
+                    prefix: prefix.clone(),
+                    suffix: suffix.clone(),
+                }),
+            }
+        }
+        Ok(ctx_triggers)
+    }
+}
+
+/// Convert a five-field cron expression to EventBridge's six-field form, which adds a year field,
+/// requires `?` in one of day-of-month or day-of-week, and numbers the days of the week from 1 (Sunday).
+fn aws_cron_expression(cron: &str) -> Result<String, String> {
+    let fields: Vec<&str> = cron.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Err(format!(
+            "`{}` is not a five-field cron expression (minute hour day-of-month month day-of-week)",
+            cron
+        ));
+    }
+    let (minute, hour, month) = (fields[0], fields[1], fields[3]);
+    let (day_of_month, day_of_week) = match (fields[2], fields[4]) {
+        (dom, "*") => (dom.to_string(), "?".to_string()),
+        ("*", dow) => ("?".to_string(), aws_day_of_week(dow)),
+        _ => {
+            return Err(format!(
+                "`{}` restricts both day-of-month and day-of-week, which EventBridge does not support",
+                cron
+            ));
+        }
+    };
+    Ok(format!(
+        "cron({} {} {} {} {} *)",
+        minute, hour, day_of_month, month, day_of_week
+    ))
+}
+
+fn aws_day_of_week(dow: &str) -> String {
+    // Only the days themselves are renumbered, not any step after a `/`
+    let (days, step) = match dow.split_once('/') {
+        Some((days, step)) => (days, Some(step)),
+        None => (dow, None),
+    };
+    let days: String = days
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => char::from_digit(d % 7 + 1, 10).unwrap(),
+            None => c,
+        })
+        .collect();
+    match step {
+        Some(step) => format!("{}/{}", days, step),
+        None => days,
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Authorizer {
     pub id: String,
@@ -678,3 +1196,116 @@ pub struct Iomod {
     pub coordinates: String,
     pub version: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cron_adds_year_and_question_mark() {
+        assert_eq!(
+            aws_cron_expression("0 12 * * *"),
+            Ok("cron(0 12 * * ? *)".to_string())
+        );
+        assert_eq!(
+            aws_cron_expression("*/5 * 1 * *"),
+            Ok("cron(*/5 * 1 * ? *)".to_string())
+        );
+        assert_eq!(
+            aws_cron_expression("0 9 * * 1-5"),
+            Ok("cron(0 9 ? * 2-6 *)".to_string())
+        );
+    }
+
+    #[test]
+    fn cron_renumbers_days_of_week() {
+        assert_eq!(aws_day_of_week("0"), "1");
+        assert_eq!(aws_day_of_week("7"), "1");
+        assert_eq!(aws_day_of_week("6"), "7");
+        assert_eq!(aws_day_of_week("0,3"), "1,4");
+        assert_eq!(aws_day_of_week("1-5/2"), "2-6/2");
+        assert_eq!(aws_day_of_week("MON"), "MON");
+    }
+
+    #[test]
+    fn cron_rejects_invalid_expressions() {
+        assert!(aws_cron_expression("0 12 1 * 1").is_err());
+        assert!(aws_cron_expression("0 12 * *").is_err());
+        assert!(aws_cron_expression("0 12 * * * 2024").is_err());
+    }
+
+    /// The context of a project with `assemblylift.toml` and the given service manifests
+    fn context(assemblylift_toml: &str, services: &[(&str, &str)]) -> Context {
+        let dir = tempfile::tempdir().unwrap();
+        let project = ProjectFs::new("shop".into(), Some(dir.path().to_path_buf()));
+        for (name, manifest) in services {
+            let service_dir = project.service_dir(name.to_string()).dir();
+            std::fs::create_dir_all(&service_dir).unwrap();
+            std::fs::write(service_dir.join("service.toml"), manifest).unwrap();
+        }
+        let manifest = ::toml::from_str(assemblylift_toml).unwrap();
+        Context::from_project(project, manifest, None).unwrap()
+    }
+
+    const SERVICE: &str = r#"[gateway]
+provider = { name = "gloo" }
+
+[[functions]]
+name = "create"
+language = "rust"
+http = { verb = "POST", path = "/orders" }
+"#;
+
+    fn k8s_project(options: &str) -> String {
+        format!(
+            r#"[project]
+name = "shop"
+
+[[platforms]]
+id = "cluster"
+name = "kubernetes"
+options = {{ config_path = "~/.kube/config" }}
+
+[[platforms]]
+id = "aws"
+name = "aws"
+options = {{ region = "us-east-1" }}
+
+[[registries]]
+id = "ecr"
+provider = {{ name = "ecr", platform_id = "aws" }}
+
+[[services]]
+name = "order-api"
+provider = {{ name = "k8s", platform_id = "cluster", options = {{ {} }} }}
+registry_id = "ecr"
+"#,
+            options
+        )
+    }
+
+    #[test]
+    fn terraform_targets_are_snake_case() {
+        let ctx = context(&k8s_project(""), &[("order-api", SERVICE)]);
+        assert_eq!(
+            ctx.service("order-api").unwrap().terraform_targets(),
+            vec![
+                "module.order_api_service",
+                "module.order_api_api",
+                "module.order_api_container_registry",
+            ]
+        );
+    }
+
+    #[test]
+    fn manifests_output_targets_only_terraform_modules() {
+        let ctx = context(
+            &k8s_project(r#"output = "manifests", registry_url = "registry.example.com""#),
+            &[("order-api", SERVICE)],
+        );
+        assert_eq!(
+            ctx.service("order-api").unwrap().terraform_targets(),
+            vec!["module.order_api_container_registry"]
+        );
+    }
+}
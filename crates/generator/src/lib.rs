@@ -6,9 +6,11 @@ pub mod context;
 pub mod projectfs;
 pub mod providers;
 pub mod toml;
+pub mod validate;
 
 handlebars_helper!(concat: |s1: String, s2: String| format!("{}{}", s1, s2));
-handlebars_helper!(snake_case: |s: String| s.replace("-", "_").replace(".", "_"));
+handlebars_helper!(snake_case: |s: String| to_snake_case(&s));
+handlebars_helper!(quote: |s: String| serde_json::to_string(&s).unwrap());
 
 pub type CastResult<T> = std::result::Result<T, CastError>;
 pub type Map<K, V> = std::collections::HashMap<K, V>;
@@ -22,6 +24,7 @@ pub struct CastError(pub String);
 pub enum ContentType {
     HCL,
     Dockerfile,
+    YAML,
 }
 
 /// A `Fragment` is the output of a `cast` operation. Its contents may be part of, or the entirety of,
@@ -67,3 +70,8 @@ pub fn concat_cast<T>(accum: CastResult<Vec<T>>, v: CastResult<Vec<T>>) -> CastR
     out.append(&mut v?);
     Ok(out)
 }
+
+/// Convert a resource name to an identifier valid in Terraform, as the `snake_case` template helper does
+pub fn to_snake_case(s: &str) -> String {
+    s.replace("-", "_").replace(".", "_")
+}
@@ -1,6 +1,6 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::sync::Arc;
 
 use path_abs::{PathAbs, PathDir};
 use serde::{Deserialize, Serialize};
@@ -36,6 +36,15 @@ impl ServiceDir {
     }
 }
 
+/// The directory infrastructure is cast into and Terraform is run from, relative to the project root: `net`, or
+/// `net/<env>` for a named environment
+pub fn infra_dir(environment: Option<&str>) -> PathBuf {
+    match environment {
+        Some(environment) => PathBuf::from("net").join(environment),
+        None => PathBuf::from("net"),
+    }
+}
+
 pub struct NetDir {
     dir: PathBuf,
 }
@@ -56,6 +65,11 @@ impl NetDir {
         ServiceDir::new(path)
     }
 
+    /// Hashes of the inputs of each function's last build, used to skip unchanged functions
+    pub fn build_cache_path(&self) -> PathBuf {
+        self.dir.join(".build-cache.json")
+    }
+
     pub fn runtime_dir(&self) -> PathBuf {
         PathBuf::from(&*format!(
             "{}/runtime",
@@ -65,7 +79,7 @@ impl NetDir {
 }
 
 impl Project {
-    pub fn new(name: String, project_path: Option<PathBuf>) -> Rc<Self> {
+    pub fn new(name: String, project_path: Option<PathBuf>) -> Arc<Self> {
         let project_path = match project_path {
             Some(path) => {
                 if !Path::exists(&*path.clone()) {
@@ -106,7 +120,7 @@ impl Project {
             PathAbs::from(PathDir::new(path.clone()).unwrap()).as_path(),
         );
 
-        Rc::new(Self {
+        Arc::new(Self {
             name,
             project_path,
             service_path,
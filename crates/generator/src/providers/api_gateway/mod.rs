@@ -10,7 +10,7 @@ use crate::{
 
 use super::{
     GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
-    ServiceProvider, Platform,
+    ServiceProvider, Platform, OptionSpec,
 };
 
 pub fn provider_name() -> String {
@@ -53,6 +53,10 @@ impl Provider for ApiGatewayProvider {
         self.options.clone()
     }
 
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
     fn set_option(&mut self, key: &str, value: &str) {
         self.options.insert(key.into(), value.into()).unwrap();
     }
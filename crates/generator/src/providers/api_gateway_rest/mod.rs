@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    context::{Authorizer, Service},
+    providers::aws_lambda,
+    quote, snake_case, CastError, CastResult, ContentType, Fragment, Options,
+};
+
+use super::{
+    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
+    ServiceProvider, Platform, OptionSpec, OptionType,
+};
+
+pub fn provider_name() -> String {
+    "aws-apigw-rest".into()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ApiGatewayRestProvider {
+    #[serde(default = "provider_name")]
+    name: String,
+    options: Options,
+    platform: Option<Platform>,
+}
+
+impl ApiGatewayRestProvider {
+    pub fn new(options: Options, platform: Option<Platform>) -> Box<Self> {
+        Box::new(Self {
+            name: provider_name(),
+            options,
+            platform,
+        })
+    }
+
+    fn numeric_option(&self, key: &str) -> CastResult<Option<u64>> {
+        self.options
+            .get(key)
+            .map(|value| {
+                value.parse::<u64>().map_err(|_| {
+                    CastError(format!("option {} of {} must be a number", key, self.name()))
+                })
+            })
+            .transpose()
+    }
+
+    fn usage_plan(&self) -> CastResult<Value> {
+        let rate_limit = self.numeric_option("usage_plan_rate_limit")?;
+        let burst_limit = self.numeric_option("usage_plan_burst_limit")?;
+        let quota_limit = self.numeric_option("usage_plan_quota_limit")?;
+        let quota_period = self
+            .options
+            .get("usage_plan_quota_period")
+            .map(|p| p.to_uppercase())
+            .unwrap_or("DAY".into());
+        if !["DAY", "WEEK", "MONTH"].contains(&quota_period.as_str()) {
+            return Err(CastError(format!(
+                "usage_plan_quota_period must be one of day, week, or month; got {}",
+                quota_period
+            )));
+        }
+
+        let api_key_required = self.options.get("api_key_required").map(|v| v == "true").unwrap_or(false);
+        if !api_key_required && rate_limit.is_none() && burst_limit.is_none() && quota_limit.is_none() {
+            return Ok(Value::Null);
+        }
+        Ok(json!({
+            "rate_limit": rate_limit,
+            "burst_limit": burst_limit,
+            "quota_limit": quota_limit,
+            "quota_period": quota_period,
+        }))
+    }
+
+    fn request_validator(&self) -> CastResult<Value> {
+        let (body, parameters) = match self.options.get("validate_request").map(|v| v.as_str()) {
+            None | Some("none") => return Ok(Value::Null),
+            Some("body") => (true, false),
+            Some("parameters") => (false, true),
+            Some("all") => (true, true),
+            Some(other) => {
+                return Err(CastError(format!(
+                    "validate_request must be one of none, body, parameters, or all; got {}",
+                    other
+                )))
+            }
+        };
+        Ok(json!({ "body": body, "parameters": parameters }))
+    }
+
+    fn service_json(&self, service: &Service) -> CastResult<Value> {
+        let mut resources: BTreeMap<String, Value> = BTreeMap::new();
+        let mut authorizers: BTreeMap<String, Value> = BTreeMap::new();
+        let mut methods: Vec<Value> = Vec::new();
+
+        for function in &service.functions {
+            let http = match &function.http {
+                Some(http) => http,
+                None => continue,
+            };
+
+            // Each segment of the path is its own resource, nested under the resource of the segment before it
+            let mut parent_id: Option<String> = None;
+            let mut path_params: Vec<String> = Vec::new();
+            for part in http.path.split('/').filter(|p| !p.is_empty()) {
+                let id = match &parent_id {
+                    Some(parent) => format!("{}-{}", parent, resource_id(part)),
+                    None => format!("r-{}", resource_id(part)),
+                };
+                if let Some(param) = part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+                    path_params.push(param.trim_end_matches('+').to_string());
+                }
+                resources.entry(id.clone()).or_insert(json!({
+                    "id": id,
+                    "parent_id": parent_id,
+                    "path_part": part,
+                }));
+                parent_id = Some(id);
+            }
+
+            let authorization = match &function.authorizer {
+                Some(authorizer) => {
+                    let authorization = self.authorization(authorizer)?;
+                    if let Some(cognito) = authorization.get("cognito") {
+                        authorizers.insert(authorizer.id.clone(), cognito.clone());
+                    }
+                    authorization
+                }
+                None => json!({ "type": "NONE" }),
+            };
+
+            methods.push(json!({
+                "function_name": function.name,
+                "resource_id": parent_id,
+                "http_method": http.verb.to_uppercase(),
+                "path_params": path_params,
+                "authorization": authorization,
+            }));
+        }
+
+        let mut service_json = service.as_json().map_err(|e| CastError(e.to_string()))?;
+        service_json["resources"] = resources.into_values().collect::<Vec<_>>().into();
+        service_json["methods"] = methods.into();
+        service_json["authorizers"] = authorizers.into_values().collect::<Vec<_>>().into();
+        service_json["usage_plan"] = self.usage_plan()?;
+        service_json["request_validator"] = self.request_validator()?;
+        service_json["api_key_required"] = self
+            .options
+            .get("api_key_required")
+            .map(|v| v == "true")
+            .unwrap_or(false)
+            .into();
+        service_json["stage_name"] = self
+            .options
+            .get("stage")
+            .cloned()
+            .unwrap_or("default".into())
+            .into();
+        Ok(service_json)
+    }
+
+    /// REST APIs have no JWT authorizer; JWTs are only accepted from a Cognito user pool issuer, which is mapped to a
+    /// COGNITO_USER_POOLS authorizer.
+    fn authorization(&self, authorizer: &Authorizer) -> CastResult<Value> {
+        match authorizer.r#type.to_lowercase().as_str() {
+            "iam" => Ok(json!({ "type": "AWS_IAM" })),
+            "jwt" => {
+                let issuer = &authorizer.jwt_config.as_ref().unwrap().issuer;
+                let (region, pool_id) = cognito_user_pool(issuer).ok_or(CastError(format!(
+                    "authorizer {} has issuer {}; {} only supports JWT authorizers with a Cognito user pool issuer",
+                    authorizer.id,
+                    issuer,
+                    self.name()
+                )))?;
+                Ok(json!({
+                    "type": "COGNITO_USER_POOLS",
+                    "scopes": authorizer.scopes,
+                    "cognito": {
+                        "id": authorizer.id,
+                        "region": region,
+                        "pool_id": pool_id,
+                    },
+                }))
+            }
+            other => Err(CastError(format!(
+                "{} does not support authorizers of type {}",
+                self.name(),
+                other
+            ))),
+        }
+    }
+}
+
+/// A Terraform-safe name for a path segment. Distinct segments always get distinct names: `_` becomes `__`, and any
+/// other character which is not alphanumeric becomes its code point in hex between underscores, e.g. `{` is `_7b_`.
+/// Segments of a path are joined with `-`, which never appears in a name.
+fn resource_id(path_part: &str) -> String {
+    let mut id = String::with_capacity(path_part.len());
+    for c in path_part.chars() {
+        match c {
+            c if c.is_ascii_alphanumeric() => id.push(c),
+            '_' => id.push_str("__"),
+            c => id.push_str(&format!("_{:x}_", c as u32)),
+        }
+    }
+    id
+}
+
+/// Parse the region & pool ID from a Cognito issuer URL, e.g. `https://cognito-idp.us-east-1.amazonaws.com/us-east-1_abc123`
+fn cognito_user_pool(issuer: &str) -> Option<(String, String)> {
+    let rest = issuer.strip_prefix("https://cognito-idp.")?;
+    let (region, pool_id) = rest.split_once(".amazonaws.com/")?;
+    Some((region.to_string(), pool_id.trim_end_matches('/').to_string()))
+}
+
+#[typetag::serde]
+impl Provider for ApiGatewayRestProvider {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn platform(&self) -> Option<Platform> {
+        self.platform.clone()
+    }
+
+    fn compatible_platforms(&self) -> Vec<String> {
+        vec!["aws".into()]
+    }
+
+    fn options(&self) -> Options {
+        self.options.clone()
+    }
+
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        vec![
+            OptionSpec::optional("stage", OptionType::String, "Name of the API stage")
+                .with_default("default"),
+            OptionSpec::optional(
+                "api_key_required",
+                OptionType::Bool,
+                "Require an API key on every method",
+            )
+            .with_default("false"),
+            OptionSpec::optional(
+                "usage_plan_rate_limit",
+                OptionType::Integer,
+                "Steady-state requests per second allowed by the usage plan",
+            ),
+            OptionSpec::optional(
+                "usage_plan_burst_limit",
+                OptionType::Integer,
+                "Burst of requests allowed by the usage plan",
+            ),
+            OptionSpec::optional(
+                "usage_plan_quota_limit",
+                OptionType::Integer,
+                "Requests allowed by the usage plan per quota period",
+            ),
+            OptionSpec::optional(
+                "usage_plan_quota_period",
+                OptionType::one_of(&["day", "week", "month"]),
+                "Period of the usage plan quota",
+            )
+            .with_default("day"),
+            OptionSpec::optional(
+                "validate_request",
+                OptionType::one_of(&["none", "body", "parameters", "all"]),
+                "What to validate in requests before invoking functions",
+            )
+            .with_default("none"),
+        ]
+    }
+
+    fn set_option(&mut self, key: &str, value: &str) {
+        self.options.insert(key.into(), value.into());
+    }
+
+    fn boot(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_booted(&self) -> bool {
+        true
+    }
+
+    fn as_service_provider(&self) -> Result<&dyn ServiceProvider> {
+        Err(anyhow!("{} is not a ServiceProvider", self.name()))
+    }
+
+    fn as_function_provider(&self) -> Result<&dyn FunctionProvider> {
+        Err(anyhow!("{} is not a FunctionProvider", self.name()))
+    }
+
+    fn as_gateway_provider(&self) -> Result<&dyn GatewayProvider> {
+        Ok(self)
+    }
+
+    fn as_dns_provider(&self) -> Result<&dyn DnsProvider> {
+        Err(anyhow!("{} is not a DnsProvider", self.name()))
+    }
+
+    fn as_container_registry_provider(&self) -> Result<&dyn ContainerRegistryProvider> {
+        Err(anyhow!(
+            "{} is not a ContainerRegistryProvider",
+            self.name()
+        ))
+    }
+}
+
+impl GatewayProvider for ApiGatewayRestProvider {
+    fn cast_service(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        hbs.register_helper("quote", Box::new(quote));
+        hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_template_string("root", include_str!("templates/api_impl.tf.handlebars"))
+            .unwrap();
+
+        let api_fragment = Fragment {
+            content_type: ContentType::HCL,
+            content: hbs.render("root", &self.service_json(service)?).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/infra/{}/api.tf",
+                service.name,
+                self.name(),
+            )),
+        };
+
+        Ok(vec![api_fragment])
+    }
+
+    fn compatible_service_providers(&self) -> Vec<String> {
+        vec![aws_lambda::provider_name()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_ids_are_distinct() {
+        assert_eq!(resource_id("users"), "users");
+        assert_eq!(resource_id("{id}"), "_7b_id_7d_");
+        assert_eq!(resource_id("_id_"), "__id__");
+        assert_eq!(resource_id("{proxy+}"), "_7b_proxy_2b__7d_");
+        assert_ne!(resource_id("Users"), resource_id("users"));
+        assert_ne!(resource_id("a.b"), resource_id("a-b"));
+    }
+
+    #[test]
+    fn cognito_issuer() {
+        assert_eq!(
+            cognito_user_pool("https://cognito-idp.us-east-1.amazonaws.com/us-east-1_abc123/"),
+            Some(("us-east-1".into(), "us-east-1_abc123".into()))
+        );
+        assert_eq!(cognito_user_pool("https://example.auth0.com/"), None);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
@@ -11,8 +12,8 @@ use crate::{
 };
 
 use super::{
-    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Platform, Provider,
-    ServiceProvider,
+    ContainerRegistryProvider, DnsProvider, FunctionProvider, GatewayProvider, OptionSpec,
+    Platform, Provider, ServiceProvider, HAS_ARM64_OPTION,
 };
 
 pub fn provider_name() -> String {
@@ -37,6 +38,10 @@ impl AwsLambdaProvider {
             platform,
         })
     }
+
+    fn has_arm64(&self) -> bool {
+        self.options.contains_key(HAS_ARM64_OPTION)
+    }
 }
 
 #[typetag::serde]
@@ -57,26 +62,48 @@ impl Provider for AwsLambdaProvider {
         self.options.clone()
     }
 
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
     fn set_option(&mut self, key: &str, value: &str) {
         self.options.insert(key.into(), value.into()).unwrap();
     }
 
     fn boot(&self) -> Result<()> {
-        let runtime_url = &*format!(
-            "http://public.assemblylift.akkoro.io/runtime/{}/aws-lambda/bootstrap.zip",
-            // clap::crate_version!(),
-            "0.4.0-beta.0"
-        );
+        // clap::crate_version!(),
+        let runtime_version = "0.4.0-beta.0";
+
+        std::fs::create_dir_all("./.asml/runtime")?;
+        let bootstrap = assemblylift_tools::fetch_locked(
+            "aws-lambda-bootstrap",
+            runtime_version,
+            &format!(
+                "https://public.assemblylift.akkoro.io/runtime/{}/aws-lambda/bootstrap.zip",
+                runtime_version
+            ),
+        )?;
+        std::fs::write("./.asml/runtime/bootstrap.zip", bootstrap)?;
 
-        std::fs::create_dir_all("./.asml/runtime").unwrap();
-        assemblylift_tools::download_to_path(runtime_url, "./.asml/runtime/bootstrap.zip")?;
+        if self.has_arm64() {
+            let bootstrap = assemblylift_tools::fetch_locked(
+                "aws-lambda-arm64-bootstrap",
+                runtime_version,
+                &format!(
+                    "https://public.assemblylift.akkoro.io/runtime/{}/aws-lambda-arm64/bootstrap.zip",
+                    runtime_version
+                ),
+            )?;
+            std::fs::write("./.asml/runtime/bootstrap-arm64.zip", bootstrap)?;
+        }
 
-        // FIXME handle errors
         Ok(())
     }
 
     fn is_booted(&self) -> bool {
         std::path::Path::new("./.asml/runtime/bootstrap.zip").exists()
+            && (!self.has_arm64()
+                || std::path::Path::new("./.asml/runtime/bootstrap-arm64.zip").exists())
     }
 
     fn as_service_provider(&self) -> Result<&dyn ServiceProvider> {
@@ -119,9 +146,34 @@ impl ServiceProvider for AwsLambdaProvider {
         hbs.register_template_string("root", include_str!("templates/service_impl.tf.handlebars"))
             .unwrap();
 
+        let mut service_json = service.as_json().unwrap();
+        service_json["bucket_notifications"] = bucket_notifications(&service.functions);
+        service_json["has_arm64"] = service
+            .functions
+            .iter()
+            .any(|function| function.architecture == "arm64")
+            .into();
+        // One Ruby or Python layer per version of the runtime used in the service
+        service_json["ruby_layers"] =
+            runtime_layers(service.functions.iter().map(|f| f.ruby_version.as_ref()));
+        service_json["python_layers"] =
+            runtime_layers(service.functions.iter().map(|f| f.python_version.as_ref()));
+        for (function, function_json) in service
+            .functions
+            .iter()
+            .zip(service_json["functions"].as_array_mut().unwrap())
+        {
+            if let Some(version) = &function.ruby_version {
+                function_json["ruby_layer"] = runtime_layer_id(version).into();
+            }
+            if let Some(version) = &function.python_version {
+                function_json["python_layer"] = runtime_layer_id(version).into();
+            }
+        }
+
         let service_fragment = Fragment {
             content_type: ContentType::HCL,
-            content: hbs.render("root", &service.as_json().unwrap()).unwrap(),
+            content: hbs.render("root", &service_json).unwrap(),
             write_path: PathBuf::from(format!(
                 "net/services/{}/infra/{}/service.tf",
                 service.name,
@@ -163,3 +215,52 @@ impl FunctionProvider for AwsLambdaProvider {
         Ok(fragments)
     }
 }
+
+/// Terraform identifier of the layer of runtime `version`
+fn runtime_layer_id(version: &str) -> String {
+    version.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+}
+
+/// The layers of the distinct runtime `versions`
+fn runtime_layers<'a>(versions: impl Iterator<Item = Option<&'a String>>) -> serde_json::Value {
+    let versions: BTreeSet<&String> = versions.flatten().collect();
+    versions
+        .iter()
+        .map(|version| {
+            serde_json::json!({
+                "id": runtime_layer_id(version),
+                "version": version,
+            })
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// S3 allows a single notification configuration per bucket, so the bucket triggers of every function in
+/// the service are grouped by bucket and cast together at the service level.
+fn bucket_notifications(functions: &[Function]) -> serde_json::Value {
+    let mut buckets: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+    for function in functions {
+        for trigger in &function.triggers.buckets {
+            buckets
+                .entry(trigger.bucket.clone())
+                .or_default()
+                .push(serde_json::json!({
+                    "function_name": function.name,
+                    "events": trigger.events,
+                    "prefix": trigger.prefix,
+                    "suffix": trigger.suffix,
+                }));
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|(bucket, functions)| {
+            serde_json::json!({
+                "id": bucket.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+                "bucket": bucket,
+                "functions": functions,
+            })
+        })
+        .collect()
+}
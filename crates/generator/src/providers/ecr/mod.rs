@@ -8,7 +8,7 @@ use crate::{context::Service, snake_case, CastResult, ContentType, Fragment, Opt
 
 use super::{
     GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
-    ServiceProvider, Platform,
+    ServiceProvider, Platform, OptionSpec,
 };
 
 pub fn provider_name() -> String {
@@ -51,6 +51,10 @@ impl Provider for EcrProvider {
         self.options.clone()
     }
 
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
     fn set_option(&mut self, key: &str, value: &str) {
         self.options.insert(key.into(), value.into()).unwrap();
     }
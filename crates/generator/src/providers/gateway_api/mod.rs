@@ -0,0 +1,285 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    context::{Http, Service},
+    providers::kubernetes,
+    quote, snake_case, CastError, CastResult, ContentType, Fragment, Options,
+};
+
+use super::{
+    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
+    ServiceProvider, Platform, OptionSpec, OptionType,
+};
+
+pub fn provider_name() -> String {
+    "gateway-api".into()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GatewayApiProvider {
+    #[serde(default = "provider_name")]
+    name: String,
+    options: Options,
+    platform: Option<Platform>,
+}
+
+impl GatewayApiProvider {
+    pub fn new(options: Options, platform: Option<Platform>) -> Box<Self> {
+        Box::new(Self {
+            name: provider_name(),
+            options,
+            platform,
+        })
+    }
+
+    /// The Gateway the service's routes attach to; either an existing Gateway named by the `gateway_name` option,
+    /// or one cast per service using the `gateway_class` option.
+    fn gateway_ref(&self, service: &Service) -> CastResult<Value> {
+        if let Some(name) = self.options.get("gateway_name") {
+            return Ok(json!({
+                "existing": true,
+                "name": name,
+                "namespace": self.options.get("gateway_namespace").map(|ns| ns.as_str()).unwrap_or("default"),
+            }));
+        }
+
+        let class = self.options.get("gateway_class").ok_or(CastError(format!(
+            "{} requires either the gateway_class or gateway_name option",
+            self.name()
+        )))?;
+        let has_domain = service.domain.is_some();
+        Ok(json!({
+            "existing": false,
+            "class": class,
+            "name": service.name,
+            "namespace": format!("asml-{}-{}", service.project_name, service.name),
+            "section": if has_domain { "https" } else { "http" },
+            "redirect": has_domain,
+        }))
+    }
+
+    fn service_json(&self, service: &Service) -> CastResult<Value> {
+        let routes: Vec<Value> = service
+            .functions
+            .iter()
+            .filter_map(|function| {
+                function.http.as_ref().map(|http| {
+                    let (path_type, path) = path_match(http);
+                    json!({
+                        "function_name": function.name,
+                        "method": http.verb.to_uppercase(),
+                        "path_type": path_type,
+                        "path": path,
+                        "timeout": function.timeout,
+                    })
+                })
+            })
+            .collect();
+
+        let mut service_json = service.as_json().unwrap();
+        service_json["domain_name"] = service.domain_name().into();
+        service_json["gateway_ref"] = self.gateway_ref(service)?;
+        service_json["routes"] = routes.into();
+        Ok(service_json)
+    }
+}
+
+/// Paths with `{param}` segments are matched with a regular expression; a greedy `{param+}` matches the rest of the
+/// path. All other paths are matched exactly.
+fn path_match(http: &Http) -> (&'static str, String) {
+    if !http.path.contains('{') {
+        return ("Exact", http.path.clone());
+    }
+    let pattern = http
+        .path
+        .split('/')
+        .map(|segment| match segment {
+            s if s.starts_with('{') && s.ends_with("+}") => ".*".to_string(),
+            s if s.starts_with('{') && s.ends_with('}') => "[^/]+".to_string(),
+            s => regex_escape(s),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    ("RegularExpression", pattern)
+}
+
+/// Escapes regex metacharacters with a backslash. Templates emit the pattern with the `quote` helper, which escapes
+/// the backslashes again for HCL & YAML strings.
+fn regex_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[typetag::serde]
+impl Provider for GatewayApiProvider {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn platform(&self) -> Option<Platform> {
+        self.platform.clone()
+    }
+
+    fn compatible_platforms(&self) -> Vec<String> {
+        vec!["kubernetes".into()]
+    }
+
+    fn options(&self) -> Options {
+        self.options.clone()
+    }
+
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        vec![
+            OptionSpec::optional(
+                "gateway_class",
+                OptionType::String,
+                "GatewayClass of a Gateway cast for the service",
+            ),
+            OptionSpec::optional(
+                "gateway_name",
+                OptionType::String,
+                "Name of an existing Gateway to attach routes to, instead of casting one",
+            ),
+            OptionSpec::optional(
+                "gateway_namespace",
+                OptionType::String,
+                "Namespace of the existing Gateway",
+            )
+            .with_default("default"),
+        ]
+    }
+
+    fn set_option(&mut self, key: &str, value: &str) {
+        self.options.insert(key.into(), value.into());
+    }
+
+    fn boot(&self) -> Result<()> {
+        // The Gateway API CRDs & controller are expected to be installed with the cluster's ingress controller
+        Ok(())
+    }
+
+    fn is_booted(&self) -> bool {
+        true
+    }
+
+    fn as_service_provider(&self) -> Result<&dyn ServiceProvider> {
+        Err(anyhow!("{} is not a ServiceProvider", self.name()))
+    }
+
+    fn as_function_provider(&self) -> Result<&dyn FunctionProvider> {
+        Err(anyhow!("{} is not a FunctionProvider", self.name()))
+    }
+
+    fn as_gateway_provider(&self) -> Result<&dyn GatewayProvider> {
+        Ok(self)
+    }
+
+    fn as_dns_provider(&self) -> Result<&dyn DnsProvider> {
+        Err(anyhow!("{} is not a DnsProvider", self.name()))
+    }
+
+    fn as_container_registry_provider(&self) -> Result<&dyn ContainerRegistryProvider> {
+        Err(anyhow!(
+            "{} is not a ContainerRegistryProvider",
+            self.name()
+        ))
+    }
+}
+
+impl GatewayProvider for GatewayApiProvider {
+    fn cast_service(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        let service_json = self.service_json(service)?;
+
+        if kubernetes::manifests_output(&service.provider.options())? {
+            let mut hbs = Handlebars::new();
+            hbs.register_escape_fn(handlebars::no_escape);
+            hbs.register_helper("quote", Box::new(quote));
+            hbs.register_template_string("root", include_str!("templates/api.yaml.handlebars"))
+                .unwrap();
+
+            return Ok(vec![Fragment {
+                content_type: ContentType::YAML,
+                content: hbs.render("root", &service_json).unwrap(),
+                write_path: kubernetes::manifests_dir(&service.name).join("gateway.yaml"),
+            }]);
+        }
+
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_helper("quote", Box::new(quote));
+        hbs.register_template_string("root", include_str!("templates/api_impl.tf.handlebars"))
+            .unwrap();
+
+        Ok(vec![Fragment {
+            content_type: ContentType::HCL,
+            content: hbs.render("root", &service_json).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/infra/{}/api.tf",
+                service.name,
+                self.name(),
+            )),
+        }])
+    }
+
+    fn compatible_service_providers(&self) -> Vec<String> {
+        vec![kubernetes::provider_name()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http(path: &str) -> Http {
+        Http {
+            verb: "GET".into(),
+            path: path.into(),
+        }
+    }
+
+    #[test]
+    fn static_paths_match_exactly() {
+        assert_eq!(
+            path_match(&http("/users/me.json")),
+            ("Exact", "/users/me.json".to_string())
+        );
+    }
+
+    #[test]
+    fn params_match_a_segment() {
+        assert_eq!(
+            path_match(&http("/users/{id}/posts.json")),
+            ("RegularExpression", r"/users/[^/]+/posts\.json".to_string())
+        );
+    }
+
+    #[test]
+    fn greedy_params_match_the_rest() {
+        assert_eq!(
+            path_match(&http("/files/{path+}")),
+            ("RegularExpression", "/files/.*".to_string())
+        );
+    }
+
+    #[test]
+    fn escape_metacharacters() {
+        assert_eq!(regex_escape("a.b"), r"a\.b");
+        assert_eq!(
+            regex_escape(r"+*?()|[]{}^$\"),
+            r"\+\*\?\(\)\|\[\]\{\}\^\$\\"
+        );
+        assert_eq!(regex_escape("plain-path_1"), "plain-path_1");
+    }
+}
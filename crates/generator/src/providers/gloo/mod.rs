@@ -8,13 +8,13 @@ use serde::{Deserialize, Serialize};
 use assemblylift_tools::{glooctl::GlooCtl, kubectl::KubeCtl};
 
 use crate::{
-    context::Service, providers::kubernetes, snake_case, CastError, CastResult, ContentType,
+    context::Service, providers::kubernetes, quote, snake_case, CastError, CastResult, ContentType,
     Fragment, Options,
 };
 
 use super::{
-    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
-    ServiceProvider, Platform,
+    kubeconfig, ContainerRegistryProvider, DnsProvider, FunctionProvider, GatewayProvider,
+    OptionSpec, OptionType, Platform, Provider, ServiceProvider,
 };
 
 pub fn provider_name() -> String {
@@ -41,12 +41,10 @@ impl GlooProvider {
     pub fn gloo_proxy_ip(&self) -> Option<String> {
         let mut labels = HashMap::new();
         labels.insert("gloo".to_string(), "gateway-proxy".to_string());
-        let kubectl = KubeCtl::default_with_config(
-            self.platform.as_ref().unwrap().options.get("config_path").unwrap().into(),
-        );
+        let kubectl = KubeCtl::default_with_config(kubeconfig(&self.platform).ok()?).ok()?;
         let gateways = kubectl
             .get_in_namespace("services", "gloo-system", Some(labels))
-            .unwrap();
+            .ok()?;
         let mut selector = Selector::new();
         let v: Vec<String> = selector
             .str_path("$.items[0].status.loadBalancer.ingress[0].ip")
@@ -79,13 +77,25 @@ impl Provider for GlooProvider {
         self.options.clone()
     }
 
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
+    fn platform_option_specs(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::required(
+            "config_path",
+            OptionType::String,
+            "Path to the kubeconfig of the cluster",
+        )]
+    }
+
     fn set_option(&mut self, key: &str, value: &str) {
         self.options.insert(key.into(), value.into());
     }
 
     fn boot(&self) -> Result<()> {
-        let kubeconfig = self.platform.as_ref().unwrap().options.get("config_path").unwrap();
-        Ok(GlooCtl::default_with_config(kubeconfig.into()).install_gateway())
+        GlooCtl::default_with_config(kubeconfig(&self.platform)?)?.install_gateway();
+        Ok(())
     }
 
     fn is_booted(&self) -> bool {
@@ -118,6 +128,10 @@ impl Provider for GlooProvider {
 
 impl GatewayProvider for GlooProvider {
     fn cast_service(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        if kubernetes::manifests_output(&service.provider.options())? {
+            return self.cast_service_manifests(service);
+        }
+
         let mut svc: Service = service.into();
         svc.gateway.provider.set_option(
             "__cluster_ip",
@@ -152,3 +166,23 @@ impl GatewayProvider for GlooProvider {
         vec![kubernetes::provider_name()]
     }
 }
+
+impl GlooProvider {
+    /// Cast the service's Upstreams and VirtualServices as YAML manifests, alongside those of the service provider
+    fn cast_service_manifests(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        hbs.register_helper("quote", Box::new(quote));
+        hbs.register_template_string("root", include_str!("templates/api.yaml.handlebars"))
+            .unwrap();
+
+        let mut service_json = service.as_json().unwrap();
+        service_json["domain_name"] = service.domain_name().into();
+
+        Ok(vec![Fragment {
+            content_type: ContentType::YAML,
+            content: hbs.render("root", &service_json).unwrap(),
+            write_path: kubernetes::manifests_dir(&service.name).join("gateway.yaml"),
+        }])
+    }
+}
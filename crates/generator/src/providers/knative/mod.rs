@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use assemblylift_tools::kubectl::KubeCtl;
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    concat_cast,
+    context::{Function, Service},
+    snake_case, CastError, CastResult, ContentType, Fragment, Options,
+};
+
+use super::{
+    kubeconfig, ContainerRegistryProvider, DnsProvider, FunctionProvider, GatewayProvider,
+    OptionSpec, OptionType, Platform, Provider, ServiceProvider,
+};
+
+pub fn provider_name() -> String {
+    "knative".into()
+}
+
+/// Release of Knative Serving installed by `boot`. Its manifests are recorded in the tools lockfile.
+pub const SERVING_VERSION: &str = "knative-v1.12.3";
+
+#[derive(Serialize, Deserialize)]
+pub struct KnativeProvider {
+    #[serde(default = "provider_name")]
+    name: String,
+    options: Options,
+    platform: Option<Platform>,
+}
+
+impl KnativeProvider {
+    pub fn new(options: Options, platform: Option<Platform>) -> Box<Self> {
+        Box::new(Self {
+            name: provider_name(),
+            options,
+            platform,
+        })
+    }
+
+    fn kubectl(&self) -> Result<KubeCtl> {
+        KubeCtl::default_with_config(kubeconfig(&self.platform)?)
+    }
+
+    /// The function as template data, with its autoscaling mapped onto Knative's annotations.
+    /// Functions scale to zero unless `min_replicas` is set, or they scale on CPU.
+    fn function_json(&self, function: &Function) -> CastResult<serde_json::Value> {
+        let mut function_json = function.as_json().unwrap();
+        let autoscaling = function.autoscaling.as_ref();
+        let min_replicas = autoscaling.and_then(|a| a.min_replicas);
+        let (class, metric, target) = match autoscaling {
+            Some(a) if a.target_rps.is_some() => (None, Some("rps"), a.target_rps),
+            Some(a) if a.target_concurrency.is_some() => (
+                None,
+                Some("concurrency"),
+                a.target_concurrency.map(u32::from),
+            ),
+            Some(a) if a.target_cpu_percent.is_some() => (
+                Some("hpa.autoscaling.knative.dev"),
+                Some("cpu"),
+                a.target_cpu_percent.map(u32::from),
+            ),
+            _ => (None, None, None),
+        };
+        let min_scale = match (class, min_replicas) {
+            (Some(_), Some(0)) => {
+                return Err(CastError(format!(
+                    "function `{}` scales on CPU, which cannot scale to zero; set min_replicas >= 1 or use another target",
+                    function.name
+                )))
+            }
+            (Some(_), None) => 1u16,
+            (_, min) => min.unwrap_or(0u16),
+        };
+        function_json["knative"] = serde_json::json!({
+            "min_scale": min_scale,
+            "max_scale": autoscaling.map(|a| a.max_replicas),
+            "class": class,
+            "metric": metric,
+            "target": target,
+        });
+        Ok(function_json)
+    }
+}
+
+#[typetag::serde]
+impl Provider for KnativeProvider {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn platform(&self) -> Option<Platform> {
+        self.platform.clone()
+    }
+
+    fn compatible_platforms(&self) -> Vec<String> {
+        vec!["kubernetes".into()]
+    }
+
+    fn options(&self) -> Options {
+        self.options.clone()
+    }
+
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
+    fn platform_option_specs(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::required(
+            "config_path",
+            OptionType::String,
+            "Path to the kubeconfig of the cluster",
+        )]
+    }
+
+    fn requires_registry(&self) -> bool {
+        true
+    }
+
+    fn set_option(&mut self, key: &str, value: &str) {
+        self.options.insert(key.into(), value.into());
+    }
+
+    fn boot(&self) -> Result<()> {
+        println!("Installing Knative Serving");
+        let kubectl = self.kubectl()?;
+        for manifest in ["serving-crds", "serving-core"] {
+            let url = format!(
+                "https://github.com/knative/serving/releases/download/{}/{}.yaml",
+                SERVING_VERSION, manifest
+            );
+            let yaml = assemblylift_tools::fetch_locked(
+                &format!("knative-{}", manifest),
+                SERVING_VERSION,
+                &url,
+            )?;
+            kubectl
+                .apply_manifest(&yaml, &url)
+                .map_err(|e| anyhow!(e))?;
+        }
+        // arm64 functions select their nodes by architecture
+        kubectl
+            .patch_merge(
+                "configmap/config-features",
+                "knative-serving",
+                r#"{"data":{"kubernetes.podspec-nodeselector":"enabled"}}"#,
+            )
+            .map_err(|e| anyhow!(e))?;
+        Ok(())
+    }
+
+    fn is_booted(&self) -> bool {
+        let mut labels = HashMap::new();
+        labels.insert("app".to_string(), "controller".to_string());
+        let kubectl = match self.kubectl() {
+            Ok(kubectl) => kubectl,
+            Err(_) => return false,
+        };
+        match kubectl.get_in_namespace("deployments", "knative-serving", Some(labels)) {
+            Ok(deployments) => deployments["items"]
+                .as_array()
+                .map(|items| !items.is_empty())
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    fn as_service_provider(&self) -> Result<&dyn ServiceProvider> {
+        Ok(self)
+    }
+
+    fn as_function_provider(&self) -> Result<&dyn FunctionProvider> {
+        Ok(self)
+    }
+
+    fn as_gateway_provider(&self) -> Result<&dyn GatewayProvider> {
+        Err(anyhow!("{} is not a GatewayProvider", self.name()))
+    }
+
+    fn as_dns_provider(&self) -> Result<&dyn DnsProvider> {
+        Err(anyhow!("{} is not a DnsProvider", self.name()))
+    }
+
+    fn as_container_registry_provider(&self) -> Result<&dyn ContainerRegistryProvider> {
+        Err(anyhow!(
+            "{} is not a ContainerRegistryProvider",
+            self.name()
+        ))
+    }
+}
+
+impl ServiceProvider for KnativeProvider {
+    fn cast_service(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        if service.container_registry.is_none() {
+            return Err(CastError(format!(
+                "service `{}` requires registry_id to be set",
+                service.name.clone()
+            )));
+        }
+
+        let mut fragments: Vec<Fragment> = Vec::new();
+
+        let mut function_fragments = service
+            .functions
+            .iter()
+            .map(|function| self.as_function_provider().unwrap().cast_function(function))
+            .reduce(concat_cast)
+            .unwrap_or_else(|| {
+                Err(CastError(format!(
+                    "service `{}` has no functions to cast",
+                    service.name
+                )))
+            })?;
+
+        let mut hbs = Handlebars::new();
+        hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_template_string("root", include_str!("templates/service_impl.tf.handlebars"))
+            .unwrap();
+
+        let service_fragment = Fragment {
+            content_type: ContentType::HCL,
+            content: hbs.render("root", &service.as_json().unwrap()).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/infra/{}/service.tf",
+                service.name,
+                self.name(),
+            )),
+        };
+
+        fragments.append(&mut vec![service_fragment]);
+        fragments.append(&mut function_fragments);
+
+        Ok(fragments)
+    }
+}
+
+impl FunctionProvider for KnativeProvider {
+    fn cast_function(&self, function: &Function) -> CastResult<Vec<Fragment>> {
+        let triggers = &function.triggers;
+        if !(triggers.schedules.is_empty()
+            && triggers.queues.is_empty()
+            && triggers.topics.is_empty()
+            && triggers.buckets.is_empty())
+        {
+            return Err(CastError(format!(
+                "function `{}` has triggers, which are not supported by provider `{}`",
+                function.name,
+                self.name(),
+            )));
+        }
+        let function_json = self.function_json(function)?;
+
+        let mut hbs = Handlebars::new();
+        hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_template_string(
+            "root",
+            include_str!("templates/function_impl.tf.handlebars"),
+        )
+        .unwrap();
+        hbs.register_template_string(
+            "dockerfile",
+            include_str!("../kubernetes/templates/function.dockerfile.handlebars"),
+        )
+        .unwrap();
+
+        let tf_fragment = Fragment {
+            content_type: ContentType::HCL,
+            content: hbs.render("root", &function_json).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/infra/{}/functions/{}/infra/function.tf",
+                function.service_name,
+                self.name(),
+                function.name,
+            )),
+        };
+
+        let dockerfile_fragment = Fragment {
+            content_type: ContentType::Dockerfile,
+            content: hbs.render("dockerfile", &function_json).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/functions/{}/Dockerfile",
+                function.service_name, function.name,
+            )),
+        };
+
+        Ok(vec![tf_fragment, dockerfile_fragment])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{Autoscaling, Triggers};
+
+    fn function(autoscaling: Autoscaling) -> Function {
+        Function {
+            name: "my-function".into(),
+            service_name: "my-service".into(),
+            project_name: "my-project".into(),
+            coordinates: "my-project.my-service.my-function".into(),
+            language: "rust".into(),
+            handler_name: "my-function.component.wasm".into(),
+            runtime_environment: "native".into(),
+            runtime_version: "0.4.0-beta.0".into(),
+            environment_variables: Default::default(),
+            http: None,
+            authorizer: None,
+            size: 1024,
+            timeout: 5,
+            cpu_compat_mode: "default".into(),
+            precompiled: false,
+            architecture: "x86_64".into(),
+            cpu_millicores: 578,
+            autoscaling: Some(autoscaling),
+            triggers: Triggers::default(),
+            build: None,
+            components: Vec::new(),
+            ruby_version: None,
+            python_version: None,
+        }
+    }
+
+    fn autoscaling() -> Autoscaling {
+        Autoscaling {
+            min_replicas: None,
+            max_replicas: 10,
+            target_cpu_percent: None,
+            target_rps: None,
+            target_concurrency: None,
+        }
+    }
+
+    fn provider() -> Box<KnativeProvider> {
+        KnativeProvider::new(Default::default(), None)
+    }
+
+    #[test]
+    fn cpu_cannot_scale_to_zero() {
+        let f = function(Autoscaling {
+            min_replicas: Some(0),
+            target_cpu_percent: Some(70),
+            ..autoscaling()
+        });
+        assert!(provider().function_json(&f).is_err());
+    }
+
+    #[test]
+    fn cpu_scales_from_one_with_the_hpa_class() {
+        let f = function(Autoscaling {
+            target_cpu_percent: Some(70),
+            ..autoscaling()
+        });
+        let json = provider().function_json(&f).unwrap();
+        assert_eq!(json["knative"]["min_scale"], 1);
+        assert_eq!(json["knative"]["class"], "hpa.autoscaling.knative.dev");
+        assert_eq!(json["knative"]["metric"], "cpu");
+        assert_eq!(json["knative"]["target"], 70);
+    }
+
+    #[test]
+    fn concurrency_and_rps_map_to_annotations() {
+        let concurrency = function(Autoscaling {
+            target_concurrency: Some(50),
+            ..autoscaling()
+        });
+        let rps = function(Autoscaling {
+            min_replicas: Some(2),
+            target_rps: Some(200),
+            ..autoscaling()
+        });
+
+        let tf = &provider().cast_function(&concurrency).unwrap()[0].content;
+        assert!(tf.contains(r#""autoscaling.knative.dev/min-scale" = "0""#));
+        assert!(tf.contains(r#""autoscaling.knative.dev/max-scale" = "10""#));
+        assert!(tf.contains(r#""autoscaling.knative.dev/metric"    = "concurrency""#));
+        assert!(tf.contains(r#""autoscaling.knative.dev/target"    = "50""#));
+        assert!(!tf.contains("autoscaling.knative.dev/class"));
+
+        let tf = &provider().cast_function(&rps).unwrap()[0].content;
+        assert!(tf.contains(r#""autoscaling.knative.dev/min-scale" = "2""#));
+        assert!(tf.contains(r#""autoscaling.knative.dev/metric"    = "rps""#));
+        assert!(tf.contains(r#""autoscaling.knative.dev/target"    = "200""#));
+    }
+}
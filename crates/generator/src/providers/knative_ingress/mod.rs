@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use handlebars::Handlebars;
+use jsonpath_lib::Selector;
+use serde::{Deserialize, Serialize};
+
+use assemblylift_tools::kubectl::KubeCtl;
+
+use crate::{
+    context::Service, providers::knative, snake_case, CastResult, ContentType, Fragment, Options,
+};
+
+use super::{
+    kubeconfig, ContainerRegistryProvider, DnsProvider, FunctionProvider, GatewayProvider,
+    OptionSpec, OptionType, Platform, Provider, ServiceProvider,
+};
+
+pub fn provider_name() -> String {
+    "knative-ingress".into()
+}
+
+/// Release of Kourier installed by `boot`, matching Knative Serving. Its manifest is recorded in the tools lockfile.
+pub const KOURIER_VERSION: &str = knative::SERVING_VERSION;
+
+/// Knative's own ingress, using Kourier as the networking layer. Each function with an `http` route is served at
+/// its own hostname; the route's verb & path are not matched.
+#[derive(Serialize, Deserialize)]
+pub struct KnativeIngressProvider {
+    #[serde(default = "provider_name")]
+    name: String,
+    options: Options,
+    platform: Option<Platform>,
+}
+
+impl KnativeIngressProvider {
+    pub fn new(options: Options, platform: Option<Platform>) -> Box<Self> {
+        Box::new(Self {
+            name: provider_name(),
+            options,
+            platform,
+        })
+    }
+
+    fn kubectl(&self) -> Result<KubeCtl> {
+        KubeCtl::default_with_config(kubeconfig(&self.platform)?)
+    }
+
+    pub fn kourier_ip(&self) -> Option<String> {
+        let mut labels = HashMap::new();
+        labels.insert(
+            "networking.knative.dev/ingress-provider".to_string(),
+            "kourier".to_string(),
+        );
+        let services = self
+            .kubectl()
+            .ok()?
+            .get_in_namespace("services", "kourier-system", Some(labels))
+            .ok()?;
+        let mut selector = Selector::new();
+        let v: Vec<String> = selector
+            .str_path("$.items[0].status.loadBalancer.ingress[0].ip")
+            .unwrap()
+            .value(&services)
+            .select_as()
+            .unwrap_or_default();
+        v.first().cloned()
+    }
+}
+
+#[typetag::serde]
+impl Provider for KnativeIngressProvider {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn platform(&self) -> Option<Platform> {
+        self.platform.clone()
+    }
+
+    fn compatible_platforms(&self) -> Vec<String> {
+        vec!["kubernetes".into()]
+    }
+
+    fn options(&self) -> Options {
+        self.options.clone()
+    }
+
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::optional(
+            "default_domain",
+            OptionType::String,
+            "Knative's default domain, under which functions are served",
+        )]
+    }
+
+    fn platform_option_specs(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::required(
+            "config_path",
+            OptionType::String,
+            "Path to the kubeconfig of the cluster",
+        )]
+    }
+
+    fn set_option(&mut self, key: &str, value: &str) {
+        self.options.insert(key.into(), value.into());
+    }
+
+    fn boot(&self) -> Result<()> {
+        println!("Installing Kourier for Knative Serving");
+        let kubectl = self.kubectl()?;
+        let url = format!(
+            "https://github.com/knative/net-kourier/releases/download/{}/kourier.yaml",
+            KOURIER_VERSION
+        );
+        let yaml = assemblylift_tools::fetch_locked("knative-kourier", KOURIER_VERSION, &url)?;
+        kubectl
+            .apply_manifest(&yaml, &url)
+            .map_err(|e| anyhow!(e))?;
+        kubectl
+            .patch_merge(
+                "configmap/config-network",
+                "knative-serving",
+                r#"{"data":{"ingress-class":"kourier.ingress.networking.knative.dev","autocreate-cluster-domain-claims":"true"}}"#,
+            )
+            .map_err(|e| anyhow!(e))?;
+        if let Some(domain) = self.options.get("default_domain") {
+            kubectl
+                .patch_merge(
+                    "configmap/config-domain",
+                    "knative-serving",
+                    &serde_json::json!({ "data": { domain: "" } }).to_string(),
+                )
+                .map_err(|e| anyhow!(e))?;
+        }
+        Ok(())
+    }
+
+    fn is_booted(&self) -> bool {
+        self.kourier_ip().is_some()
+    }
+
+    fn as_service_provider(&self) -> Result<&dyn ServiceProvider> {
+        Err(anyhow!("{} is not a ServiceProvider", self.name()))
+    }
+
+    fn as_function_provider(&self) -> Result<&dyn FunctionProvider> {
+        Err(anyhow!("{} is not a FunctionProvider", self.name()))
+    }
+
+    fn as_gateway_provider(&self) -> Result<&dyn GatewayProvider> {
+        Ok(self)
+    }
+
+    fn as_dns_provider(&self) -> Result<&dyn DnsProvider> {
+        Err(anyhow!("{} is not a DnsProvider", self.name()))
+    }
+
+    fn as_container_registry_provider(&self) -> Result<&dyn ContainerRegistryProvider> {
+        Err(anyhow!(
+            "{} is not a ContainerRegistryProvider",
+            self.name()
+        ))
+    }
+}
+
+impl GatewayProvider for KnativeIngressProvider {
+    fn cast_service(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        let mut hbs = Handlebars::new();
+        hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_template_string("root", include_str!("templates/api_impl.tf.handlebars"))
+            .unwrap();
+
+        let api_fragment = Fragment {
+            content_type: ContentType::HCL,
+            content: hbs.render("root", &service.as_json().unwrap()).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/infra/{}/api.tf",
+                service.name,
+                self.name(),
+            )),
+        };
+
+        Ok(vec![api_fragment])
+    }
+
+    fn compatible_service_providers(&self) -> Vec<String> {
+        vec![knative::provider_name()]
+    }
+}
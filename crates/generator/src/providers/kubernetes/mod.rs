@@ -1,25 +1,76 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
 use assemblylift_tools::cmctl::CmCtl;
 use handlebars::Handlebars;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
 
 use crate::{
     concat_cast,
     context::{Function, Service},
-    snake_case, CastError, CastResult, ContentType, Fragment, Options,
+    quote, snake_case, CastError, CastResult, ContentType, Fragment, Options,
 };
 
 use super::{
-    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
-    ServiceProvider, Platform,
+    kubeconfig, ContainerRegistryProvider, DnsProvider, FunctionProvider, GatewayProvider,
+    OptionSpec, OptionType, Platform, Provider, ServiceProvider,
 };
 
 pub fn provider_name() -> String {
     "k8s".into()
 }
 
+/// Whether the provider `options` select plain YAML manifests (`output = "manifests"`) rather than Terraform
+pub fn manifests_output(options: &Options) -> CastResult<bool> {
+    match options.get("output").map(|o| o.as_str()) {
+        None | Some("terraform") => Ok(false),
+        Some("manifests") => Ok(true),
+        Some(other) => Err(CastError(format!(
+            "unknown output `{}` for provider `{}`; expected `terraform` or `manifests`",
+            other,
+            provider_name(),
+        ))),
+    }
+}
+
+/// The directory manifests for `service_name` are written to, as a Kustomize base
+pub fn manifests_dir(service_name: &str) -> PathBuf {
+    PathBuf::from(format!("net/services/{}/k8s", service_name))
+}
+
+/// The tag of a function's image when output is `manifests`: a digest of the Dockerfile and the build artifacts in
+/// `function_dir`, so that the manifest (and so the Deployment) changes whenever the image does
+fn image_tag(function_dir: &Path, dockerfile: &str) -> CastResult<String> {
+    if !function_dir.is_dir() {
+        return Err(CastError(format!(
+            "no build artifacts at {}; the function must be built before its manifests are cast",
+            function_dir.display()
+        )));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(dockerfile.as_bytes());
+    for entry in WalkDir::new(function_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| CastError(e.to_string()))?;
+        // The Dockerfile on disk is the one cast previously
+        if !entry.file_type().is_file() || entry.file_name() == "Dockerfile" {
+            continue;
+        }
+        let path = entry.path().strip_prefix(function_dir).unwrap();
+        hasher.update(path.to_string_lossy().as_bytes());
+        let contents = std::fs::read(entry.path())
+            .map_err(|e| CastError(format!("could not read {}: {}", entry.path().display(), e)))?;
+        hasher.update(contents);
+    }
+
+    Ok(hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct KubernetesProvider {
     #[serde(default = "provider_name")]
@@ -29,6 +80,36 @@ pub struct KubernetesProvider {
 }
 
 impl KubernetesProvider {
+    /// The function as template data, with the defaults for a HorizontalPodAutoscaler filled in
+    fn function_json(&self, function: &Function) -> CastResult<serde_json::Value> {
+        let mut function_json = function.as_json().unwrap();
+        if let Some(autoscaling) = &function.autoscaling {
+            if autoscaling.min_replicas == Some(0)
+                || autoscaling.target_concurrency.is_some()
+                || autoscaling.target_rps.is_some()
+            {
+                return Err(CastError(format!(
+                    "function `{}`: provider `{}` does not support scaling to zero, target_concurrency or target_rps",
+                    function.name,
+                    self.name(),
+                )));
+            }
+            let json = &mut function_json["autoscaling"];
+            json["min_replicas"] = autoscaling.min_replicas.unwrap_or(1u16).into();
+            // Scale on CPU unless some target is given
+            if autoscaling.target_cpu_percent.is_none() {
+                json["target_cpu_percent"] = 80u16.into();
+            }
+        }
+        if let Some(registry_url) = self.options.get("registry_url") {
+            function_json["registry_url"] = registry_url.trim_end_matches('/').into();
+        }
+        if let Some(secret) = self.options.get("image_pull_secret") {
+            function_json["image_pull_secret"] = secret.clone().into();
+        }
+        Ok(function_json)
+    }
+
     pub fn new(options: Options, platform: Option<Platform>) -> Box<Self> {
         Box::new(Self {
             name: provider_name(),
@@ -56,6 +137,39 @@ impl Provider for KubernetesProvider {
         self.options.clone()
     }
 
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        vec![
+            OptionSpec::optional(
+                "output",
+                OptionType::one_of(&["terraform", "manifests"]),
+                "Cast Kubernetes resources as Terraform, or as YAML manifests with Kustomize bases",
+            )
+            .with_default("terraform"),
+            OptionSpec::optional(
+                "registry_url",
+                OptionType::String,
+                "Host of the registry function images are pushed to; required when output is `manifests`",
+            ),
+            OptionSpec::optional(
+                "image_pull_secret",
+                OptionType::String,
+                "Name of an existing secret for pulling function images, when output is `manifests`",
+            ),
+        ]
+    }
+
+    fn platform_option_specs(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::required(
+            "config_path",
+            OptionType::String,
+            "Path to the kubeconfig of the cluster",
+        )]
+    }
+
+    fn requires_registry(&self) -> bool {
+        true
+    }
+
     fn set_option(&mut self, key: &str, value: &str) {
         self.options.insert(key.into(), value.into()).unwrap();
     }
@@ -63,8 +177,7 @@ impl Provider for KubernetesProvider {
     fn boot(&self) -> Result<()> {
         // TODO this is only needed by the DNS provider and only if K8s is in use
         //      could have boot take a &Context and do it that way
-        let kubeconfig = self.platform.as_ref().unwrap().options.get("config_path").unwrap();
-        CmCtl::default_with_config(kubeconfig.into()).install();
+        CmCtl::default_with_config(kubeconfig(&self.platform)?)?.install();
         Ok(())
     }
 
@@ -104,7 +217,14 @@ impl ServiceProvider for KubernetesProvider {
                 service.name.clone()
             )));
         }
-        
+        if manifests_output(&self.options)? && !self.options.contains_key("registry_url") {
+            return Err(CastError(format!(
+                "service `{}` requires option `registry_url` of provider `{}` when output is `manifests`",
+                service.name,
+                self.name(),
+            )));
+        }
+
         let mut fragments: Vec<Fragment> = Vec::new();
 
         let mut function_fragments = service
@@ -114,6 +234,12 @@ impl ServiceProvider for KubernetesProvider {
             .reduce(concat_cast)
             .unwrap()?;
 
+        if manifests_output(&self.options)? {
+            fragments.append(&mut self.cast_service_manifests(service)?);
+            fragments.append(&mut function_fragments);
+            return Ok(fragments);
+        }
+
         let mut hbs = Handlebars::new();
         hbs.register_helper("snake_case", Box::new(snake_case));
         hbs.register_template_string("root", include_str!("templates/service_impl.tf.handlebars"))
@@ -138,6 +264,19 @@ impl ServiceProvider for KubernetesProvider {
 
 impl FunctionProvider for KubernetesProvider {
     fn cast_function(&self, function: &Function) -> CastResult<Vec<Fragment>> {
+        let triggers = &function.triggers;
+        if !(triggers.queues.is_empty()
+            && triggers.topics.is_empty()
+            && triggers.buckets.is_empty())
+        {
+            return Err(CastError(format!(
+                "function `{}` has sqs, sns, or s3 triggers, which are not supported by provider `{}`",
+                function.name,
+                self.name(),
+            )));
+        }
+        let mut function_json = self.function_json(function)?;
+
         let mut hbs = Handlebars::new();
         hbs.register_helper("snake_case", Box::new(snake_case));
         hbs.register_template_string(
@@ -150,29 +289,112 @@ impl FunctionProvider for KubernetesProvider {
             include_str!("templates/function.dockerfile.handlebars"),
         )
         .unwrap();
+        let dockerfile = hbs.render("dockerfile", &function_json).unwrap();
+        let function_dir = PathBuf::from(format!(
+            "net/services/{}/functions/{}",
+            function.service_name, function.name,
+        ));
 
-        let tf_fragment = Fragment {
-            content_type: ContentType::HCL,
-            content: hbs.render("root", &function.as_json().unwrap()).unwrap(),
-            write_path: PathBuf::from(format!(
-                "net/services/{}/infra/{}/functions/{}/infra/function.tf",
-                function.service_name,
-                self.name(),
-                function.name,
-            )),
+        let function_fragment = match manifests_output(&self.options)? {
+            true => {
+                function_json["image_tag"] = image_tag(&function_dir, &dockerfile)?.into();
+                let mut yaml = Handlebars::new();
+                yaml.register_escape_fn(handlebars::no_escape);
+                yaml.register_helper("quote", Box::new(quote));
+                yaml.register_template_string(
+                    "root",
+                    include_str!("templates/function.yaml.handlebars"),
+                )
+                .unwrap();
+                Fragment {
+                    content_type: ContentType::YAML,
+                    content: yaml.render("root", &function_json).unwrap(),
+                    write_path: manifests_dir(&function.service_name)
+                        .join(format!("function-{}.yaml", function.name)),
+                }
+            }
+            false => Fragment {
+                content_type: ContentType::HCL,
+                content: hbs.render("root", &function_json).unwrap(),
+                write_path: PathBuf::from(format!(
+                    "net/services/{}/infra/{}/functions/{}/infra/function.tf",
+                    function.service_name,
+                    self.name(),
+                    function.name,
+                )),
+            },
         };
 
         let dockerfile_fragment = Fragment {
             content_type: ContentType::Dockerfile,
-            content: hbs
-                .render("dockerfile", &function.as_json().unwrap())
-                .unwrap(),
-            write_path: PathBuf::from(format!(
-                "net/services/{}/functions/{}/Dockerfile",
-                function.service_name, function.name,
-            )),
+            content: dockerfile,
+            write_path: function_dir.join("Dockerfile"),
         };
 
-        Ok(vec![tf_fragment, dockerfile_fragment])
+        Ok(vec![function_fragment, dockerfile_fragment])
+    }
+}
+
+impl KubernetesProvider {
+    /// Cast the service's namespace and (if it has a domain) certificate issuer as YAML manifests.
+    /// No registry credentials secret is cast in this mode; `image_pull_secret` names one created separately.
+    fn cast_service_manifests(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        hbs.register_helper("quote", Box::new(quote));
+        hbs.register_template_string(
+            "namespace",
+            include_str!("templates/namespace.yaml.handlebars"),
+        )
+        .unwrap();
+        hbs.register_template_string(
+            "cluster_issuer",
+            include_str!("templates/cluster_issuer.yaml.handlebars"),
+        )
+        .unwrap();
+
+        let service_json = service.as_json().unwrap();
+        let mut fragments = vec![Fragment {
+            content_type: ContentType::YAML,
+            content: hbs.render("namespace", &service_json).unwrap(),
+            write_path: manifests_dir(&service.name).join("namespace.yaml"),
+        }];
+        if service.domain.is_some() {
+            fragments.push(Fragment {
+                content_type: ContentType::YAML,
+                content: hbs.render("cluster_issuer", &service_json).unwrap(),
+                write_path: manifests_dir(&service.name).join("cluster-issuer.yaml"),
+            });
+        }
+
+        Ok(fragments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_tag_follows_the_build_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("handler.component.wasm"), b"v1").unwrap();
+        std::fs::write(dir.path().join("Dockerfile"), "FROM old").unwrap();
+        let tag = image_tag(dir.path(), "FROM runtime").unwrap();
+        assert_eq!(tag.len(), 16);
+
+        // The previously cast Dockerfile is not part of the digest
+        std::fs::write(dir.path().join("Dockerfile"), "FROM runtime").unwrap();
+        assert_eq!(image_tag(dir.path(), "FROM runtime").unwrap(), tag);
+
+        assert_ne!(image_tag(dir.path(), "FROM other").unwrap(), tag);
+        std::fs::write(dir.path().join("handler.component.wasm"), b"v2").unwrap();
+        assert_ne!(image_tag(dir.path(), "FROM runtime").unwrap(), tag);
+    }
+
+    #[test]
+    fn image_tag_requires_a_build() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(image_tag(&dir.path().join("missing"), "FROM runtime").is_err());
     }
 }
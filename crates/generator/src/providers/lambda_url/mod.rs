@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    context::Service, providers::aws_lambda, quote, snake_case, CastError, CastResult, ContentType,
+    Fragment, Options,
+};
+
+use super::{
+    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, Provider,
+    ServiceProvider, Platform, OptionSpec, OptionType,
+};
+
+pub fn provider_name() -> String {
+    "aws-lambda-url".into()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LambdaUrlProvider {
+    #[serde(default = "provider_name")]
+    name: String,
+    options: Options,
+    platform: Option<Platform>,
+}
+
+impl LambdaUrlProvider {
+    pub fn new(options: Options, platform: Option<Platform>) -> Box<Self> {
+        Box::new(Self {
+            name: provider_name(),
+            options,
+            platform,
+        })
+    }
+
+    fn service_json(&self, service: &Service) -> CastResult<Value> {
+        let mut urls: Vec<Value> = Vec::new();
+        for function in service.functions.iter().filter(|f| f.http.is_some()) {
+            // Function URLs only support IAM auth; anything else has to be handled by the function itself
+            let authorization_type = match &function.authorizer {
+                None => "NONE",
+                Some(authorizer) if authorizer.r#type.to_lowercase() == "iam" => "AWS_IAM",
+                Some(authorizer) => {
+                    return Err(CastError(format!(
+                        "function {} uses authorizer {} of type {}; {} only supports iam authorizers",
+                        function.name,
+                        authorizer.id,
+                        authorizer.r#type,
+                        self.name()
+                    )))
+                }
+            };
+            urls.push(json!({
+                "function_name": function.name,
+                "authorization_type": authorization_type,
+            }));
+        }
+
+        let mut service_json = service.as_json().map_err(|e| CastError(e.to_string()))?;
+        service_json["urls"] = urls.into();
+        service_json["cors_allow_origins"] = match self.options.get("cors_allow_origins") {
+            Some(origins) => origins.split(',').map(|o| o.trim()).collect::<Vec<_>>().into(),
+            None => Value::Null,
+        };
+        Ok(service_json)
+    }
+}
+
+#[typetag::serde]
+impl Provider for LambdaUrlProvider {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn platform(&self) -> Option<Platform> {
+        self.platform.clone()
+    }
+
+    fn compatible_platforms(&self) -> Vec<String> {
+        vec!["aws".into()]
+    }
+
+    fn options(&self) -> Options {
+        self.options.clone()
+    }
+
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::optional(
+            "cors_allow_origins",
+            OptionType::String,
+            "Comma-separated origins allowed by CORS",
+        )]
+    }
+
+    fn set_option(&mut self, key: &str, value: &str) {
+        self.options.insert(key.into(), value.into());
+    }
+
+    fn boot(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_booted(&self) -> bool {
+        true
+    }
+
+    fn as_service_provider(&self) -> Result<&dyn ServiceProvider> {
+        Err(anyhow!("{} is not a ServiceProvider", self.name()))
+    }
+
+    fn as_function_provider(&self) -> Result<&dyn FunctionProvider> {
+        Err(anyhow!("{} is not a FunctionProvider", self.name()))
+    }
+
+    fn as_gateway_provider(&self) -> Result<&dyn GatewayProvider> {
+        Ok(self)
+    }
+
+    fn as_dns_provider(&self) -> Result<&dyn DnsProvider> {
+        Err(anyhow!("{} is not a DnsProvider", self.name()))
+    }
+
+    fn as_container_registry_provider(&self) -> Result<&dyn ContainerRegistryProvider> {
+        Err(anyhow!(
+            "{} is not a ContainerRegistryProvider",
+            self.name()
+        ))
+    }
+}
+
+impl GatewayProvider for LambdaUrlProvider {
+    fn cast_service(&self, service: &Service) -> CastResult<Vec<Fragment>> {
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        hbs.register_helper("quote", Box::new(quote));
+        hbs.register_helper("snake_case", Box::new(snake_case));
+        hbs.register_template_string("root", include_str!("templates/api_impl.tf.handlebars"))
+            .unwrap();
+
+        let api_fragment = Fragment {
+            content_type: ContentType::HCL,
+            content: hbs.render("root", &self.service_json(service)?).unwrap(),
+            write_path: PathBuf::from(format!(
+                "net/services/{}/infra/{}/api.tf",
+                service.name,
+                self.name(),
+            )),
+        };
+
+        Ok(vec![api_fragment])
+    }
+
+    fn compatible_service_providers(&self) -> Vec<String> {
+        vec![aws_lambda::provider_name()]
+    }
+}
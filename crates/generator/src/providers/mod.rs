@@ -2,9 +2,14 @@ use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 pub mod api_gateway;
+pub mod api_gateway_rest;
 pub mod aws_lambda;
 pub mod ecr;
+pub mod gateway_api;
 pub mod gloo;
+pub mod knative;
+pub mod knative_ingress;
+pub mod lambda_url;
 pub mod kubernetes;
 pub mod route53;
 
@@ -14,8 +19,10 @@ use crate::{
 };
 
 use self::{
-    api_gateway::ApiGatewayProvider, aws_lambda::AwsLambdaProvider, ecr::EcrProvider,
-    gloo::GlooProvider, kubernetes::KubernetesProvider, route53::Route53Provider,
+    api_gateway::ApiGatewayProvider, api_gateway_rest::ApiGatewayRestProvider,
+    aws_lambda::AwsLambdaProvider, ecr::EcrProvider, gateway_api::GatewayApiProvider,
+    gloo::GlooProvider, knative::KnativeProvider, knative_ingress::KnativeIngressProvider,
+    kubernetes::KubernetesProvider, lambda_url::LambdaUrlProvider, route53::Route53Provider,
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -35,12 +42,117 @@ impl From<&crate::toml::asml::Platform> for Platform {
     }
 }
 
+/// Internal option set on a service provider when any of the service's functions are `arm64`
+pub const HAS_ARM64_OPTION: &str = "__has_arm64";
+
+/// The kubeconfig path set by the `config_path` option of a Kubernetes-based platform
+pub fn kubeconfig(platform: &Option<Platform>) -> Result<String> {
+    let platform = platform
+        .as_ref()
+        .ok_or_else(|| anyhow!("provider requires a Kubernetes platform"))?;
+    platform
+        .options
+        .get("config_path")
+        .cloned()
+        .ok_or_else(|| anyhow!("platform `{}` is missing option `config_path`", platform.id))
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionType {
+    String,
+    Bool,
+    Integer,
+    OneOf(Vec<String>),
+}
+
+/// Declares an option a provider accepts
+#[derive(Serialize, Clone, Debug)]
+pub struct OptionSpec {
+    pub name: String,
+    pub r#type: OptionType,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: String,
+}
+
+impl OptionSpec {
+    pub fn required(name: &str, r#type: OptionType, description: &str) -> Self {
+        Self {
+            name: name.into(),
+            r#type,
+            required: true,
+            default: None,
+            description: description.into(),
+        }
+    }
+
+    pub fn optional(name: &str, r#type: OptionType, description: &str) -> Self {
+        Self {
+            name: name.into(),
+            r#type,
+            required: false,
+            default: None,
+            description: description.into(),
+        }
+    }
+
+    pub fn with_default(mut self, default: &str) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Check that `value` is of the option's type
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        let ok = match &self.r#type {
+            OptionType::String => true,
+            OptionType::Bool => value == "true" || value == "false",
+            OptionType::Integer => value.parse::<i64>().is_ok(),
+            OptionType::OneOf(values) => values.iter().any(|v| v == value),
+        };
+        match ok {
+            true => Ok(()),
+            false => Err(format!(
+                "option `{}` must be {}; got `{}`",
+                self.name,
+                self.r#type.describe(),
+                value
+            )),
+        }
+    }
+}
+
+impl OptionType {
+    pub fn one_of(values: &[&str]) -> Self {
+        OptionType::OneOf(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            OptionType::String => "a string".into(),
+            OptionType::Bool => "true or false".into(),
+            OptionType::Integer => "an integer".into(),
+            OptionType::OneOf(values) => format!("one of {}", values.join(", ")),
+        }
+    }
+}
+
 #[typetag::serde(tag = "provider")]
 pub trait Provider {
     fn name(&self) -> String;
     fn platform(&self) -> Option<Platform>;
     fn compatible_platforms(&self) -> Vec<String>;
     fn options(&self) -> Options;
+    /// The options the provider accepts in its `options` table
+    fn option_specs(&self) -> Vec<OptionSpec>;
+    /// The options the provider requires of its platform, in addition to any the platform itself uses
+    fn platform_option_specs(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+    /// Whether services of the provider must set `registry_id`, as their function images are pushed to it
+    fn requires_registry(&self) -> bool {
+        false
+    }
     fn set_option(&mut self, key: &str, value: &str);
     fn boot(&self) -> Result<()>;
     fn is_booted(&self) -> bool;
@@ -78,13 +190,35 @@ pub trait ContainerRegistryProvider: Provider {
 pub struct ProviderFactory;
 
 impl ProviderFactory {
+    /// Names of all providers known to the factory
+    pub fn provider_names() -> Vec<String> {
+        vec![
+            api_gateway::provider_name(),
+            api_gateway_rest::provider_name(),
+            aws_lambda::provider_name(),
+            ecr::provider_name(),
+            gateway_api::provider_name(),
+            gloo::provider_name(),
+            knative::provider_name(),
+            knative_ingress::provider_name(),
+            kubernetes::provider_name(),
+            lambda_url::provider_name(),
+            route53::provider_name(),
+        ]
+    }
+
     pub fn new_provider(name: &str, options: Options, platform: Option<Platform>) -> Result<Box<dyn Provider>> {
         match name {
             _ if name == api_gateway::provider_name() => Ok(ApiGatewayProvider::new(options, platform)),
+            _ if name == api_gateway_rest::provider_name() => Ok(ApiGatewayRestProvider::new(options, platform)),
             _ if name == aws_lambda::provider_name() => Ok(AwsLambdaProvider::new(options, platform)),
             _ if name == ecr::provider_name() => Ok(EcrProvider::new(options, platform)),
+            _ if name == gateway_api::provider_name() => Ok(GatewayApiProvider::new(options, platform)),
             _ if name == gloo::provider_name() => Ok(GlooProvider::new(options, platform)),
+            _ if name == knative::provider_name() => Ok(KnativeProvider::new(options, platform)),
+            _ if name == knative_ingress::provider_name() => Ok(KnativeIngressProvider::new(options, platform)),
             _ if name == kubernetes::provider_name() => Ok(KubernetesProvider::new(options, platform)),
+            _ if name == lambda_url::provider_name() => Ok(LambdaUrlProvider::new(options, platform)),
             _ if name == route53::provider_name() => Ok(Route53Provider::new(options, platform)),
             _ => Err(anyhow!("unrecognized provider named {}", name)),
         }
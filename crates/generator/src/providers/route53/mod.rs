@@ -6,12 +6,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     context::{Domain, Service},
-    providers::{api_gateway, gloo, Provider},
-    snake_case, CastResult, ContentType, Fragment, Options,
+    providers::{api_gateway, api_gateway_rest, gateway_api, gloo, knative_ingress, kubernetes, Provider},
+    quote, snake_case, CastResult, ContentType, Fragment, Options,
 };
 
 use super::{
-    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, ServiceProvider, Platform,
+    GatewayProvider, ContainerRegistryProvider, DnsProvider, FunctionProvider, ServiceProvider,
+    Platform, OptionSpec, OptionType,
 };
 
 pub fn provider_name() -> String {
@@ -54,6 +55,27 @@ impl Provider for Route53Provider {
         self.options.clone()
     }
 
+    fn option_specs(&self) -> Vec<OptionSpec> {
+        vec![
+            OptionSpec::optional(
+                "cm_acme_email",
+                OptionType::String,
+                "Contact email for Let's Encrypt certificates issued by cert-manager",
+            ),
+            OptionSpec::optional(
+                "cm_letsencrypt_env",
+                OptionType::one_of(&["staging", "production"]),
+                "Let's Encrypt environment used by cert-manager",
+            )
+            .with_default("production"),
+            OptionSpec::optional(
+                "cm_aws_credentials",
+                OptionType::String,
+                "Name of a secret with AWS credentials for cert-manager's DNS01 solver",
+            ),
+        ]
+    }
+
     fn set_option(&mut self, key: &str, value: &str) {
         self.options.insert(key.into(), value.into()).unwrap();
     }
@@ -123,11 +145,26 @@ impl DnsProvider for Route53Provider {
             include_str!("templates/dns_impl_apigw.tf.handlebars"),
         )
         .unwrap();
+        hbs.register_template_string(
+            &api_gateway_rest::provider_name(),
+            include_str!("templates/dns_impl_apigw.tf.handlebars"),
+        )
+        .unwrap();
+        hbs.register_template_string(
+            &gateway_api::provider_name(),
+            include_str!("templates/dns_impl_gateway_api.tf.handlebars"),
+        )
+        .unwrap();
         hbs.register_template_string(
             &gloo::provider_name(),
             include_str!("templates/dns_impl_gloo.tf.handlebars"),
         )
         .unwrap();
+        hbs.register_template_string(
+            &knative_ingress::provider_name(),
+            include_str!("templates/dns_impl_knative.tf.handlebars"),
+        )
+        .unwrap();
 
         let service_fragment = Fragment {
             content_type: ContentType::HCL,
@@ -139,10 +176,42 @@ impl DnsProvider for Route53Provider {
             )),
         };
 
-        Ok(vec![service_fragment])
+        let mut fragments = vec![service_fragment];
+        let gateway_name = service.gateway.provider.name();
+        let cast_certificate = gateway_name == gloo::provider_name()
+            || (gateway_name == gateway_api::provider_name()
+                && !service.gateway.provider.options().contains_key("gateway_name"));
+        if cast_certificate
+            && kubernetes::manifests_output(&service.provider.options())?
+        {
+            let mut yaml = Handlebars::new();
+            yaml.register_escape_fn(handlebars::no_escape);
+            yaml.register_helper("quote", Box::new(quote));
+            yaml.register_template_string(
+                "certificate",
+                include_str!("templates/certificate.yaml.handlebars"),
+            )
+            .unwrap();
+
+            let mut service_json = service.as_json().unwrap();
+            service_json["domain_name"] = service.domain_name().into();
+            fragments.push(Fragment {
+                content_type: ContentType::YAML,
+                content: yaml.render("certificate", &service_json).unwrap(),
+                write_path: kubernetes::manifests_dir(&service.name).join("certificate.yaml"),
+            });
+        }
+
+        Ok(fragments)
     }
 
     fn compatible_gateway_providers(&self) -> Vec<String> {
-        vec![api_gateway::provider_name(), gloo::provider_name()]
+        vec![
+            api_gateway::provider_name(),
+            api_gateway_rest::provider_name(),
+            gateway_api::provider_name(),
+            gloo::provider_name(),
+            knative_ingress::provider_name(),
+        ]
     }
 }
@@ -3,8 +3,8 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use super::Provider;
-use crate::Options;
+use super::{read_manifest, ManifestError, Provider};
+use crate::{Options, StringMap};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Manifest {
@@ -17,6 +17,7 @@ pub struct Manifest {
     domains: Option<Vec<Domain>>,
     registries: Option<Vec<Registry>>,
     pub terraform: Option<Terraform>,
+    environments: Option<Vec<Environment>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -31,10 +32,62 @@ pub struct Platform {
     pub options: Options,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Where Terraform keeps the project's state. `backend` is one of `s3`, `local`, `http`, or `kubernetes`; it
+/// defaults to `s3` when `state_bucket_name` is set, and to `local` otherwise. `options` are passed through to the
+/// backend block as-is.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Terraform {
-    pub state_bucket_name: String,
-    pub lock_table_name: String,
+    pub backend: Option<String>,
+    pub state_bucket_name: Option<String>,
+    pub lock_table_name: Option<String>,
+    pub options: Option<Options>,
+}
+
+/// A named deployment environment (e.g. dev, staging, prod), overriding parts of the manifest when selected with
+/// `--env`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Environment {
+    pub name: String,
+    /// Options merged into the options of the platform with the same id
+    pub platforms: Option<Vec<PlatformOverride>>,
+    /// Replaces the project's domains
+    pub domains: Option<Vec<Domain>>,
+    /// Replaces the project's `[terraform]` table
+    pub terraform: Option<Terraform>,
+    /// Environment variables added to every function
+    pub environment: Option<StringMap<String>>,
+    pub services: Option<Vec<ServiceOverride>>,
+}
+
+impl Environment {
+    /// The override of `function` in `service`, if the environment has one
+    pub fn function_override(&self, service: &str, function: &str) -> Option<&FunctionOverride> {
+        self.services
+            .as_ref()
+            .and_then(|services| services.iter().find(|s| s.name == service))
+            .and_then(|s| s.functions.as_ref())
+            .and_then(|functions| functions.iter().find(|f| f.name == function))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlatformOverride {
+    pub id: String,
+    pub options: Options,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ServiceOverride {
+    pub name: String,
+    pub domain_name: Option<String>,
+    pub functions: Option<Vec<FunctionOverride>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FunctionOverride {
+    pub name: String,
+    pub size_mb: Option<u16>,
+    pub environment: Option<StringMap<String>>,
 }
 
 /* Represents a reference by name to a service (toml::service::Manifest) */
@@ -71,11 +124,8 @@ pub struct HttpAuth {
 }
 
 impl Manifest {
-    pub fn read(file: &PathBuf) -> Result<Self, io::Error> {
-        match std::fs::read_to_string(file) {
-            Ok(contents) => Ok(Self::from(contents)),
-            Err(why) => Err(io::Error::new(io::ErrorKind::Other, why.to_string())),
-        }
+    pub fn read(file: &PathBuf) -> Result<Self, ManifestError> {
+        read_manifest(file)
     }
 
     pub fn write(&self, mut path: PathBuf) -> Result<(), io::Error> {
@@ -144,6 +194,52 @@ impl Manifest {
         }
     }
 
+    pub fn environments(&self) -> Vec<Environment> {
+        self.environments.clone().unwrap_or_default()
+    }
+
+    pub fn environment(&self, name: &str) -> Option<&Environment> {
+        self.environments
+            .as_ref()
+            .and_then(|environments| environments.iter().find(|e| e.name == name))
+    }
+
+    /// Apply the project-level overrides of an environment. Function overrides are applied when the service
+    /// manifests are read.
+    pub fn with_environment(mut self, environment: &Environment) -> Result<Self, String> {
+        for platform_override in environment.platforms.clone().unwrap_or_default() {
+            let platform = self
+                .platforms
+                .iter_mut()
+                .find(|p| p.id == platform_override.id)
+                .ok_or(format!(
+                    "environment `{}` overrides unknown platform `{}`",
+                    environment.name, platform_override.id
+                ))?;
+            platform.options.extend(platform_override.options);
+        }
+        if let Some(domains) = environment.domains.clone() {
+            self.domains = Some(domains);
+        }
+        if let Some(terraform) = environment.terraform.clone() {
+            self.terraform = Some(terraform);
+        }
+        for service_override in environment.services.clone().unwrap_or_default() {
+            let service = self
+                .services
+                .iter_mut()
+                .find(|s| s.name == service_override.name)
+                .ok_or(format!(
+                    "environment `{}` overrides unknown service `{}`",
+                    environment.name, service_override.name
+                ))?;
+            if service_override.domain_name.is_some() {
+                service.domain_name = service_override.domain_name;
+            }
+        }
+        Ok(self)
+    }
+
     pub fn registries(&self) -> Vec<Registry> {
         match self.registries.as_ref() {
             Some(registries) => registries.clone(),
@@ -166,3 +262,92 @@ impl Into<String> for Manifest {
         toml::to_string(&self).expect("unable to serialize TOML")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+        [project]
+        name = "shop"
+
+        [[platforms]]
+        id = "aws"
+        name = "aws"
+        options = { region = "us-east-1", profile = "default" }
+
+        [[domains]]
+        dns_name = "shop.example.com"
+        provider = { name = "route53", platform_id = "aws" }
+
+        [[services]]
+        name = "orders"
+        provider = { name = "aws-lambda", platform_id = "aws" }
+        domain_name = "shop.example.com"
+
+        [[environments]]
+        name = "staging"
+        platforms = [{ id = "aws", options = { region = "eu-west-1" } }]
+        domains = [{ dns_name = "staging.example.com", provider = { name = "route53", platform_id = "aws" } }]
+        services = [
+            { name = "orders", domain_name = "staging.example.com", functions = [
+                { name = "create", size_mb = 512, environment = { LOG_LEVEL = "debug" } },
+            ] },
+        ]
+    "#;
+
+    fn manifest() -> Manifest {
+        Manifest::from(MANIFEST.to_string())
+    }
+
+    #[test]
+    fn environment_overrides_platform_options() {
+        let manifest = manifest();
+        let staging = manifest.environment("staging").unwrap().clone();
+        let manifest = manifest.with_environment(&staging).unwrap();
+        let options = &manifest.platforms[0].options;
+        assert_eq!(options["region"], "eu-west-1");
+        assert_eq!(options["profile"], "default");
+    }
+
+    #[test]
+    fn environment_overrides_domains() {
+        let manifest = manifest();
+        let staging = manifest.environment("staging").unwrap().clone();
+        let manifest = manifest.with_environment(&staging).unwrap();
+        let domains: Vec<String> = manifest.domains().into_iter().map(|d| d.dns_name).collect();
+        assert_eq!(domains, vec!["staging.example.com"]);
+        assert_eq!(
+            manifest.services[0].domain_name.as_deref(),
+            Some("staging.example.com")
+        );
+    }
+
+    #[test]
+    fn environment_overrides_functions() {
+        let manifest = manifest();
+        let staging = manifest.environment("staging").unwrap();
+        let create = staging.function_override("orders", "create").unwrap();
+        assert_eq!(create.size_mb, Some(512));
+        assert_eq!(create.environment.as_ref().unwrap()["LOG_LEVEL"], "debug");
+        assert!(staging.function_override("orders", "cancel").is_none());
+        assert!(staging.function_override("users", "create").is_none());
+    }
+
+    #[test]
+    fn environment_rejects_unknown_overrides() {
+        let manifest = manifest();
+        let mut staging = manifest.environment("staging").unwrap().clone();
+        staging.services.as_mut().unwrap()[0].name = "users".into();
+        assert_eq!(
+            manifest.clone().with_environment(&staging).unwrap_err(),
+            "environment `staging` overrides unknown service `users`"
+        );
+        staging.services = None;
+        staging.platforms.as_mut().unwrap()[0].id = "gcp".into();
+        assert_eq!(
+            manifest.with_environment(&staging).unwrap_err(),
+            "environment `staging` overrides unknown platform `gcp`"
+        );
+    }
+}
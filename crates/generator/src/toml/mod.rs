@@ -1,3 +1,6 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
 use super::StringMap;
@@ -12,3 +15,208 @@ pub struct Provider {
     pub options: StringMap<String>,
     pub platform_id: Option<String>,
 }
+
+/// A problem found while reading or validating a manifest, located in its file when possible
+#[derive(Clone, Debug)]
+pub struct ManifestError {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl ManifestError {
+    pub fn new(file: &Path, message: impl Into<String>) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            line: None,
+            column: None,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    /// A TOML syntax or schema error, located by the span reported by the parser
+    pub fn from_toml(file: &Path, contents: &str, error: toml::de::Error) -> Self {
+        let mut e = Self::new(file, error.message().trim());
+        if let Some(span) = error.span() {
+            let (line, column) = line_column(contents, span.start);
+            e.line = Some(line);
+            e.column = Some(column);
+        }
+        e
+    }
+
+    /// Locate the error at the first occurrence of `needle` in the manifest, e.g. the quoted value it refers to
+    pub fn at(mut self, contents: &str, needle: &str) -> Self {
+        if let Some(offset) = contents.find(needle) {
+            let (line, column) = line_column(contents, offset);
+            self.line = Some(line);
+            self.column = Some(column);
+        }
+        self
+    }
+
+    /// Locate the error at the first occurrence of `needle` within `table`, or within its `index`th entry if it is an
+    /// array of tables. If `needle` is not found there, e.g. because the table is written inline, the whole manifest
+    /// is searched.
+    pub fn at_in(
+        mut self,
+        contents: &str,
+        table: &str,
+        index: Option<usize>,
+        needle: &str,
+    ) -> Self {
+        let found = table_spans(contents, table)
+            .into_iter()
+            .filter(|(entry, _, _)| index.is_none() || index == Some(*entry))
+            .find_map(|(_, start, end)| contents[start..end].find(needle).map(|o| start + o));
+        match found {
+            Some(offset) => {
+                let (line, column) = line_column(contents, offset);
+                self.line = Some(line);
+                self.column = Some(column);
+                self
+            }
+            None => self.at(contents, needle),
+        }
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\n  hint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// 1-based line & column of a byte offset
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (line, column)
+}
+
+/// The byte ranges of the manifest under headers of `table`, e.g. `[[services]]` or `[services.build]`, each with
+/// the index of the array-of-tables entry it belongs to
+fn table_spans(contents: &str, table: &str) -> Vec<(usize, usize, usize)> {
+    let mut spans = Vec::new();
+    let mut entry: Option<usize> = None;
+    let mut current: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if let Some((entry, start)) = current.take() {
+                spans.push((entry, start, offset));
+            }
+            let is_array = trimmed.starts_with("[[");
+            let name = trimmed.trim_start_matches('[');
+            let name = name[..name.find(']').unwrap_or(name.len())].trim();
+            if name == table && is_array {
+                entry = Some(entry.map_or(0, |e| e + 1));
+                current = Some((entry.unwrap(), offset));
+            } else if name == table || name.starts_with(&format!("{}.", table)) {
+                current = Some((entry.unwrap_or(0), offset));
+            }
+        }
+        offset += line.len();
+    }
+    if let Some((entry, start)) = current {
+        spans.push((entry, start, offset));
+    }
+    spans
+}
+
+/// Read & parse a manifest, with any error located in the file
+fn read_manifest<T: serde::de::DeserializeOwned>(file: &Path) -> Result<T, ManifestError> {
+    let contents = std::fs::read_to_string(file)
+        .map_err(|e| ManifestError::new(file, format!("could not read manifest: {}", e)))?;
+    toml::from_str(&contents).map_err(|e| ManifestError::from_toml(file, &contents, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[project]
+name = "shop"
+
+[[platforms]]
+id = "aws"
+name = "aws"
+
+[[services]]
+name = "orders"
+provider = { name = "aws-lambda", platform_id = "aws" }
+
+[[services]]
+name = "users"
+provider = { name = "aws-lambda", platform_id = "aws" }
+"#;
+
+    #[test]
+    fn line_and_column() {
+        assert_eq!(line_column("abc", 0), (1, 1));
+        assert_eq!(line_column("abc\ndef", 5), (2, 2));
+        assert_eq!(line_column("abc\n", 4), (2, 1));
+        assert_eq!(line_column("abc", 100), (1, 4));
+    }
+
+    #[test]
+    fn toml_errors_are_located() {
+        let contents = "[project]\nname = \"shop\"\nversion = \n";
+        let error = toml::from_str::<toml::Value>(contents).unwrap_err();
+        let e = ManifestError::from_toml(Path::new("assemblylift.toml"), contents, error);
+        assert_eq!(e.line, Some(3));
+        assert!(e.to_string().starts_with("assemblylift.toml:3:"), "{}", e);
+    }
+
+    #[test]
+    fn errors_are_located_in_their_table() {
+        let file = Path::new("assemblylift.toml");
+        let e = ManifestError::new(file, "").at(MANIFEST, "\"aws\"");
+        assert_eq!((e.line, e.column), (Some(5), Some(6)));
+
+        let e = ManifestError::new(file, "").at_in(MANIFEST, "services", None, "\"aws\"");
+        assert_eq!((e.line, e.column), (Some(10), Some(49)));
+
+        let e = ManifestError::new(file, "").at_in(MANIFEST, "services", Some(1), "\"aws\"");
+        assert_eq!((e.line, e.column), (Some(14), Some(49)));
+    }
+
+    #[test]
+    fn errors_fall_back_to_the_whole_manifest() {
+        let file = Path::new("assemblylift.toml");
+        let e = ManifestError::new(file, "").at_in(MANIFEST, "domains", None, "\"shop\"");
+        assert_eq!((e.line, e.column), (Some(2), Some(8)));
+    }
+
+    #[test]
+    fn display_with_location_and_hint() {
+        let mut e = ManifestError::new(Path::new("/p/assemblylift.toml"), "unknown platform `gpc`")
+            .with_hint("expected one of: aws");
+        e.line = Some(12);
+        e.column = Some(8);
+        assert_eq!(
+            e.to_string(),
+            "/p/assemblylift.toml:12:8: unknown platform `gpc`\n  hint: expected one of: aws"
+        );
+    }
+}
@@ -3,8 +3,8 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use super::Provider;
 use super::StringMap;
+use super::{read_manifest, ManifestError, Provider};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Manifest {
@@ -15,11 +15,8 @@ pub struct Manifest {
 }
 
 impl Manifest {
-    pub fn read(file: &PathBuf) -> Result<Self, io::Error> {
-        match std::fs::read_to_string(file) {
-            Ok(contents) => Ok(Self::from(contents)),
-            Err(why) => Err(io::Error::new(io::ErrorKind::Other, why.to_string())),
-        }
+    pub fn read(file: &PathBuf) -> Result<Self, ManifestError> {
+        read_manifest(file)
     }
 
     pub fn write(&self, mut path: PathBuf) -> Result<(), io::Error> {
@@ -67,7 +64,14 @@ impl Manifest {
             size_mb: None,
             cpu_compat_mode: None,
             precompile: None,
+            architecture: None,
             environment: None,
+            triggers: None,
+            autoscaling: None,
+            build: None,
+            components: None,
+            ruby_version: None,
+            python_version: None,
         };
         functions.push(fun);
         self.functions = functions;
@@ -138,8 +142,59 @@ pub struct Function {
     pub size_mb: Option<u16>,
     pub cpu_compat_mode: Option<String>,
     pub precompile: Option<bool>,
+    pub architecture: Option<String>,
     pub http: Option<HttpFunction>,
     pub environment: Option<StringMap<String>>,
+    pub triggers: Option<Vec<Trigger>>,
+    pub autoscaling: Option<Autoscaling>,
+    pub build: Option<Build>,
+    /// Components to compose into the function's component, as paths relative to the project root
+    pub components: Option<Vec<String>>,
+    /// Version of the Ruby runtime a `ruby` function runs on
+    pub ruby_version: Option<String>,
+    /// Version of the Python runtime a `python` function runs on
+    pub python_version: Option<String>,
+}
+
+/// How to build a function in a language without a bespoke integration, such as TinyGo, C or Zig
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Build {
+    /// Shell command run from the function directory
+    pub command: String,
+    /// The WebAssembly module or component the command produces, relative to the function directory
+    pub output: String,
+}
+
+/// Horizontal autoscaling for functions on providers which run them as long-lived deployments
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Autoscaling {
+    pub min_replicas: Option<u16>,
+    pub max_replicas: u16,
+    pub target_cpu_percent: Option<u16>,
+    pub target_rps: Option<u32>,
+    pub target_concurrency: Option<u16>,
+}
+
+/// A non-HTTP event source which invokes a function
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Trigger {
+    /// Invoke on a schedule, given as a standard five-field cron expression
+    Schedule { cron: String },
+    /// Invoke with batches of messages from an SQS queue
+    Sqs {
+        queue_arn: String,
+        batch_size: Option<u16>,
+    },
+    /// Invoke for each message published to an SNS topic
+    Sns { topic_arn: String },
+    /// Invoke for object events in an S3 bucket
+    S3 {
+        bucket: String,
+        events: Option<Vec<String>>,
+        prefix: Option<String>,
+        suffix: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -0,0 +1,920 @@
+//! Validation of the project & service manifests before anything is cast. Every problem found is reported with its
+//! location, rather than stopping at the first.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::context::{Autoscaling, Build, Triggers};
+use crate::projectfs::Project;
+use crate::providers::{kubernetes, OptionSpec, Platform, Provider, ProviderFactory};
+use crate::toml::{self, ManifestError};
+use crate::Options;
+
+/// Languages the CLI builds itself; a function in any other language is built with its own `build` command
+pub const LANGUAGES: [&str; 4] = ["rust", "ruby", "python", "javascript"];
+pub const ARCHITECTURES: [&str; 2] = ["x86_64", "arm64"];
+pub const DEFAULT_RUBY_VERSION: &str = "3.3.0-dev";
+pub const DEFAULT_PYTHON_VERSION: &str = "3.12.0";
+/// Paths the Hyper runtime answers itself, such as its health check, and never routes to the function
+pub const RESERVED_PATH_PREFIX: &str = "/_asml";
+
+/// Validate the project in `project`, with the overrides of `environment` applied if given
+pub fn validate(project: &Project, environment: Option<&str>) -> Vec<ManifestError> {
+    let file = project.dir().join("assemblylift.toml");
+    let manifest = match toml::asml::Manifest::read(&file) {
+        Ok(manifest) => manifest,
+        Err(e) => return vec![e],
+    };
+    let contents = std::fs::read_to_string(&file).unwrap_or_default();
+    let mut errors: Vec<ManifestError> = Vec::new();
+
+    for (i, env) in manifest.environments().iter().enumerate() {
+        if let Err(e) = manifest.clone().with_environment(env) {
+            errors.push(ManifestError::new(&file, e).at_in(
+                &contents,
+                "environments",
+                Some(i),
+                &quoted(&env.name),
+            ));
+        }
+    }
+    let manifest = match environment {
+        Some(name) => match manifest.environment(name).cloned() {
+            Some(env) => match manifest.with_environment(&env) {
+                Ok(manifest) => manifest,
+                Err(_) => return errors,
+            },
+            None => {
+                let names: Vec<String> = manifest
+                    .environments()
+                    .into_iter()
+                    .map(|e| e.name)
+                    .collect();
+                errors.push(
+                    ManifestError::new(&file, format!("environment `{}` is not defined", name))
+                        .with_hint(one_of("defined environments", &names)),
+                );
+                return errors;
+            }
+        },
+        None => manifest,
+    };
+
+    let platforms: Vec<Platform> = manifest.platforms.iter().map(Platform::from).collect();
+    let platform_ids: Vec<String> = platforms.iter().map(|p| p.id.clone()).collect();
+    let known_platforms = known_platforms();
+    let mut seen = HashSet::new();
+    for (i, platform) in platforms.iter().enumerate() {
+        if !seen.insert(platform.id.clone()) {
+            errors.push(
+                ManifestError::new(
+                    &file,
+                    format!("platform id `{}` is defined more than once", platform.id),
+                )
+                .at_in(&contents, "platforms", Some(i), &quoted(&platform.id)),
+            );
+        }
+        if !known_platforms.contains(&platform.name) {
+            errors.push(
+                ManifestError::new(&file, format!("unknown platform `{}`", platform.name))
+                    .at_in(&contents, "platforms", Some(i), &quoted(&platform.name))
+                    .with_hint(one_of("expected one of", &known_platforms)),
+            );
+        }
+    }
+
+    let mut checker = Checker {
+        file: &file,
+        contents: &contents,
+        platforms: &platforms,
+        errors: &mut errors,
+        scope: ("", None),
+    };
+
+    let registries = manifest.registries();
+    for (i, registry) in registries.iter().enumerate() {
+        checker.scope = ("registries", Some(i));
+        if let Some(provider) = checker.provider(&registry.provider, &registry.id) {
+            if provider.as_container_registry_provider().is_err() {
+                checker.error(
+                    format!(
+                        "provider `{}` of registry `{}` is not a container registry provider",
+                        provider.name(),
+                        registry.id
+                    ),
+                    &registry.provider.name,
+                );
+            }
+        }
+    }
+
+    let domains = manifest.domains();
+    let mut domain_providers: Vec<(String, Option<Box<dyn Provider>>)> = Vec::new();
+    for (i, domain) in domains.iter().enumerate() {
+        checker.scope = ("domains", Some(i));
+        let provider = checker.provider(&domain.provider, &domain.dns_name);
+        if let Some(provider) = &provider {
+            if provider.as_dns_provider().is_err() {
+                checker.error(
+                    format!(
+                        "provider `{}` of domain `{}` is not a DNS provider",
+                        provider.name(),
+                        domain.dns_name
+                    ),
+                    &domain.provider.name,
+                );
+            }
+        }
+        domain_providers.push((domain.dns_name.clone(), provider));
+    }
+
+    let authorizers = manifest.authorizers();
+    for (i, authorizer) in authorizers.iter().enumerate() {
+        checker.scope = ("authorizers", Some(i));
+        match authorizer.auth_type.to_lowercase().as_str() {
+            "iam" => {}
+            "jwt" => {
+                if authorizer.issuer.is_none() {
+                    checker.error(
+                        format!("JWT authorizer `{}` requires an issuer", authorizer.id),
+                        &authorizer.id,
+                    );
+                }
+                if authorizer.audience.is_none() {
+                    checker.error(
+                        format!("JWT authorizer `{}` requires an audience", authorizer.id),
+                        &authorizer.id,
+                    );
+                }
+            }
+            other => checker.error_with_hint(
+                format!(
+                    "authorizer `{}` has unknown auth_type `{}`",
+                    authorizer.id, other
+                ),
+                &authorizer.auth_type,
+                "expected one of: iam, jwt".into(),
+            ),
+        }
+    }
+    let authorizer_ids: Vec<String> = authorizers.iter().map(|a| a.id.clone()).collect();
+
+    let mut seen = HashSet::new();
+    for (i, service_ref) in manifest.services.iter().enumerate() {
+        checker.scope = ("services", Some(i));
+        if !seen.insert(service_ref.name.clone()) {
+            checker.error(
+                format!("service `{}` is defined more than once", service_ref.name),
+                &service_ref.name,
+            );
+        }
+        if service_ref.provider.platform_id.is_none() {
+            checker.error_with_hint(
+                format!("service `{}` has no platform_id", service_ref.name),
+                &service_ref.name,
+                one_of("set provider.platform_id to one of", &platform_ids),
+            );
+            continue;
+        }
+        let service_provider = checker.provider(&service_ref.provider, &service_ref.name);
+        if let Some(provider) = &service_provider {
+            if provider.as_service_provider().is_err() {
+                checker.error(
+                    format!(
+                        "provider `{}` of service `{}` is not a service provider",
+                        provider.name(),
+                        service_ref.name
+                    ),
+                    &service_ref.provider.name,
+                );
+            }
+        }
+        if let Some(provider) = &service_provider {
+            if provider.requires_registry() && service_ref.registry_id.is_none() {
+                checker.error_with_hint(
+                    format!(
+                        "service `{}` requires registry_id to be set",
+                        service_ref.name
+                    ),
+                    &service_ref.name,
+                    format!(
+                        "provider `{}` pushes function images to the service's registry",
+                        provider.name()
+                    ),
+                );
+            }
+            if provider.name() == kubernetes::provider_name()
+                && kubernetes::manifests_output(&provider.options()).unwrap_or(false)
+                && !provider.options().contains_key("registry_url")
+            {
+                checker.error_with_hint(
+                    format!(
+                        "service `{}` casts manifests, but provider `{}` has no registry_url",
+                        service_ref.name,
+                        provider.name()
+                    ),
+                    &service_ref.name,
+                    "set the host function images are pushed to, e.g. options = { output = \"manifests\", registry_url = \"123456789012.dkr.ecr.us-east-1.amazonaws.com\" }".into(),
+                );
+            }
+        }
+        if let Some(registry_id) = &service_ref.registry_id {
+            if !registries.iter().any(|r| &r.id == registry_id) {
+                let ids: Vec<String> = registries.iter().map(|r| r.id.clone()).collect();
+                checker.error_with_hint(
+                    format!(
+                        "registry `{}` of service `{}` is not defined",
+                        registry_id, service_ref.name
+                    ),
+                    registry_id,
+                    one_of("defined registries", &ids),
+                );
+            }
+        }
+        let domain_provider = match &service_ref.domain_name {
+            Some(domain_name) => match domain_providers
+                .iter()
+                .find(|(name, _)| name == domain_name)
+            {
+                Some((_, provider)) => provider.as_ref(),
+                None => {
+                    let names: Vec<String> = domains.iter().map(|d| d.dns_name.clone()).collect();
+                    checker.error_with_hint(
+                        format!(
+                            "domain `{}` of service `{}` is not defined",
+                            domain_name, service_ref.name
+                        ),
+                        domain_name,
+                        one_of("defined domains", &names),
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let service_file = project
+            .service_dir(service_ref.name.clone())
+            .dir()
+            .join("service.toml");
+        let service_errors = validate_service(
+            &service_file,
+            service_provider.as_deref(),
+            domain_provider.map(|p| p.as_ref()),
+            &platforms,
+            &authorizer_ids,
+        );
+        checker.errors.extend(service_errors);
+    }
+
+    errors
+}
+
+fn validate_service(
+    file: &Path,
+    service_provider: Option<&dyn Provider>,
+    domain_provider: Option<&dyn Provider>,
+    platforms: &[Platform],
+    authorizer_ids: &[String],
+) -> Vec<ManifestError> {
+    let manifest = match toml::service::Manifest::read(&file.to_path_buf()) {
+        Ok(manifest) => manifest,
+        Err(e) => return vec![e],
+    };
+    let contents = std::fs::read_to_string(file).unwrap_or_default();
+    let mut errors: Vec<ManifestError> = Vec::new();
+    let mut checker = Checker {
+        file,
+        contents: &contents,
+        platforms,
+        errors: &mut errors,
+        scope: ("gateway", None),
+    };
+
+    let gateway = &manifest.gateway.provider;
+    if gateway.platform_id.is_some() {
+        checker.error_at(
+            "gateway providers cannot currently define platform independent of service provider"
+                .to_string(),
+            "platform_id",
+        );
+    }
+    let gateway_provider = match ProviderFactory::new_provider(
+        &gateway.name,
+        gateway.options.clone(),
+        service_provider.and_then(|p| p.platform()),
+    ) {
+        Ok(provider) => {
+            checker.options(&provider.option_specs(), &gateway.options, &gateway.name);
+            Some(provider)
+        }
+        Err(_) => {
+            checker.error_with_hint(
+                format!("unknown provider `{}`", gateway.name),
+                &gateway.name,
+                one_of("expected one of", &ProviderFactory::provider_names()),
+            );
+            None
+        }
+    };
+    if let (Some(gateway_provider), Some(service_provider)) = (&gateway_provider, service_provider)
+    {
+        match gateway_provider.as_gateway_provider() {
+            Ok(gp) => {
+                let compatible = gp.compatible_service_providers();
+                if !compatible.contains(&service_provider.name()) {
+                    checker.error_with_hint(
+                        format!(
+                            "gateway provider `{}` is incompatible with service provider `{}`",
+                            gateway_provider.name(),
+                            service_provider.name()
+                        ),
+                        &gateway.name,
+                        one_of("it requires one of", &compatible),
+                    );
+                }
+            }
+            Err(_) => checker.error(
+                format!(
+                    "provider `{}` is not a gateway provider",
+                    gateway_provider.name()
+                ),
+                &gateway.name,
+            ),
+        }
+        if let Some(dns) = domain_provider.and_then(|p| p.as_dns_provider().ok()) {
+            let compatible = dns.compatible_gateway_providers();
+            if !compatible.contains(&gateway_provider.name()) {
+                checker.error_with_hint(
+                    format!(
+                        "DNS provider `{}` of the service's domain is incompatible with gateway provider `{}`",
+                        domain_provider.unwrap().name(),
+                        gateway_provider.name()
+                    ),
+                    &gateway.name,
+                    one_of("it requires one of", &compatible),
+                );
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for (i, function) in manifest.functions.iter().enumerate() {
+        checker.scope = ("functions", Some(i));
+        if !seen.insert(function.name.clone()) {
+            checker.error(
+                format!("function `{}` is defined more than once", function.name),
+                &function.name,
+            );
+        }
+        if let Some(authorizer_id) = &function.authorizer_id {
+            if !authorizer_ids.contains(authorizer_id) {
+                checker.error_with_hint(
+                    format!(
+                        "authorizer `{}` of function `{}` is not defined in assemblylift.toml",
+                        authorizer_id, function.name
+                    ),
+                    authorizer_id,
+                    one_of("defined authorizers", authorizer_ids),
+                );
+            }
+        }
+        if let Some(http) = &function.http {
+            let reserved = http
+                .path
+                .strip_prefix(RESERVED_PATH_PREFIX)
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'));
+            if reserved {
+                checker.error(
+                    format!(
+                        "function `{}` has http path `{}`, but paths under {} are reserved for the runtime",
+                        function.name, http.path, RESERVED_PATH_PREFIX
+                    ),
+                    &http.path,
+                );
+            }
+        }
+        match &function.language {
+            Some(language)
+                if !LANGUAGES.contains(&language.as_str()) && function.build.is_none() =>
+            {
+                checker.error_with_hint(
+                    format!(
+                        "function `{}` has unknown language `{}`",
+                        function.name, language
+                    ),
+                    language,
+                    format!(
+                        "{}, or any other with a `build` command",
+                        one_of("expected one of", &LANGUAGES.map(String::from))
+                    ),
+                );
+            }
+            language => {
+                if let Err(e) = Build::from_manifest(
+                    function.build.as_ref(),
+                    language.as_deref().unwrap_or("rust"),
+                    &function.name,
+                ) {
+                    checker.error(e, &function.name);
+                }
+            }
+        }
+        if let Some(ruby_version) = &function.ruby_version {
+            if !is_runtime_version(ruby_version) {
+                checker.error_with_hint(
+                    format!(
+                        "function `{}` has invalid ruby_version `{}`",
+                        function.name, ruby_version
+                    ),
+                    ruby_version,
+                    "expected letters, digits, `.` & `-`".to_string(),
+                );
+            }
+            if function.language.as_deref() != Some("ruby") {
+                checker.error(
+                    format!(
+                        "function `{}` sets a ruby_version, but its language is not ruby",
+                        function.name
+                    ),
+                    ruby_version,
+                );
+            }
+        }
+        if let Some(python_version) = &function.python_version {
+            if !is_runtime_version(python_version) {
+                checker.error_with_hint(
+                    format!(
+                        "function `{}` has invalid python_version `{}`",
+                        function.name, python_version
+                    ),
+                    python_version,
+                    "expected letters, digits, `.` & `-`".to_string(),
+                );
+            }
+            if function.language.as_deref() != Some("python") {
+                checker.error(
+                    format!(
+                        "function `{}` sets a python_version, but its language is not python",
+                        function.name
+                    ),
+                    python_version,
+                );
+            }
+        }
+        if let Some(architecture) = &function.architecture {
+            if !ARCHITECTURES.contains(&architecture.as_str()) {
+                checker.error_with_hint(
+                    format!(
+                        "function `{}` has unknown architecture `{}`",
+                        function.name, architecture
+                    ),
+                    architecture,
+                    one_of("expected one of", &ARCHITECTURES.map(String::from)),
+                );
+            }
+        }
+        if let Some(autoscaling) = &function.autoscaling {
+            if let Err(e) = Autoscaling::from_manifest(autoscaling, &function.name) {
+                checker.error(e, &function.name);
+            }
+        }
+        if let Err(e) = Triggers::from_manifest(
+            &function.triggers.clone().unwrap_or_default(),
+            &function.name,
+        ) {
+            checker.error(e, &function.name);
+        }
+    }
+
+    errors
+}
+
+struct Checker<'a> {
+    file: &'a Path,
+    contents: &'a str,
+    platforms: &'a [Platform],
+    errors: &'a mut Vec<ManifestError>,
+    /// The table being checked, and its entry if it is an array of tables, which errors are located in
+    scope: (&'a str, Option<usize>),
+}
+
+impl<'a> Checker<'a> {
+    /// Report an error located at the quoted `value` it refers to
+    fn error(&mut self, message: String, value: &str) {
+        self.error_at(message, &quoted(value));
+    }
+
+    /// Report an error located at `needle`, e.g. an option key
+    fn error_at(&mut self, message: String, needle: &str) {
+        let error = self.located(message, needle);
+        self.errors.push(error);
+    }
+
+    fn error_with_hint(&mut self, message: String, value: &str, hint: String) {
+        let error = self.located(message, &quoted(value)).with_hint(hint);
+        self.errors.push(error);
+    }
+
+    /// An error located at the first `needle` in the current scope
+    fn located(&self, message: String, needle: &str) -> ManifestError {
+        let (table, index) = self.scope;
+        ManifestError::new(self.file, message).at_in(self.contents, table, index, needle)
+    }
+
+    /// Check that a provider exists, and that its platform is defined & compatible with it
+    fn provider(
+        &mut self,
+        provider: &toml::Provider,
+        resource_id: &str,
+    ) -> Option<Box<dyn Provider>> {
+        let platform = match &provider.platform_id {
+            Some(platform_id) => match self.platforms.iter().find(|p| &p.id == platform_id) {
+                Some(platform) => Some(platform.clone()),
+                None => {
+                    let ids: Vec<String> = self.platforms.iter().map(|p| p.id.clone()).collect();
+                    self.error_with_hint(
+                        format!(
+                            "platform `{}` of `{}` is not defined",
+                            platform_id, resource_id
+                        ),
+                        platform_id,
+                        one_of("defined platforms", &ids),
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let instance = match ProviderFactory::new_provider(
+            &provider.name,
+            provider.options.clone(),
+            platform.clone(),
+        ) {
+            Ok(instance) => instance,
+            Err(_) => {
+                self.error_with_hint(
+                    format!("unknown provider `{}`", provider.name),
+                    &provider.name,
+                    one_of("expected one of", &ProviderFactory::provider_names()),
+                );
+                return None;
+            }
+        };
+
+        self.options(&instance.option_specs(), &provider.options, &provider.name);
+
+        if let Some(platform) = platform {
+            // Platform options are checked in the platform's own table
+            let index = self.platforms.iter().position(|p| p.id == platform.id);
+            let scope = std::mem::replace(&mut self.scope, ("platforms", index));
+            for spec in instance.platform_option_specs() {
+                if spec.required && !platform.options.contains_key(&spec.name) {
+                    self.error_with_hint(
+                        format!(
+                            "platform `{}` is missing option `{}` required by provider `{}`",
+                            platform.id,
+                            spec.name,
+                            instance.name()
+                        ),
+                        &platform.id,
+                        spec.description.clone(),
+                    );
+                }
+                if let Some(value) = platform.options.get(&spec.name) {
+                    if let Err(e) = spec.check(value) {
+                        self.error_at(e, &spec.name);
+                    }
+                }
+            }
+            self.scope = scope;
+
+            let compatible = instance.compatible_platforms();
+            if !compatible.contains(&platform.name) && !compatible.contains(&"*".into()) {
+                self.error_with_hint(
+                    format!(
+                        "provider `{}` is incompatible with platform `{}` ({})",
+                        instance.name(),
+                        platform.id,
+                        platform.name
+                    ),
+                    &provider.name,
+                    one_of("it requires a platform of", &compatible),
+                );
+            }
+        }
+
+        Some(instance)
+    }
+
+    /// Check provider options against the provider's declared specs
+    fn options(&mut self, specs: &[OptionSpec], options: &Options, provider_name: &str) {
+        let mut keys: Vec<&String> = options.keys().collect();
+        keys.sort();
+        for key in keys {
+            match specs.iter().find(|spec| &spec.name == key) {
+                Some(spec) => {
+                    if let Err(e) = spec.check(&options[key]) {
+                        self.error_at(e, key);
+                    }
+                }
+                None => {
+                    let names: Vec<String> = specs.iter().map(|s| s.name.clone()).collect();
+                    let error = self
+                        .located(
+                            format!("unknown option `{}` for provider `{}`", key, provider_name),
+                            key,
+                        )
+                        .with_hint(one_of("it accepts", &names));
+                    self.errors.push(error);
+                }
+            }
+        }
+        for spec in specs.iter().filter(|spec| spec.required) {
+            if !options.contains_key(&spec.name) {
+                self.error_with_hint(
+                    format!(
+                        "provider `{}` is missing required option `{}`",
+                        provider_name, spec.name
+                    ),
+                    provider_name,
+                    spec.description.clone(),
+                );
+            }
+        }
+    }
+}
+
+/// Names of all platforms supported by at least one provider
+fn known_platforms() -> Vec<String> {
+    let mut names: Vec<String> = ProviderFactory::provider_names()
+        .iter()
+        .filter_map(|name| ProviderFactory::new_provider(name, Default::default(), None).ok())
+        .flat_map(|provider| provider.compatible_platforms())
+        .filter(|name| name != "*")
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value)
+}
+
+/// Whether `version` is usable as a runtime version, which names a directory and is part of a URL
+fn is_runtime_version(version: &str) -> bool {
+    !version.starts_with('.')
+        && !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+fn one_of(prefix: &str, values: &[String]) -> String {
+    match values.is_empty() {
+        true => format!("{}: (none)", prefix),
+        false => format!("{}: {}", prefix, values.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r#"[project]
+name = "shop"
+
+[[platforms]]
+id = "aws"
+name = "aws"
+options = { region = "us-east-1" }
+"#;
+
+    const SERVICE: &str = r#"[gateway]
+provider = { name = "aws-apigw" }
+
+[[functions]]
+name = "create"
+language = "rust"
+http = { verb = "POST", path = "/orders" }
+"#;
+
+    const FUNCTION: &str = r#"[[functions]]
+name = "create"
+language = "rust"
+"#;
+
+    /// Validate a project with `assemblylift.toml` appended to [`PROJECT`], and the given service manifests
+    fn validate_project(assemblylift_toml: &str, services: &[(&str, &str)]) -> Vec<ManifestError> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("assemblylift.toml"),
+            format!("{}{}", PROJECT, assemblylift_toml),
+        )
+        .unwrap();
+        let project = Project::new("shop".into(), Some(dir.path().to_path_buf()));
+        for (name, manifest) in services {
+            let service_dir = project.service_dir(name.to_string()).dir();
+            std::fs::create_dir_all(&service_dir).unwrap();
+            std::fs::write(service_dir.join("service.toml"), manifest).unwrap();
+        }
+        validate(&project, None)
+    }
+
+    fn located(errors: &[ManifestError]) -> Vec<(String, Option<usize>, Option<usize>)> {
+        errors
+            .iter()
+            .map(|e| {
+                let file = e.file.file_name().unwrap().to_string_lossy().to_string();
+                (format!("{}: {}", file, e.message), e.line, e.column)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_project() {
+        let errors = validate_project(
+            r#"
+[[services]]
+name = "orders"
+provider = { name = "aws-lambda", platform_id = "aws" }
+"#,
+            &[("orders", SERVICE)],
+        );
+        assert!(errors.is_empty(), "{:?}", located(&errors));
+    }
+
+    #[test]
+    fn undefined_references_are_located_in_their_service() {
+        let errors = validate_project(
+            r#"
+[[services]]
+name = "orders"
+provider = { name = "aws-lambda", platform_id = "aws" }
+
+[[services]]
+name = "users"
+provider = { name = "aws-lambda", platform_id = "gcp" }
+registry_id = "ecr"
+domain_name = "example.com"
+"#,
+            &[("orders", SERVICE), ("users", SERVICE)],
+        );
+        assert_eq!(
+            located(&errors),
+            vec![
+                (
+                    "assemblylift.toml: platform `gcp` of `users` is not defined".into(),
+                    Some(15),
+                    Some(49)
+                ),
+                (
+                    "assemblylift.toml: registry `ecr` of service `users` is not defined".into(),
+                    Some(16),
+                    Some(15)
+                ),
+                (
+                    "assemblylift.toml: domain `example.com` of service `users` is not defined"
+                        .into(),
+                    Some(17),
+                    Some(15)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn container_providers_require_a_registry() {
+        let errors = validate_project(
+            r#"
+[[platforms]]
+id = "cluster"
+name = "kubernetes"
+options = { config_path = "~/.kube/config" }
+
+[[services]]
+name = "orders"
+provider = { name = "knative", platform_id = "cluster" }
+
+[[services]]
+name = "users"
+provider = { name = "k8s", platform_id = "cluster" }
+"#,
+            &[("orders", FUNCTION), ("users", FUNCTION)],
+        );
+        let messages: Vec<&str> = errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .filter(|m| m.contains("registry_id"))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "service `orders` requires registry_id to be set",
+                "service `users` requires registry_id to be set",
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_platform_is_located_at_the_duplicate() {
+        let errors = validate_project(
+            r#"
+[[platforms]]
+id = "aws"
+name = "aws"
+options = {}
+"#,
+            &[],
+        );
+        assert_eq!(
+            located(&errors),
+            vec![(
+                "assemblylift.toml: platform id `aws` is defined more than once".into(),
+                Some(10),
+                Some(6)
+            )]
+        );
+    }
+
+    #[test]
+    fn function_errors_are_located_in_their_function() {
+        let service = format!(
+            "{}{}",
+            SERVICE,
+            r#"
+[[functions]]
+name = "cancel"
+language = "cobol"
+authorizer_id = "create"
+http = { verb = "POST", path = "/_asml/health" }
+"#
+        );
+        let errors = validate_project(
+            r#"
+[[services]]
+name = "orders"
+provider = { name = "aws-lambda", platform_id = "aws" }
+"#,
+            &[("orders", &service)],
+        );
+        // `create` is also the name of the first function, but the error is located in the second
+        let locations: Vec<(Option<usize>, Option<usize>)> =
+            errors.iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(errors.len(), 3, "{:?}", located(&errors));
+        assert!(errors[0].message.contains("authorizer `create`"));
+        assert!(errors[1].message.contains("/_asml/health"));
+        assert!(errors[2].message.contains("unknown language `cobol`"));
+        assert_eq!(
+            locations,
+            vec![
+                (Some(12), Some(17)),
+                (Some(13), Some(32)),
+                (Some(11), Some(12))
+            ]
+        );
+    }
+
+    #[test]
+    fn any_language_with_a_build_is_accepted() {
+        let service = format!(
+            "{}{}",
+            SERVICE,
+            r#"
+[[functions]]
+name = "cancel"
+language = "grain"
+build = { command = "grain compile handler.gr", output = "handler.gr.wasm" }
+
+[[functions]]
+name = "refund"
+language = "ruby"
+build = { command = "make", output = "handler.wasm" }
+"#
+        );
+        let errors = validate_project(
+            r#"
+[[services]]
+name = "orders"
+provider = { name = "aws-lambda", platform_id = "aws" }
+"#,
+            &[("orders", &service)],
+        );
+        assert_eq!(errors.len(), 1, "{:?}", located(&errors));
+        assert!(errors[0]
+            .message
+            .contains("`refund` in language `ruby` does not take a `build`"));
+    }
+
+    #[test]
+    fn runtime_versions_are_path_safe() {
+        assert!(is_runtime_version("3.3.0-dev"));
+        assert!(is_runtime_version("3.12.0"));
+        assert!(!is_runtime_version(""));
+        assert!(!is_runtime_version(".."));
+        assert!(!is_runtime_version("3.3/../../etc"));
+        assert!(!is_runtime_version("3.3.0?x=1"));
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// API Gateway HTTP API proxy event, payload format 2.0
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiGatewayV2HttpRequest {
+    pub version: String,
+    pub route_key: String,
+    pub raw_path: String,
+    pub raw_query_string: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookies: Option<Vec<String>>,
+    pub headers: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_string_parameters: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_parameters: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage_variables: Option<HashMap<String, String>>,
+    pub request_context: ApiGatewayV2HttpRequestContext,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    pub is_base64_encoded: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiGatewayV2HttpRequestContext {
+    pub account_id: String,
+    pub api_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorizer: Option<ApiGatewayV2HttpRequestContextAuthorizer>,
+    pub domain_name: String,
+    pub domain_prefix: String,
+    pub http: ApiGatewayV2HttpRequestContextHttp,
+    pub request_id: String,
+    pub route_key: String,
+    pub stage: String,
+    pub time: String,
+    pub time_epoch: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiGatewayV2HttpRequestContextAuthorizer {
+    pub jwt: Option<ApiGatewayV2HttpRequestContextAuthorizerJwt>,
+    pub lambda: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiGatewayV2HttpRequestContextAuthorizerJwt {
+    pub claims: HashMap<String, String>,
+    pub scopes: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiGatewayV2HttpRequestContextHttp {
+    pub method: String,
+    pub path: String,
+    pub protocol: String,
+    pub source_ip: String,
+    pub user_agent: String,
+}
+
+/// Response to an API Gateway HTTP API proxy event, payload format 2.0
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiGatewayV2HttpResponse {
+    pub status_code: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cookies: Vec<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub is_base64_encoded: bool,
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::sqs::region_from_arn;
+use super::{fixture_id, now_millis, FIXTURE_ACCOUNT_ID, FIXTURE_REGION};
+
+pub fn fixture_table_arn() -> String {
+    format!(
+        "arn:aws:dynamodb:{}:{}:table/fixture-table",
+        FIXTURE_REGION, FIXTURE_ACCOUNT_ID
+    )
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DynamoDbEvent {
+    #[serde(rename = "Records")]
+    pub records: Vec<DynamoDbEventRecord>,
+}
+
+impl DynamoDbEvent {
+    pub fn from_records(records: Vec<DynamoDbEventRecord>) -> Self {
+        Self { records }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamoDbEventRecord {
+    #[serde(rename = "eventID")]
+    pub event_id: String,
+    pub event_name: String,
+    pub event_version: String,
+    pub event_source: String,
+    pub aws_region: String,
+    pub dynamodb: DynamoDbStreamRecord,
+    #[serde(rename = "eventSourceARN")]
+    pub event_source_arn: String,
+}
+
+impl DynamoDbEventRecord {
+    /// An `INSERT` stream record for `new_image` on the table `table_arn`
+    pub fn insert(table_arn: &str, new_image: HashMap<String, AttributeValue>) -> Self {
+        Self::new("INSERT", table_arn, None, Some(new_image))
+    }
+
+    /// A `MODIFY` stream record replacing `old_image` with `new_image`
+    pub fn modify(
+        table_arn: &str,
+        old_image: HashMap<String, AttributeValue>,
+        new_image: HashMap<String, AttributeValue>,
+    ) -> Self {
+        Self::new("MODIFY", table_arn, Some(old_image), Some(new_image))
+    }
+
+    /// A `REMOVE` stream record for `old_image`
+    pub fn remove(table_arn: &str, old_image: HashMap<String, AttributeValue>) -> Self {
+        Self::new("REMOVE", table_arn, Some(old_image), None)
+    }
+
+    fn new(
+        event_name: &str,
+        table_arn: &str,
+        old_image: Option<HashMap<String, AttributeValue>>,
+        new_image: Option<HashMap<String, AttributeValue>>,
+    ) -> Self {
+        let view_type = match (&old_image, &new_image) {
+            (Some(_), Some(_)) => "NEW_AND_OLD_IMAGES",
+            (Some(_), None) => "OLD_IMAGE",
+            _ => "NEW_IMAGE",
+        };
+        Self {
+            event_id: fixture_id(),
+            event_name: event_name.to_string(),
+            event_version: "1.1".to_string(),
+            event_source: "aws:dynamodb".to_string(),
+            aws_region: region_from_arn(table_arn),
+            dynamodb: DynamoDbStreamRecord {
+                approximate_creation_date_time: (now_millis() / 1000) as f64,
+                keys: HashMap::new(),
+                new_image,
+                old_image,
+                sequence_number: format!("{}", now_millis()),
+                size_bytes: 0,
+                stream_view_type: view_type.to_string(),
+            },
+            event_source_arn: format!("{}/stream/fixture", table_arn),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DynamoDbStreamRecord {
+    pub approximate_creation_date_time: f64,
+    pub keys: HashMap<String, AttributeValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_image: Option<HashMap<String, AttributeValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_image: Option<HashMap<String, AttributeValue>>,
+    pub sequence_number: String,
+    pub size_bytes: u64,
+    pub stream_view_type: String,
+}
+
+/// A DynamoDB attribute value in its wire representation, e.g. `{"S": "hello"}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    S(String),
+    N(String),
+    B(String),
+    SS(Vec<String>),
+    NS(Vec<String>),
+    BS(Vec<String>),
+    M(HashMap<String, AttributeValue>),
+    L(Vec<AttributeValue>),
+    NULL(bool),
+    BOOL(bool),
+}
+
+impl AttributeValue {
+    pub fn from_json(value: &Value) -> Self {
+        match value {
+            Value::Null => AttributeValue::NULL(true),
+            Value::Bool(b) => AttributeValue::BOOL(*b),
+            Value::Number(n) => AttributeValue::N(n.to_string()),
+            Value::String(s) => AttributeValue::S(s.clone()),
+            Value::Array(a) => AttributeValue::L(a.iter().map(AttributeValue::from_json).collect()),
+            Value::Object(_) => AttributeValue::M(AttributeValue::from_json_object(value)),
+        }
+    }
+
+    /// Convert the fields of a JSON object into an item image; anything other than an object
+    /// produces an empty image
+    pub fn from_json_object(value: &Value) -> HashMap<String, AttributeValue> {
+        match value {
+            Value::Object(o) => o
+                .iter()
+                .map(|(k, v)| (k.clone(), AttributeValue::from_json(v)))
+                .collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{fixture_id, now_rfc3339, FIXTURE_ACCOUNT_ID, FIXTURE_REGION};
+
+/// An EventBridge event; scheduled rules deliver these with a `Scheduled Event` detail type
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EventBridgeEvent {
+    pub version: String,
+    pub id: String,
+    #[serde(rename = "detail-type")]
+    pub detail_type: String,
+    pub source: String,
+    pub account: String,
+    pub time: String,
+    pub region: String,
+    pub resources: Vec<String>,
+    pub detail: Value,
+}
+
+impl EventBridgeEvent {
+    pub fn new(source: &str, detail_type: &str, detail: Value) -> Self {
+        Self {
+            version: "0".to_string(),
+            id: fixture_id(),
+            detail_type: detail_type.to_string(),
+            source: source.to_string(),
+            account: FIXTURE_ACCOUNT_ID.to_string(),
+            time: now_rfc3339(),
+            region: FIXTURE_REGION.to_string(),
+            resources: Vec::new(),
+            detail,
+        }
+    }
+
+    /// The event delivered when the schedule rule `rule_arn` fires
+    pub fn scheduled(rule_arn: &str) -> Self {
+        let mut event = Self::new(
+            "aws.events",
+            "Scheduled Event",
+            Value::Object(Default::default()),
+        );
+        event.resources.push(rule_arn.to_string());
+        event
+    }
+}
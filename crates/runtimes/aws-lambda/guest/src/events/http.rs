@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{ApiGatewayEvent, ApiGatewayRequestContext, ApiGatewayRequestContextIdentity};
+
+use super::apigw_v2::{
+    ApiGatewayV2HttpRequest, ApiGatewayV2HttpRequestContext, ApiGatewayV2HttpRequestContextHttp,
+};
+use super::{fixture_id, now_millis, now_rfc3339, FIXTURE_ACCOUNT_ID};
+
+/// A plain HTTP request, as received by a local runtime such as the Hyper runtime
+#[derive(Clone, Debug, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    /// The request path, optionally including the query string
+    pub path: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    pub fn new(
+        method: &str,
+        path: &str,
+        headers: BTreeMap<String, String>,
+        body: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            method: method.to_uppercase(),
+            path: path.to_string(),
+            headers,
+            body,
+        }
+    }
+
+    pub fn raw_path(&self) -> &str {
+        self.path.split('?').next().unwrap_or("/")
+    }
+
+    pub fn raw_query_string(&self) -> &str {
+        self.path.split_once('?').map(|(_, q)| q).unwrap_or("")
+    }
+
+    pub fn query_string_parameters(&self) -> Option<HashMap<String, String>> {
+        let query = self.raw_query_string();
+        if query.is_empty() {
+            return None;
+        }
+        Some(
+            query
+                .split('&')
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    let mut kv = p.splitn(2, '=');
+                    (
+                        kv.next().unwrap_or_default().to_string(),
+                        kv.next().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// The body as text, lossily decoded as UTF-8
+    pub fn body_text(&self) -> String {
+        match &self.body {
+            Some(body) => String::from_utf8_lossy(body).to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// The body as API Gateway would deliver it, plus whether it was base64 encoded
+    fn encoded_body(&self) -> (Option<String>, bool) {
+        match &self.body {
+            Some(body) if body.is_empty() => (None, false),
+            Some(body) => match std::str::from_utf8(body) {
+                Ok(text) => (Some(text.to_string()), false),
+                Err(_) => (Some(base64::encode(body)), true),
+            },
+            None => (None, false),
+        }
+    }
+
+    fn source_ip(&self) -> String {
+        self.headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_string())
+            .unwrap_or("127.0.0.1".to_string())
+    }
+
+    /// Map this request onto an API Gateway REST API (payload format 1.0) proxy event
+    pub fn to_apigw_v1(&self) -> ApiGatewayEvent {
+        ApiGatewayEvent {
+            resource: self.raw_path().to_string(),
+            path: self.raw_path().to_string(),
+            http_method: self.method.clone(),
+            headers: self.headers.clone().into_iter().collect(),
+            query_string_parameters: self.query_string_parameters(),
+            path_parameters: None,
+            stage_variables: None,
+            request_context: Some(ApiGatewayRequestContext {
+                authorizer: None,
+                identity: Some(ApiGatewayRequestContextIdentity {
+                    access_key: None,
+                    account_id: None,
+                    caller: None,
+                    cognito_amr: None,
+                    cognito_authentication_provider: None,
+                    cognito_authentication_type: None,
+                    cognito_identity_id: None,
+                    cognito_identity_pool_id: None,
+                    principal_org_id: None,
+                    source_ip: self.source_ip(),
+                    user: None,
+                    user_agent: self.headers.get("user-agent").cloned(),
+                    user_arn: None,
+                }),
+            }),
+            body: self.encoded_body().0,
+        }
+    }
+
+    /// Map this request onto an API Gateway HTTP API (payload format 2.0) proxy event
+    pub fn to_apigw_v2(&self) -> ApiGatewayV2HttpRequest {
+        let (body, is_base64_encoded) = self.encoded_body();
+        let cookies = self
+            .headers
+            .get("cookie")
+            .map(|c| c.split(';').map(|s| s.trim().to_string()).collect());
+        ApiGatewayV2HttpRequest {
+            version: "2.0".to_string(),
+            route_key: "$default".to_string(),
+            raw_path: self.raw_path().to_string(),
+            raw_query_string: self.raw_query_string().to_string(),
+            cookies,
+            headers: self.headers.clone().into_iter().collect(),
+            query_string_parameters: self.query_string_parameters(),
+            path_parameters: None,
+            stage_variables: None,
+            request_context: ApiGatewayV2HttpRequestContext {
+                account_id: FIXTURE_ACCOUNT_ID.to_string(),
+                api_id: "local".to_string(),
+                authorizer: None,
+                domain_name: self
+                    .headers
+                    .get("host")
+                    .cloned()
+                    .unwrap_or("localhost".to_string()),
+                domain_prefix: "local".to_string(),
+                http: ApiGatewayV2HttpRequestContextHttp {
+                    method: self.method.clone(),
+                    path: self.raw_path().to_string(),
+                    protocol: "HTTP/1.1".to_string(),
+                    source_ip: self.source_ip(),
+                    user_agent: self.headers.get("user-agent").cloned().unwrap_or_default(),
+                },
+                request_id: fixture_id(),
+                route_key: "$default".to_string(),
+                stage: "$default".to_string(),
+                time: now_rfc3339(),
+                time_epoch: now_millis(),
+            },
+            body,
+            is_base64_encoded,
+        }
+    }
+}
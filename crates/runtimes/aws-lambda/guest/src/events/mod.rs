@@ -0,0 +1,251 @@
+//! Typed AWS Lambda event payloads, and generators for producing them locally.
+//!
+//! Each event type has one or more constructors which build a plausible event as Lambda would deliver
+//! it, which is useful for fixtures in tests and for emulating a Lambda event source when running a
+//! function outside of Lambda (see [`EventShape`]).
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+pub mod apigw_v2;
+pub mod dynamodb;
+pub mod eventbridge;
+pub mod http;
+pub mod s3;
+pub mod sns;
+pub mod sqs;
+
+pub use apigw_v2::*;
+pub use dynamodb::*;
+pub use eventbridge::*;
+pub use http::*;
+pub use s3::*;
+pub use sns::*;
+pub use sqs::*;
+
+/// Default region and account used by the event generators
+pub const FIXTURE_REGION: &str = "us-east-1";
+pub const FIXTURE_ACCOUNT_ID: &str = "123456789012";
+
+/// The Lambda event shape which a plain HTTP request should be wrapped in
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EventShape {
+    /// API Gateway REST API (payload format 1.0)
+    ApiGatewayV1,
+    /// API Gateway HTTP API (payload format 2.0)
+    ApiGatewayV2,
+    Sqs,
+    Sns,
+    S3,
+    EventBridge,
+    DynamoDb,
+}
+
+impl FromStr for EventShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "apigw" | "apigw-v1" | "rest" => Ok(EventShape::ApiGatewayV1),
+            "apigw-v2" | "http" => Ok(EventShape::ApiGatewayV2),
+            "sqs" => Ok(EventShape::Sqs),
+            "sns" => Ok(EventShape::Sns),
+            "s3" => Ok(EventShape::S3),
+            "eventbridge" | "schedule" => Ok(EventShape::EventBridge),
+            "dynamodb" => Ok(EventShape::DynamoDb),
+            other => Err(format!("unknown event shape `{}`", other)),
+        }
+    }
+}
+
+impl EventShape {
+    /// Wrap `request` in this event shape. For the API Gateway shapes the request is mapped onto the
+    /// proxy integration event; for every other shape the request body becomes the event payload
+    /// (message body, object key, event detail, or stream record image).
+    pub fn wrap(&self, request: &HttpRequest) -> serde_json::Result<Value> {
+        let body = request.body_text();
+        match self {
+            EventShape::ApiGatewayV1 => serde_json::to_value(request.to_apigw_v1()),
+            EventShape::ApiGatewayV2 => serde_json::to_value(request.to_apigw_v2()),
+            EventShape::Sqs => serde_json::to_value(SqsEvent::from_bodies(
+                &sqs::fixture_queue_arn(),
+                vec![body],
+            )),
+            EventShape::Sns => serde_json::to_value(SnsEvent::from_messages(
+                &sns::fixture_topic_arn(),
+                vec![body],
+            )),
+            EventShape::S3 => serde_json::to_value(S3Event::object_created(
+                "fixture-bucket",
+                request.path.trim_start_matches('/'),
+                body.len() as u64,
+            )),
+            EventShape::EventBridge => {
+                let detail = serde_json::from_str(&body).unwrap_or(Value::String(body));
+                serde_json::to_value(EventBridgeEvent::new(
+                    "assemblylift.local",
+                    &format!("{} {}", request.method, request.path),
+                    detail,
+                ))
+            }
+            EventShape::DynamoDb => {
+                let image = serde_json::from_str(&body).unwrap_or(Value::Null);
+                serde_json::to_value(DynamoDbEvent::from_records(vec![
+                    DynamoDbEventRecord::insert(
+                        &dynamodb::fixture_table_arn(),
+                        AttributeValue::from_json_object(&image),
+                    ),
+                ]))
+            }
+        }
+    }
+}
+
+static FIXTURE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A unique, UUID-formatted identifier for generated events
+pub(crate) fn fixture_id() -> String {
+    let n = FIXTURE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let t = now_millis();
+    format!(
+        "{:08x}-{:04x}-4{:03x}-8{:03x}-{:012x}",
+        (t >> 16) as u32,
+        (t & 0xffff) as u16,
+        (n >> 12) & 0xfff,
+        n & 0xfff,
+        (t ^ n.rotate_left(32)) & 0xffff_ffff_ffff,
+    )
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// The current time formatted as an RFC 3339 timestamp in UTC, e.g. `2024-01-31T12:00:00.000Z`
+pub(crate) fn now_rfc3339() -> String {
+    let millis = now_millis();
+    let secs = millis / 1000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        millis % 1000,
+    )
+}
+
+// Howard Hinnant's days-to-civil algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_from_days_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_753), (2024, 1, 31));
+    }
+
+    #[test]
+    fn wrap_http_as_apigw_v2() {
+        let request = HttpRequest::new("POST", "/items?color=red", Default::default(), Some(b"{}".to_vec()));
+        let event = EventShape::ApiGatewayV2.wrap(&request).unwrap();
+        assert_eq!(event["version"], "2.0");
+        assert_eq!(event["rawPath"], "/items");
+        assert_eq!(event["rawQueryString"], "color=red");
+        assert_eq!(event["requestContext"]["http"]["method"], "POST");
+        assert_eq!(event["body"], "{}");
+    }
+
+    #[test]
+    fn wrap_binary_body_as_base64() {
+        let request = HttpRequest::new("POST", "/", Default::default(), Some(vec![0xff, 0x00]));
+        let event = EventShape::ApiGatewayV2.wrap(&request).unwrap();
+        assert_eq!(event["body"], "/wA=");
+        assert_eq!(event["isBase64Encoded"], true);
+    }
+
+    #[test]
+    fn wrap_http_as_sqs() {
+        let request = HttpRequest::new("POST", "/", Default::default(), Some(b"hello".to_vec()));
+        let event = EventShape::Sqs.wrap(&request).unwrap();
+        assert_eq!(event["Records"][0]["body"], "hello");
+        assert_eq!(event["Records"][0]["eventSource"], "aws:sqs");
+    }
+
+    #[test]
+    fn wrap_http_as_sns() {
+        let request = HttpRequest::new("POST", "/", Default::default(), Some(b"hello".to_vec()));
+        let event = EventShape::Sns.wrap(&request).unwrap();
+        let record = &event["Records"][0];
+        assert_eq!(record["EventSource"], "aws:sns");
+        assert_eq!(record["Sns"]["Type"], "Notification");
+        assert_eq!(record["Sns"]["Message"], "hello");
+        assert_eq!(record["Sns"]["TopicArn"], sns::fixture_topic_arn());
+    }
+
+    #[test]
+    fn wrap_http_as_s3() {
+        let request = HttpRequest::new("PUT", "/uploads/cat.png", Default::default(), Some(b"meow".to_vec()));
+        let event = EventShape::S3.wrap(&request).unwrap();
+        let record = &event["Records"][0];
+        assert_eq!(record["eventSource"], "aws:s3");
+        assert_eq!(record["eventName"], "ObjectCreated:Put");
+        assert_eq!(record["s3"]["bucket"]["name"], "fixture-bucket");
+        assert_eq!(record["s3"]["object"]["key"], "uploads/cat.png");
+        assert_eq!(record["s3"]["object"]["size"], 4);
+    }
+
+    #[test]
+    fn wrap_http_as_eventbridge() {
+        let request = HttpRequest::new("POST", "/orders", Default::default(), Some(br#"{"id":7}"#.to_vec()));
+        let event = EventShape::EventBridge.wrap(&request).unwrap();
+        assert_eq!(event["source"], "assemblylift.local");
+        assert_eq!(event["detail-type"], "POST /orders");
+        assert_eq!(event["detail"]["id"], 7);
+
+        // A body which isn't JSON is passed through as a string
+        let request = HttpRequest::new("POST", "/orders", Default::default(), Some(b"tick".to_vec()));
+        let event = EventShape::EventBridge.wrap(&request).unwrap();
+        assert_eq!(event["detail"], "tick");
+    }
+
+    #[test]
+    fn wrap_http_as_dynamodb() {
+        let body = br#"{"id":"a1","qty":2,"tags":["x"]}"#.to_vec();
+        let request = HttpRequest::new("POST", "/", Default::default(), Some(body));
+        let event = EventShape::DynamoDb.wrap(&request).unwrap();
+        let record = &event["Records"][0];
+        assert_eq!(record["eventName"], "INSERT");
+        assert_eq!(record["eventSource"], "aws:dynamodb");
+        assert_eq!(record["dynamodb"]["StreamViewType"], "NEW_IMAGE");
+        let image = &record["dynamodb"]["NewImage"];
+        assert_eq!(image["id"], serde_json::json!({ "S": "a1" }));
+        assert_eq!(image["qty"], serde_json::json!({ "N": "2" }));
+        assert_eq!(image["tags"], serde_json::json!({ "L": [{ "S": "x" }] }));
+        assert!(record["dynamodb"].get("OldImage").is_none());
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{fixture_id, now_rfc3339, FIXTURE_ACCOUNT_ID, FIXTURE_REGION};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct S3Event {
+    #[serde(rename = "Records")]
+    pub records: Vec<S3EventRecord>,
+}
+
+impl S3Event {
+    /// A single `ObjectCreated:Put` notification for `key` in `bucket`
+    pub fn object_created(bucket: &str, key: &str, size: u64) -> Self {
+        Self {
+            records: vec![S3EventRecord::new("ObjectCreated:Put", bucket, key, Some(size))],
+        }
+    }
+
+    /// A single `ObjectRemoved:Delete` notification for `key` in `bucket`
+    pub fn object_removed(bucket: &str, key: &str) -> Self {
+        Self {
+            records: vec![S3EventRecord::new("ObjectRemoved:Delete", bucket, key, None)],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct S3EventRecord {
+    pub event_version: String,
+    pub event_source: String,
+    pub aws_region: String,
+    pub event_time: String,
+    pub event_name: String,
+    pub user_identity: S3UserIdentity,
+    pub request_parameters: HashMap<String, String>,
+    pub response_elements: HashMap<String, String>,
+    pub s3: S3Entity,
+}
+
+impl S3EventRecord {
+    pub fn new(event_name: &str, bucket: &str, key: &str, size: Option<u64>) -> Self {
+        let mut request_parameters = HashMap::new();
+        request_parameters.insert("sourceIPAddress".to_string(), "127.0.0.1".to_string());
+        let mut response_elements = HashMap::new();
+        response_elements.insert("x-amz-request-id".to_string(), fixture_id());
+        response_elements.insert("x-amz-id-2".to_string(), fixture_id());
+        Self {
+            event_version: "2.1".to_string(),
+            event_source: "aws:s3".to_string(),
+            aws_region: FIXTURE_REGION.to_string(),
+            event_time: now_rfc3339(),
+            event_name: event_name.to_string(),
+            user_identity: S3UserIdentity {
+                principal_id: FIXTURE_ACCOUNT_ID.to_string(),
+            },
+            request_parameters,
+            response_elements,
+            s3: S3Entity {
+                s3_schema_version: "1.0".to_string(),
+                configuration_id: "fixture".to_string(),
+                bucket: S3Bucket {
+                    name: bucket.to_string(),
+                    owner_identity: S3UserIdentity {
+                        principal_id: FIXTURE_ACCOUNT_ID.to_string(),
+                    },
+                    arn: format!("arn:aws:s3:::{}", bucket),
+                },
+                object: S3Object {
+                    key: key.to_string(),
+                    size,
+                    e_tag: None,
+                    version_id: None,
+                    sequencer: format!("{:016X}", super::now_millis()),
+                },
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct S3UserIdentity {
+    pub principal_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Entity {
+    pub s3_schema_version: String,
+    pub configuration_id: String,
+    pub bucket: S3Bucket,
+    pub object: S3Object,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Bucket {
+    pub name: String,
+    pub owner_identity: S3UserIdentity,
+    pub arn: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Object {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(rename = "eTag", skip_serializing_if = "Option::is_none")]
+    pub e_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    pub sequencer: String,
+}
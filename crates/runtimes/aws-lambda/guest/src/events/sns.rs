@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::sqs::region_from_arn;
+use super::{fixture_id, now_rfc3339, FIXTURE_ACCOUNT_ID, FIXTURE_REGION};
+
+pub fn fixture_topic_arn() -> String {
+    format!("arn:aws:sns:{}:{}:fixture-topic", FIXTURE_REGION, FIXTURE_ACCOUNT_ID)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SnsEvent {
+    #[serde(rename = "Records")]
+    pub records: Vec<SnsRecord>,
+}
+
+impl SnsEvent {
+    /// An event containing one notification per entry in `messages`, published to `topic_arn`
+    pub fn from_messages(topic_arn: &str, messages: Vec<String>) -> Self {
+        Self {
+            records: messages
+                .into_iter()
+                .map(|message| SnsRecord::new(topic_arn, message))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsRecord {
+    pub event_source: String,
+    pub event_version: String,
+    pub event_subscription_arn: String,
+    pub sns: SnsMessage,
+}
+
+impl SnsRecord {
+    pub fn new(topic_arn: &str, message: String) -> Self {
+        Self {
+            event_source: "aws:sns".to_string(),
+            event_version: "1.0".to_string(),
+            event_subscription_arn: format!("{}:{}", topic_arn, fixture_id()),
+            sns: SnsMessage {
+                sns_message_type: "Notification".to_string(),
+                message_id: fixture_id(),
+                topic_arn: topic_arn.to_string(),
+                subject: None,
+                message,
+                timestamp: now_rfc3339(),
+                signature_version: "1".to_string(),
+                signature: String::new(),
+                signing_cert_url: String::new(),
+                unsubscribe_url: String::new(),
+                message_attributes: HashMap::new(),
+            },
+        }
+    }
+
+    pub fn region(&self) -> String {
+        region_from_arn(&self.sns.topic_arn)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessage {
+    #[serde(rename = "Type")]
+    pub sns_message_type: String,
+    pub message_id: String,
+    pub topic_arn: String,
+    pub subject: Option<String>,
+    pub message: String,
+    pub timestamp: String,
+    pub signature_version: String,
+    pub signature: String,
+    #[serde(rename = "SigningCertUrl")]
+    pub signing_cert_url: String,
+    #[serde(rename = "UnsubscribeUrl")]
+    pub unsubscribe_url: String,
+    pub message_attributes: HashMap<String, SnsMessageAttribute>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessageAttribute {
+    #[serde(rename = "Type")]
+    pub data_type: String,
+    pub value: String,
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{fixture_id, now_millis, FIXTURE_ACCOUNT_ID, FIXTURE_REGION};
+
+pub fn fixture_queue_arn() -> String {
+    format!("arn:aws:sqs:{}:{}:fixture-queue", FIXTURE_REGION, FIXTURE_ACCOUNT_ID)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SqsEvent {
+    #[serde(rename = "Records")]
+    pub records: Vec<SqsMessage>,
+}
+
+impl SqsEvent {
+    /// An event containing one message per entry in `bodies`, received from the queue `queue_arn`
+    pub fn from_bodies(queue_arn: &str, bodies: Vec<String>) -> Self {
+        Self {
+            records: bodies
+                .into_iter()
+                .map(|body| SqsMessage::new(queue_arn, body))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsMessage {
+    pub message_id: String,
+    pub receipt_handle: String,
+    pub body: String,
+    pub attributes: HashMap<String, String>,
+    pub message_attributes: HashMap<String, SqsMessageAttribute>,
+    pub md5_of_body: String,
+    pub event_source: String,
+    #[serde(rename = "eventSourceARN")]
+    pub event_source_arn: String,
+    pub aws_region: String,
+}
+
+impl SqsMessage {
+    pub fn new(queue_arn: &str, body: String) -> Self {
+        let sent = now_millis().to_string();
+        let mut attributes = HashMap::new();
+        attributes.insert("ApproximateReceiveCount".to_string(), "1".to_string());
+        attributes.insert("SentTimestamp".to_string(), sent.clone());
+        attributes.insert("SenderId".to_string(), FIXTURE_ACCOUNT_ID.to_string());
+        attributes.insert("ApproximateFirstReceiveTimestamp".to_string(), sent);
+        Self {
+            message_id: fixture_id(),
+            receipt_handle: fixture_id(),
+            body,
+            attributes,
+            message_attributes: HashMap::new(),
+            // Lambda doesn't verify this, so we don't bother computing a real digest
+            md5_of_body: String::new(),
+            event_source: "aws:sqs".to_string(),
+            event_source_arn: queue_arn.to_string(),
+            aws_region: region_from_arn(queue_arn),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsMessageAttribute {
+    pub string_value: Option<String>,
+    pub binary_value: Option<String>,
+    pub data_type: String,
+}
+
+pub(crate) fn region_from_arn(arn: &str) -> String {
+    arn.split(':')
+        .nth(3)
+        .filter(|r| !r.is_empty())
+        .unwrap_or(FIXTURE_REGION)
+        .to_string()
+}
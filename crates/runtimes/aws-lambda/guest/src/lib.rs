@@ -1,4 +1,6 @@
+#[cfg(feature = "guest")]
 extern crate assemblylift_core_guest;
+#[cfg(feature = "guest")]
 extern crate assemblylift_core_io_guest;
 
 use std::collections::HashMap;
@@ -6,6 +8,8 @@ use std::fmt::Debug;
 
 use serde::{Deserialize, Serialize};
 
+pub mod events;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ApiGatewayEvent {
     pub resource: String,
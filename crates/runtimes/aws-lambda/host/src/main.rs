@@ -126,24 +126,6 @@ async fn main() -> Result<(), Error> {
                 .expect(&*format!("unable to create directory {:?}", rubyusr_path));
         }
 
-        fn copy_entries(dir: &PathBuf, to: &PathBuf) {
-            for entry in fs::read_dir(dir).unwrap() {
-                let entry = entry.unwrap();
-                if entry.file_type().unwrap().is_file() {
-                    let copy_to = format!(
-                        "{}/{}",
-                        to.to_str().unwrap(),
-                        entry.file_name().to_str().unwrap()
-                    );
-                    fs::copy(entry.path(), copy_to).unwrap();
-                } else if entry.file_type().unwrap().is_dir() {
-                    let mut copy_to = PathBuf::from(to);
-                    copy_to.push(entry.path().iter().last().unwrap());
-                    fs::create_dir_all(&copy_to).unwrap();
-                    copy_entries(&entry.path(), &copy_to);
-                }
-            }
-        }
         copy_entries(
             &PathBuf::from(format!("{}/rubysrc", &module_path)),
             &PathBuf::from(rubysrc_path),
@@ -154,6 +136,41 @@ async fn main() -> Result<(), Error> {
         );
     }
 
+    // Copy Python env to /tmp
+    if let Ok("python") = runtime_environment.as_deref() {
+        let pysrc_path = "/tmp/pysrc";
+        if !Path::new(&pysrc_path).exists() {
+            fs::create_dir_all(pysrc_path)
+                .expect(&*format!("unable to create directory {:?}", pysrc_path));
+        }
+        let pythonusr_path = "/tmp/pythonusr";
+        if !Path::new(&pythonusr_path).exists() {
+            fs::create_dir_all(pythonusr_path)
+                .expect(&*format!("unable to create directory {:?}", pythonusr_path));
+        }
+
+        copy_entries(
+            &PathBuf::from(format!("{}/pysrc", &module_path)),
+            &PathBuf::from(pysrc_path),
+        );
+        copy_entries(
+            &PathBuf::from("/opt/python-wasm32-wasi/usr"),
+            &PathBuf::from(pythonusr_path),
+        );
+    }
+
+    // Copy the bundled JavaScript source to /tmp
+    if let Ok("javascript") = runtime_environment.as_deref() {
+        let jssrc_path = "/tmp/jssrc";
+        fs::create_dir_all(jssrc_path)
+            .expect(&*format!("unable to create directory {:?}", jssrc_path));
+        fs::copy(
+            format!("{}/jssrc/handler.js", &module_path),
+            format!("{}/handler.js", jssrc_path),
+        )
+        .expect("unable to copy javascript handler");
+    }
+
     let mut full_path = PathBuf::from(&module_path);
     full_path.push(&handler_name);
     let wasmtime = RefCell::new(
@@ -180,6 +197,8 @@ async fn main() -> Result<(), Error> {
             let runtime_environment = std::env::var("ASML_FUNCTION_ENV");
             let bind_paths: Vec<(String, String)> = match runtime_environment.as_deref().ok() {
                 Some("ruby") => vec![("/tmp/rubysrc".into(), "/src".into()), ("/tmp/rubyusr".into(), "/usr".into())],
+                Some("python") => vec![("/tmp/pysrc".into(), "/src".into()), ("/tmp/pythonusr".into(), "/usr".into())],
+                Some("javascript") => vec![("/tmp/jssrc".into(), "/src".into())],
                 Some(_) | None => Vec::new(),
             };
             let (status_tx, status_rx) = status_channel::<Status>(1);
@@ -227,3 +246,22 @@ async fn main() -> Result<(), Error> {
     .await?;
     Ok(())
 }
+
+fn copy_entries(dir: &PathBuf, to: &PathBuf) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_file() {
+            let copy_to = format!(
+                "{}/{}",
+                to.to_str().unwrap(),
+                entry.file_name().to_str().unwrap()
+            );
+            fs::copy(entry.path(), copy_to).unwrap();
+        } else if entry.file_type().unwrap().is_dir() {
+            let mut copy_to = PathBuf::from(to);
+            copy_to.push(entry.path().iter().last().unwrap());
+            fs::create_dir_all(&copy_to).unwrap();
+            copy_entries(&entry.path(), &copy_to);
+        }
+    }
+}
@@ -52,7 +52,8 @@ impl KeysAbi for InMemorySecrets {
         info!("decrypting with key_id={}", &id);
 
         let raw_data: &[u8] = ciphertext.as_ref();
-        if raw_data.len() <= 44 {
+        // 12-byte nonce followed by the ciphertext & 16-byte tag
+        if raw_data.len() < 28 {
             return Err(anyhow!("Encrypted data too short"));
         }
 
@@ -97,3 +98,25 @@ impl SecretsAbi for InMemorySecrets {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_plaintexts_round_trip() {
+        for plaintext in [&b""[..], b"a", b"short secret"] {
+            let ciphertext = InMemorySecrets::encrypt("default".into(), plaintext.to_vec()).unwrap();
+            // 12-byte nonce + the plaintext + 16-byte tag
+            assert_eq!(ciphertext.len(), 28 + plaintext.len());
+            let decrypted = InMemorySecrets::decrypt("default".into(), ciphertext).unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn ciphertext_shorter_than_nonce_and_tag_is_rejected() {
+        let err = InMemorySecrets::decrypt("default".into(), vec![0; 27]).unwrap_err();
+        assert!(err.to_string().contains("too short"));
+    }
+}
@@ -14,6 +14,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, warn};
 use url::Url;
 
+use assemblylift_awslambda_guest::events::{EventShape, HttpRequest};
 use assemblylift_core::wasm::{status_channel, StatusRx, StatusTx};
 
 use crate::runner::{RunnerMessage, RunnerTx};
@@ -28,7 +29,13 @@ pub const FUNCTION_COORDINATES: Lazy<Option<String>> =
     Lazy::new(|| std::env::var("ASML_FUNCTION_COORDINATES").ok());
 pub const FUNCTION_PRECOMPILED: Lazy<Option<String>> = 
     Lazy::new(|| std::env::var("ASML_FUNCTION_PRECOMPILED").ok());
+pub const FUNCTION_EVENT_SHAPE: Lazy<Option<String>> =
+    Lazy::new(|| std::env::var("ASML_FUNCTION_EVENT_SHAPE").ok());
 pub const MAX_ALLOWED_REQUEST_SIZE: u64 = 10_485_760;
+/// Port for function triggers inside the cluster; only requests on this port may pick an event shape per request
+pub const TRIGGER_PORT: u16 = 5544;
+/// Answered by the launcher itself without invoking the function, for liveness & readiness probes
+pub const HEALTH_PATH: &str = "/_asml/health";
 
 pub struct Launcher {
     runtime: tokio::runtime::Runtime,
@@ -49,39 +56,64 @@ impl Launcher {
         tokio::task::LocalSet::new().block_on(&self.runtime, async {
             let channel = status_channel(32);
 
-            let make_svc = make_service_fn(|_| {
-                debug!("called make_service_fn");
+            let serve = |port: u16, trigger: bool| {
                 let runner_tx = runner_tx.clone();
                 let tx = channel.0.clone();
                 let rx = channel.1.clone();
-                async {
-                    Ok::<_, anyhow::Error>(service_fn(move |req| {
-                        launch(req, runner_tx.clone(), tx.clone(), rx.clone())
-                    }))
+                let make_svc = make_service_fn(move |_| {
+                    debug!("called make_service_fn");
+                    let runner_tx = runner_tx.clone();
+                    let tx = tx.clone();
+                    let rx = rx.clone();
+                    async move {
+                        Ok::<_, anyhow::Error>(service_fn(move |req| {
+                            launch(req, trigger, runner_tx.clone(), tx.clone(), rx.clone())
+                        }))
+                    }
+                });
+
+                let addr = SocketAddr::from(([0, 0, 0, 0], port));
+                info!("Serving from {}", addr.to_string());
+                async move {
+                    if let Err(e) = Server::bind(&addr).serve(make_svc).await {
+                        error!("server error: {}", e);
+                    }
                 }
-            });
+            };
 
-            let addr = SocketAddr::from(([0, 0, 0, 0], 5543));
-            info!("Serving from {}", addr.to_string());
-            if let Err(e) = Server::bind(&addr).serve(make_svc).await {
-                error!("server error: {}", e);
-            }
+            tokio::join!(serve(5543, false), serve(TRIGGER_PORT, true));
         });
     }
 }
 
 async fn launch(
     req: Request<Body>,
+    trigger: bool,
     runner_tx: RunnerTx<Status>,
     status_tx: StatusTx<Status>,
     status_rx: StatusRx<Status>,
 ) -> anyhow::Result<Response<Body>> {
+    if req.uri().path() == HEALTH_PATH {
+        return Ok(Response::builder()
+            .status(200)
+            .body(Body::from("ok"))
+            .unwrap());
+    }
+
     debug!("launching function...");
     let method = req.method().to_string();
     let path = req.uri().path().to_string();
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str().to_string())
+        .unwrap_or(path.clone());
     let mut headers = BTreeMap::new();
     for h in req.headers().iter() {
-        headers.insert(h.0.as_str().to_string(), h.1.to_str().unwrap().to_string());
+        // Header values which aren't visible ASCII can't be passed on as strings, and are dropped
+        if let Ok(value) = h.1.to_str() {
+            headers.insert(h.0.as_str().to_string(), value.to_string());
+        }
     }
     let request_content_length = match req.body().size_hint().upper() {
         Some(v) => v,
@@ -99,12 +131,48 @@ async fn launch(
                 .unwrap()
         }
     };
-    let launcher_req = LauncherRequest {
-        method,
-        path,
-        headers: headers.clone(),
-        body_encoding: "base64".into(),
-        body: Some(base64::encode(input_bytes.as_ref())),
+    // When an event shape is requested, the function receives the request wrapped in the
+    // matching Lambda event instead of a LauncherRequest. Only triggers may pick the shape per request.
+    let event_shape = match FUNCTION_EVENT_SHAPE.deref() {
+        Some(shape) => Some(shape.clone()),
+        None if trigger => headers.get("x-assemblylift-function-event-shape").cloned(),
+        None => None,
+    };
+    let input = match event_shape {
+        Some(shape) => {
+            let shape = match EventShape::from_str(&shape) {
+                Ok(shape) => shape,
+                Err(e) => {
+                    return Ok(Response::builder()
+                        .status(400)
+                        .body(Body::from(e))
+                        .unwrap())
+                }
+            };
+            let http_req = HttpRequest::new(
+                &method,
+                &path_and_query,
+                headers.clone(),
+                Some(input_bytes.to_vec()),
+            );
+            match shape.wrap(&http_req).and_then(|event| serde_json::to_vec(&event)) {
+                Ok(event) => event,
+                Err(e) => {
+                    return Ok(Response::builder()
+                        .status(400)
+                        .body(Body::from(format!("could not wrap request in event: {}", e)))
+                        .unwrap())
+                }
+            }
+        }
+        None => serde_json::to_vec(&LauncherRequest {
+            method,
+            path,
+            headers: headers.clone(),
+            body_encoding: "base64".into(),
+            body: Some(base64::encode(input_bytes.as_ref())),
+        })
+        .unwrap(),
     };
 
     let wasm_ext = match FUNCTION_PRECOMPILED.deref() {
@@ -185,7 +253,7 @@ async fn launch(
 
     let runtime_environment = headers.get("x-assemblylift-function-runtime-env").cloned();
     let msg = RunnerMessage {
-        input: serde_json::to_vec(&launcher_req).unwrap(),
+        input,
         status_sender: status_tx.clone(),
         wasm_path: PathBuf::from(wasm_uri.path()),
         env_vars,
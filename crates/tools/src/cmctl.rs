@@ -9,24 +9,22 @@ pub struct CmCtl {
     kubeconfig: Option<String>,
 }
 
-impl Default for CmCtl {
-    fn default() -> Self {
-        CmCtl::new("cmctl", ".asml/bin", None)
+impl CmCtl {
+    pub fn try_default() -> anyhow::Result<Self> {
+        Self::new("cmctl", ".asml/bin", None)
     }
-}
 
-impl CmCtl {
-    pub fn new(name: &str, path: &str, kubeconfig: Option<String>) -> Self {
+    pub fn new(name: &str, path: &str, kubeconfig: Option<String>) -> anyhow::Result<Self> {
         let s = Self {
             cmd: name.into(),
             path: path.into(),
             kubeconfig,
         };
-        crate::fetch(&s).unwrap();
-        s
+        crate::fetch(&s)?;
+        Ok(s)
     }
 
-    pub fn default_with_config(kubeconfig: String) -> Self {
+    pub fn default_with_config(kubeconfig: String) -> anyhow::Result<Self> {
         Self::new("cmctl", ".asml/bin", Some(kubeconfig))
     }
 
@@ -67,4 +65,8 @@ impl Tool for CmCtl {
         #[cfg(target_os = "macos")]
         return "https://github.com/cert-manager/cert-manager/releases/download/v1.13.3/cmctl-darwin-amd64.tar.gz";
     }
+
+    fn version(&self) -> &str {
+        "1.13.3"
+    }
 }
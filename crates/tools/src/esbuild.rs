@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::Tool;
+
+pub const ESBUILD_VERSION: &str = "0.19.12";
+
+/// Bundles JavaScript & TypeScript function sources into a single script
+pub struct Esbuild {
+    cmd: String,
+    path: String,
+}
+
+impl Esbuild {
+    pub fn try_default() -> anyhow::Result<Self> {
+        Self::new("esbuild", ".asml/bin")
+    }
+
+    pub fn new(name: &str, path: &str) -> anyhow::Result<Self> {
+        let s = Self {
+            cmd: name.into(),
+            path: path.into(),
+        };
+        crate::fetch(&s)?;
+        Ok(s)
+    }
+
+    /// Bundle `entrypoint` and everything it imports into `outfile`. Modules named in `external` are left to be
+    /// provided by the JS engine.
+    pub fn bundle(
+        &self,
+        entrypoint: &Path,
+        outfile: &Path,
+        external: &[&str],
+    ) -> Result<(), String> {
+        let output = self
+            .command()
+            .arg(entrypoint)
+            .arg("--bundle")
+            .arg("--format=esm")
+            .arg("--platform=neutral")
+            .arg("--target=es2020")
+            .args(external.iter().map(|e| format!("--external:{}", e)))
+            .arg(format!("--outfile={}", outfile.display()))
+            .output()
+            .map_err(|e| format!("could not run esbuild: {}", e))?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(format!(
+                "esbuild could not bundle {}:\n{}",
+                entrypoint.display(),
+                String::from_utf8_lossy(&output.stderr)
+            )),
+        }
+    }
+}
+
+impl Tool for Esbuild {
+    fn command_name(&self) -> &str {
+        self.cmd.as_str()
+    }
+
+    fn command_path(&self) -> PathBuf {
+        Path::new(&format!("{}/{}", self.path, self.cmd)).into()
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.command_path())
+    }
+
+    fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    fn fetch_url(&self) -> &str {
+        #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+        return "https://registry.npmjs.org/@esbuild/linux-x64/-/linux-x64-0.19.12.tgz";
+        #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+        return "https://registry.npmjs.org/@esbuild/linux-arm64/-/linux-arm64-0.19.12.tgz";
+        #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+        return "https://registry.npmjs.org/@esbuild/darwin-x64/-/darwin-x64-0.19.12.tgz";
+        #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+        return "https://registry.npmjs.org/@esbuild/darwin-arm64/-/darwin-arm64-0.19.12.tgz";
+        #[cfg(target_os = "freebsd")]
+        return "https://registry.npmjs.org/@esbuild/freebsd-x64/-/freebsd-x64-0.19.12.tgz";
+    }
+
+    fn version(&self) -> &str {
+        ESBUILD_VERSION
+    }
+}
@@ -9,24 +9,22 @@ pub struct GlooCtl {
     kubeconfig: Option<String>,
 }
 
-impl Default for GlooCtl {
-    fn default() -> Self {
-        GlooCtl::new("glooctl", ".asml/bin", None)
+impl GlooCtl {
+    pub fn try_default() -> anyhow::Result<Self> {
+        Self::new("glooctl", ".asml/bin", None)
     }
-}
 
-impl GlooCtl {
-    pub fn new(name: &str, path: &str, kubeconfig: Option<String>) -> Self {
+    pub fn new(name: &str, path: &str, kubeconfig: Option<String>) -> anyhow::Result<Self> {
         let s = Self {
             cmd: name.into(),
             path: path.into(),
             kubeconfig,
         };
-        crate::fetch(&s).unwrap();
-        s
+        crate::fetch(&s)?;
+        Ok(s)
     }
 
-    pub fn default_with_config(kubeconfig: String) -> Self {
+    pub fn default_with_config(kubeconfig: String) -> anyhow::Result<Self> {
         Self::new("glooctl", ".asml/bin", Some(kubeconfig))
     }
 
@@ -75,4 +73,8 @@ impl Tool for GlooCtl {
         #[cfg(target_os = "macos")]
         return "https://github.com/solo-io/gloo/releases/download/v1.15.18/glooctl-darwin-amd64";
     }
+
+    fn version(&self) -> &str {
+        "1.15.18"
+    }
 }
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
@@ -13,24 +14,22 @@ pub struct KubeCtl {
     kubeconfig: Option<String>,
 }
 
-impl Default for KubeCtl {
-    fn default() -> Self {
-        KubeCtl::new("kubectl", ".asml/bin", None)
+impl KubeCtl {
+    pub fn try_default() -> anyhow::Result<Self> {
+        Self::new("kubectl", ".asml/bin", None)
     }
-}
 
-impl KubeCtl {
-    pub fn new(name: &str, path: &str, kubeconfig: Option<String>) -> Self {
+    pub fn new(name: &str, path: &str, kubeconfig: Option<String>) -> anyhow::Result<Self> {
         let s = Self {
             cmd: name.into(),
             path: path.into(),
             kubeconfig,
         };
-        crate::fetch(&s).unwrap();
-        s
+        crate::fetch(&s)?;
+        Ok(s)
     }
 
-    pub fn default_with_config(kubeconfig: String) -> Self {
+    pub fn default_with_config(kubeconfig: String) -> anyhow::Result<Self> {
         Self::new("kubectl", ".asml/bin", Some(kubeconfig))
     }
 
@@ -41,7 +40,7 @@ impl KubeCtl {
         labels: Option<HashMap<String, String>>,
     ) -> Result<Value, String> {
         let label_args = labels
-            .unwrap_or(Default::default())
+            .unwrap_or_default()
             .into_iter()
             .map(|l| {
                 vec![
@@ -54,7 +53,7 @@ impl KubeCtl {
                 a.append(&mut v);
                 a
             })
-            .unwrap_or(Default::default());
+            .unwrap_or_default();
         let kubeconfig = match &self.kubeconfig {
             Some(cfg) => vec![format!("--kubeconfig={}", cfg)],
             None => Vec::default(),
@@ -71,9 +70,80 @@ impl KubeCtl {
             .spawn()
             .unwrap();
         let output = child.wait_with_output().unwrap();
-        let json = std::str::from_utf8(&*output.stdout).unwrap();
+        let json = std::str::from_utf8(&output.stdout).unwrap();
         Ok(serde_json::from_str(json).unwrap())
     }
+
+    pub fn apply(&self, manifest_url: &str) -> Result<(), String> {
+        let output = self
+            .command()
+            .args(self.kubeconfig_args())
+            .args(vec!["apply", "-f", manifest_url])
+            .output()
+            .map_err(|e| e.to_string())?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(format!(
+                "could not apply {}: {}",
+                manifest_url,
+                String::from_utf8_lossy(&output.stderr)
+            )),
+        }
+    }
+
+    /// Apply the manifests in `yaml`, e.g. as fetched with [`crate::fetch_locked`] from `source`
+    pub fn apply_manifest(&self, yaml: &[u8], source: &str) -> Result<(), String> {
+        let mut child = self
+            .command()
+            .args(self.kubeconfig_args())
+            .args(vec!["apply", "-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(yaml)
+            .map_err(|e| e.to_string())?;
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(format!(
+                "could not apply {}: {}",
+                source,
+                String::from_utf8_lossy(&output.stderr)
+            )),
+        }
+    }
+
+    pub fn patch_merge(&self, resource: &str, ns: &str, patch: &str) -> Result<(), String> {
+        let output = self
+            .command()
+            .args(self.kubeconfig_args())
+            .args(vec![
+                "patch", resource, "-n", ns, "--type", "merge", "-p", patch,
+            ])
+            .output()
+            .map_err(|e| e.to_string())?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(format!(
+                "could not patch {}: {}",
+                resource,
+                String::from_utf8_lossy(&output.stderr)
+            )),
+        }
+    }
+
+    fn kubeconfig_args(&self) -> Vec<String> {
+        match &self.kubeconfig {
+            Some(cfg) => vec![format!("--kubeconfig={}", cfg)],
+            None => Vec::default(),
+        }
+    }
 }
 
 impl Tool for KubeCtl {
@@ -99,4 +169,8 @@ impl Tool for KubeCtl {
         #[cfg(target_os = "macos")]
         return "https://dl.k8s.io/release/v1.28.2/bin/darwin/amd64/kubectl";
     }
+
+    fn version(&self) -> &str {
+        "1.28.2"
+    }
 }
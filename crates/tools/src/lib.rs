@@ -1,67 +1,352 @@
 use std::io::Read;
 use std::os::unix::fs::PermissionsExt;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 use anyhow::anyhow;
 use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 pub mod cmctl;
+pub mod esbuild;
 pub mod glooctl;
 pub mod kubectl;
 pub mod terraform;
 
+/// Records the version, source & SHA-256 of each tool fetched for a project, per platform. It is written to the
+/// project root, to be committed so that every checkout fetches the same binaries.
+pub const LOCKFILE: &str = "asml-tools.lock";
+
+/// Directory of pre-provisioned tool binaries, named after their commands, to use instead of fetching them
+pub const TOOLS_DIR_VAR: &str = "ASML_TOOLS_DIR";
+/// Directory mirroring downloads by host & path, e.g. `<mirror>/releases.hashicorp.com/terraform/...`
+pub const MIRROR_VAR: &str = "ASML_MIRROR";
+/// When set, nothing is downloaded; tools & runtimes must come from the tools directory or the mirror
+pub const OFFLINE_VAR: &str = "ASML_OFFLINE";
+/// When set, downloads which are not pinned in the lockfile are fetched & pinned, like `--update-lock`
+pub const UPDATE_LOCK_VAR: &str = "ASML_TOOLS_UPDATE_LOCK";
+
+/// Tools are fetched one at a time, as functions may be cast in parallel
+static FETCH: Mutex<()> = Mutex::new(());
+/// Set by `--update-lock`
+static UPDATE_LOCK: AtomicBool = AtomicBool::new(false);
+
 pub trait Tool {
     fn command_name(&self) -> &str;
     fn command_path(&self) -> PathBuf;
     fn command(&self) -> Command;
     fn path(&self) -> &str;
     fn fetch_url(&self) -> &str;
+    /// The version fetched from `fetch_url`, as recorded in the lockfile
+    fn version(&self) -> &str;
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Lockfile {
+    #[serde(default, rename = "tool")]
+    pub tools: Vec<LockedTool>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LockedTool {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    pub url: String,
+    /// Digest of the download from `url`; if empty, it is filled in by the next fetch which updates the lockfile
+    #[serde(default)]
+    pub sha256: String,
+}
+
+impl Lockfile {
+    /// Read the lockfile at `path`, or start an empty one if there is none
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| anyhow!("could not parse {}: {}", path.display(), e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let contents = toml::to_string_pretty(self)?;
+        std::fs::write(path, contents)
+            .map_err(|e| anyhow!("could not write {}: {}", path.display(), e))
+    }
+
+    fn find(&self, name: &str, version: &str, platform: &str) -> Option<&LockedTool> {
+        self.tools
+            .iter()
+            .find(|t| t.name == name && t.version == version && t.platform == platform)
+    }
+
+    /// Record `tool`, replacing any entry for the same name, version & platform
+    fn pin(&mut self, tool: LockedTool) {
+        match self.tools.iter_mut().find(|t| {
+            t.name == tool.name && t.version == tool.version && t.platform == tool.platform
+        }) {
+            Some(locked) => *locked = tool,
+            None => self.tools.push(tool),
+        }
+    }
+}
+
+/// The lockfile of the project the CLI is run in: the nearest directory up from the working directory with an
+/// `assemblylift.toml`, or the working directory if there is none
+pub fn lockfile_path() -> anyhow::Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    let root = cwd
+        .ancestors()
+        .find(|dir| dir.join("assemblylift.toml").exists())
+        .unwrap_or(&cwd);
+    Ok(root.join(LOCKFILE))
+}
+
+/// The platform recorded in the lockfile for artifacts which are the same on every platform
+pub const ANY_PLATFORM: &str = "any";
+
+/// The platform tools are fetched for, e.g. `linux-x86_64`
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Whether an environment variable such as [OFFLINE_VAR] is set to something other than empty, `0` or `false`
+fn flag_set(var: &str) -> bool {
+    std::env::var(var)
+        .map(|v| !matches!(v.as_str(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+fn offline() -> bool {
+    flag_set(OFFLINE_VAR)
+}
+
+/// Allow downloads which are not pinned in the lockfile, recording their digests
+pub fn set_update_lock() {
+    UPDATE_LOCK.store(true, Ordering::SeqCst);
 }
 
+fn update_lock() -> bool {
+    UPDATE_LOCK.load(Ordering::SeqCst) || flag_set(UPDATE_LOCK_VAR)
+}
+
+/// Fetch `tool` to its command path unless it is already there, verifying the download against the lockfile
 pub fn fetch<T>(tool: &T) -> anyhow::Result<()>
 where
     T: Tool + Sized,
 {
-    if !tool.command_path().exists() {
-        println!("🔧 > Fetching tool {}", tool.command_name());
-
-        std::fs::create_dir_all(tool.path().clone()).unwrap();
-        let bytes = download_to_bytes(tool.fetch_url())
-            .expect(&*format!("could not download {}", tool.command_name()));
-
-        if tool.fetch_url().contains(".tar.gz") {
-            // FIXME this leans on the assumption that the only gzipped tool we fetch is cmctl
-            let tar = GzDecoder::new(bytes.as_slice());
-            let mut ar = tar::Archive::new(tar);
-            ar.entries()
-                .expect("cmctl archive is empty")
-                .find(|e| e.as_ref().unwrap().path().unwrap().file_name().unwrap() == "cmctl")
-                .expect("cmctl not found in archive")
-                .unwrap()
-                .unpack(tool.command_path())
-                .map_err(|err| anyhow!("could not unpack cmctl: {}", err.to_string()))?;
-        } else if tool.fetch_url().contains(".zip") {
-            // FIXME this leans on the assumption that the only zipped tool we fetch is terraform
-            unzip_terraform(bytes, tool.command_path().to_str().unwrap())?;
-        } else {
-            std::fs::write(tool.command_path(), bytes)
-                .map_err(|err| anyhow!("could not unpack {}: {}", tool.command_name(), err.to_string()))?;
+    fetch_tool(tool).map_err(|e| anyhow!("could not fetch tool {}: {}", tool.command_name(), e))
+}
+
+fn fetch_tool<T>(tool: &T) -> anyhow::Result<()>
+where
+    T: Tool + Sized,
+{
+    if tool.command_path().exists() {
+        return Ok(());
+    }
+    let _fetch = FETCH.lock().unwrap();
+    if tool.command_path().exists() {
+        return Ok(());
+    }
+    let name = tool.command_name();
+    std::fs::create_dir_all(tool.path())?;
+
+    if let Ok(dir) = std::env::var(TOOLS_DIR_VAR) {
+        let provided = Path::new(&dir).join(name);
+        if provided.exists() {
+            println!("🔧 > Using tool {} from {}", name, provided.display());
+            std::fs::copy(&provided, tool.command_path())
+                .map_err(|e| anyhow!("could not copy {}: {}", provided.display(), e))?;
+            return set_executable(tool);
         }
+    }
 
-        let mut perms = std::fs::metadata(tool.command_path())
-            .unwrap()
-            .permissions();
-        perms.set_mode(0o755);
-        std::fs::set_permissions(tool.command_path(), perms).map_err(|err| anyhow!(
-                "could not set {:?} binary executable (octal 755) permissions: {}",
-                tool.command_path(),
-                err.to_string(),
-            )
-        )?;
+    println!("🔧 > Fetching tool {} {}", name, tool.version());
+    let (url, bytes) = fetch_locked_for(name, tool.version(), &platform(), tool.fetch_url())?;
+    unpack(tool, &url, bytes)?;
+    set_executable(tool)
+}
+
+/// Fetch the platform-independent artifact `name`, such as a manifest, verifying it against the lockfile
+pub fn fetch_locked(name: &str, version: &str, url: &str) -> anyhow::Result<Vec<u8>> {
+    fetch_locked_for(name, version, ANY_PLATFORM, url).map(|(_, bytes)| bytes)
+}
+
+/// Fetch `name` for `platform` from the URL recorded in the lockfile, or from `url` if it is not recorded yet.
+/// Returns the URL fetched from and the verified download.
+fn fetch_locked_for(
+    name: &str,
+    version: &str,
+    platform: &str,
+    url: &str,
+) -> anyhow::Result<(String, Vec<u8>)> {
+    fetch_pinned(
+        &lockfile_path()?,
+        name,
+        version,
+        platform,
+        url,
+        update_lock(),
+    )
+}
+
+/// Fetch `name` as pinned in the lockfile at `lockfile_path`. A download which is not pinned yet is refused, unless
+/// `update` is set, in which case its digest is recorded.
+fn fetch_pinned(
+    lockfile_path: &Path,
+    name: &str,
+    version: &str,
+    platform: &str,
+    url: &str,
+    update: bool,
+) -> anyhow::Result<(String, Vec<u8>)> {
+    let mut lockfile = Lockfile::read(lockfile_path)?;
+    let mut locked = lockfile
+        .find(name, version, platform)
+        .cloned()
+        .unwrap_or(LockedTool {
+            name: name.into(),
+            version: version.into(),
+            platform: platform.into(),
+            url: url.into(),
+            sha256: String::new(),
+        });
+    if locked.sha256.is_empty() && !update {
+        return Err(anyhow!(
+            "{} {} for {} is not pinned in {}; run with --update-lock (or set {}) to fetch it from {} and record \
+             its SHA-256",
+            name,
+            locked.version,
+            platform,
+            lockfile_path.display(),
+            UPDATE_LOCK_VAR,
+            locked.url
+        ));
     }
 
-    Ok(())
+    let bytes = fetch_artifact(&locked.url)?;
+    let digest = sha256(&bytes);
+    if locked.sha256.is_empty() {
+        println!(
+            "🔒 > Pinning {} {} at SHA-256 {}",
+            name, locked.version, digest
+        );
+        locked.sha256 = digest;
+        lockfile.pin(locked.clone());
+        lockfile.write(lockfile_path)?;
+    } else if locked.sha256 != digest {
+        return Err(anyhow!(
+            "{} from {} does not match {}: expected SHA-256 {}, got {}",
+            name,
+            locked.url,
+            lockfile_path.display(),
+            locked.sha256,
+            digest
+        ));
+    }
+    Ok((locked.url, bytes))
+}
+
+/// Fetch the object at `url`, from the mirror if it has it. Offline, only the mirror is consulted.
+pub fn fetch_artifact(url: &str) -> anyhow::Result<Vec<u8>> {
+    if let Ok(mirror) = std::env::var(MIRROR_VAR) {
+        let path = mirror_path(Path::new(&mirror), url)?;
+        if path.exists() {
+            println!("📦 > Using {} from mirror", path.display());
+            return std::fs::read(&path)
+                .map_err(|e| anyhow!("could not read {}: {}", path.display(), e));
+        }
+        if offline() {
+            return Err(anyhow!(
+                "{} is not in mirror {} and {} is set",
+                url,
+                mirror,
+                OFFLINE_VAR
+            ));
+        }
+    } else if offline() {
+        return Err(anyhow!(
+            "cannot download {} while {} is set; provide it with {} or {}",
+            url,
+            OFFLINE_VAR,
+            MIRROR_VAR,
+            TOOLS_DIR_VAR
+        ));
+    }
+    download_to_bytes(url).map_err(|e| {
+        anyhow!(
+            "{}; to work without network access, set {} or {}",
+            e,
+            MIRROR_VAR,
+            TOOLS_DIR_VAR
+        )
+    })
+}
+
+/// The path of `url` within `mirror`: its host followed by its path
+fn mirror_path(mirror: &Path, url: &str) -> anyhow::Result<PathBuf> {
+    let relative = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(anyhow!("cannot map {} into a mirror", url));
+    }
+    Ok(mirror.join(relative))
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn unpack<T>(tool: &T, url: &str, bytes: Vec<u8>) -> anyhow::Result<()>
+where
+    T: Tool + Sized,
+{
+    let name = tool.command_name();
+    if url.contains(".tar.gz") || url.ends_with(".tgz") {
+        let tar = GzDecoder::new(bytes.as_slice());
+        let mut ar = tar::Archive::new(tar);
+        for entry in ar.entries()? {
+            let mut entry = entry?;
+            if entry.path()?.file_name() == Some(name.as_ref()) {
+                entry
+                    .unpack(tool.command_path())
+                    .map_err(|err| anyhow!("could not unpack {}: {}", name, err))?;
+                return Ok(());
+            }
+        }
+        Err(anyhow!("{} not found in archive", name))
+    } else if url.contains(".zip") {
+        // FIXME this leans on the assumption that the only zipped tool we fetch is terraform
+        unzip_terraform(bytes, &tool.command_path())
+    } else {
+        std::fs::write(tool.command_path(), bytes)
+            .map_err(|err| anyhow!("could not unpack {}: {}", name, err))
+    }
+}
+
+fn set_executable<T>(tool: &T) -> anyhow::Result<()>
+where
+    T: Tool + Sized,
+{
+    let mut perms = std::fs::metadata(tool.command_path())?.permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(tool.command_path(), perms).map_err(|err| {
+        anyhow!(
+            "could not set {:?} binary executable (octal 755) permissions: {}",
+            tool.command_path(),
+            err,
+        )
+    })
 }
 
 pub fn download_to_bytes<T: reqwest::IntoUrl + Clone>(url: T) -> anyhow::Result<Vec<u8>> {
@@ -82,30 +367,172 @@ pub fn download_to_bytes<T: reqwest::IntoUrl + Clone>(url: T) -> anyhow::Result<
     }
 }
 
-pub fn download_to_path<T: reqwest::IntoUrl + Clone, P: AsRef<Path>>(
-    url: T,
-    to: P,
-) -> anyhow::Result<()> {
-    match download_to_bytes(url) {
-        Ok(bytes) => {
-            if let Err(err) = std::fs::write(to, bytes) {
-                return Err(anyhow!(err));
-            }
+fn unzip_terraform(bytes_in: Vec<u8>, out_path: &Path) -> anyhow::Result<()> {
+    let reader = std::io::Cursor::new(bytes_in);
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut file_out = archive.by_name("terraform")?;
 
-            Ok(())
+    let mut outfile = std::fs::File::create(out_path)?;
+    std::io::copy(&mut file_out, &mut outfile)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(name: &str, version: &str, platform: &str, sha256: &str) -> LockedTool {
+        LockedTool {
+            name: name.into(),
+            version: version.into(),
+            platform: platform.into(),
+            url: format!("https://example.com/{}/{}/{}.zip", name, version, platform),
+            sha256: sha256.into(),
         }
-        Err(err) => Err(anyhow!(err)),
     }
-}
 
-fn unzip_terraform(bytes_in: Vec<u8>, out_dir: &str) -> anyhow::Result<()> {
-    let reader = std::io::Cursor::new(bytes_in);
-    let mut archive = zip::ZipArchive::new(reader).unwrap();
-    let mut file_out = archive.by_name("terraform").unwrap();
+    #[test]
+    fn lockfile_round_trips_and_pins_per_version_and_platform() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCKFILE);
+        assert!(Lockfile::read(&path).unwrap().tools.is_empty());
+
+        let mut lockfile = Lockfile::default();
+        lockfile.pin(locked("terraform", "1.4.6", "linux-x86_64", "aa"));
+        lockfile.pin(locked("terraform", "1.4.6", "macos-aarch64", "bb"));
+        lockfile.pin(locked("terraform", "1.5.7", "linux-x86_64", "dd"));
+        lockfile.pin(locked("terraform", "1.4.6", "linux-x86_64", "cc"));
+        lockfile.write(&path).unwrap();
+
+        let lockfile = Lockfile::read(&path).unwrap();
+        assert_eq!(lockfile.tools.len(), 3);
+        let sha256 = |version, platform| {
+            lockfile
+                .find("terraform", version, platform)
+                .map(|t| t.sha256.clone())
+        };
+        assert_eq!(sha256("1.4.6", "linux-x86_64").unwrap(), "cc");
+        assert_eq!(sha256("1.4.6", "macos-aarch64").unwrap(), "bb");
+        assert_eq!(sha256("1.5.7", "linux-x86_64").unwrap(), "dd");
+        assert!(sha256("1.5.7", "macos-aarch64").is_none());
+        assert!(lockfile.find("kubectl", "1.4.6", "linux-x86_64").is_none());
+    }
+
+    #[test]
+    fn lockfile_entries_may_omit_the_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCKFILE);
+        std::fs::write(
+            &path,
+            "[[tool]]\nname = \"kubectl\"\nversion = \"1.28.2\"\nplatform = \"linux-x86_64\"\nurl = \"https://dl.k8s.io/kubectl\"\n",
+        )
+        .unwrap();
+        let lockfile = Lockfile::read(&path).unwrap();
+        assert!(lockfile
+            .find("kubectl", "1.28.2", "linux-x86_64")
+            .unwrap()
+            .sha256
+            .is_empty());
+
+        std::fs::write(&path, "[[tool]]\nname = 1\n").unwrap();
+        assert!(Lockfile::read(&path).is_err());
+    }
+
+    #[test]
+    fn mirror_path_is_host_and_path() {
+        let mirror = Path::new("/mirror");
+        assert_eq!(
+            mirror_path(
+                mirror,
+                "https://releases.hashicorp.com/terraform/1.4.6/terraform.zip"
+            )
+            .unwrap(),
+            Path::new("/mirror/releases.hashicorp.com/terraform/1.4.6/terraform.zip")
+        );
+        assert!(mirror_path(mirror, "https://example.com/../../etc/passwd").is_err());
+        assert!(mirror_path(mirror, "file:///etc/passwd").is_err());
+    }
+
+    /// Covers everything which reads the environment, as tests run in parallel
+    #[test]
+    fn offline_fetches_are_verified_against_the_mirror_and_lockfile() {
+        let mirror = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let lockfile_path = project.path().join(LOCKFILE);
+        let url = "https://example.com/runtime/1.0.0/runtime.zip";
+        let mirrored = mirror_path(mirror.path(), url).unwrap();
+        std::fs::create_dir_all(mirrored.parent().unwrap()).unwrap();
+        std::fs::write(&mirrored, b"runtime").unwrap();
+
+        std::env::set_var(OFFLINE_VAR, "false");
+        assert!(!offline());
+        std::env::set_var(OFFLINE_VAR, "1");
+        assert!(offline());
+        std::env::set_var(MIRROR_VAR, mirror.path());
+
+        // Not pinned: refused unless updating the lockfile, which records the digest
+        let fetch_version = |version: &str, url: &str, update| {
+            fetch_pinned(
+                &lockfile_path,
+                "runtime",
+                version,
+                ANY_PLATFORM,
+                url,
+                update,
+            )
+        };
+        let fetch = |update| fetch_version("1.0.0", url, update);
+        assert!(fetch(false).unwrap_err().to_string().contains("not pinned"));
+        assert!(!lockfile_path.exists());
+        assert_eq!(fetch(true).unwrap(), (url.to_string(), b"runtime".to_vec()));
+        let lockfile = Lockfile::read(&lockfile_path).unwrap();
+        assert_eq!(
+            lockfile
+                .find("runtime", "1.0.0", ANY_PLATFORM)
+                .unwrap()
+                .sha256,
+            sha256(b"runtime")
+        );
+
+        // Another version is pinned separately, from its own URL
+        let url_2 = "https://example.com/runtime/2.0.0/runtime.zip";
+        let mirrored_2 = mirror_path(mirror.path(), url_2).unwrap();
+        std::fs::create_dir_all(mirrored_2.parent().unwrap()).unwrap();
+        std::fs::write(&mirrored_2, b"runtime 2").unwrap();
+        assert!(fetch_version("2.0.0", url_2, false)
+            .unwrap_err()
+            .to_string()
+            .contains("not pinned"));
+        assert_eq!(
+            fetch_version("2.0.0", url_2, true).unwrap(),
+            (url_2.to_string(), b"runtime 2".to_vec())
+        );
+        let lockfile = Lockfile::read(&lockfile_path).unwrap();
+        assert_eq!(lockfile.tools.len(), 2);
+        assert_eq!(
+            lockfile
+                .find("runtime", "2.0.0", ANY_PLATFORM)
+                .unwrap()
+                .sha256,
+            sha256(b"runtime 2")
+        );
+
+        // Pinned: verified, and a changed download is refused even when updating
+        assert_eq!(fetch(false).unwrap().1, b"runtime".to_vec());
+        std::fs::write(&mirrored, b"tampered").unwrap();
+        assert!(fetch(false)
+            .unwrap_err()
+            .to_string()
+            .contains("does not match"));
+        assert!(fetch(true).is_err());
+
+        // Offline, anything missing from the mirror is an error rather than a download
+        assert!(fetch_artifact("https://example.com/missing.zip")
+            .unwrap_err()
+            .to_string()
+            .contains(OFFLINE_VAR));
 
-    let mut outfile = std::fs::File::create(out_dir).unwrap();
-    match std::io::copy(&mut file_out, &mut outfile) {
-        Ok(_) => Ok(()),
-        Err(why) => Err(anyhow!(why.to_string())),
+        std::env::remove_var(MIRROR_VAR);
+        std::env::remove_var(OFFLINE_VAR);
     }
 }
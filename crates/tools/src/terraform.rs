@@ -6,86 +6,98 @@ use crate::Tool;
 pub struct Terraform {
     cmd: String,
     path: String,
+    dir: String,
+    targets: Vec<String>,
 }
 
-impl Default for Terraform {
-    fn default() -> Self {
-        Terraform::new("terraform", ".asml/bin")
+impl Terraform {
+    pub fn try_default() -> anyhow::Result<Self> {
+        Self::new("terraform", ".asml/bin")
     }
-}
 
-impl Terraform {
-    pub fn new(name: &str, path: &str) -> Self {
+    pub fn new(name: &str, path: &str) -> anyhow::Result<Self> {
         let s = Self {
             cmd: name.into(),
             path: path.into(),
+            dir: "./net".into(),
+            targets: Vec::new(),
         };
-        crate::fetch(&s).unwrap();
-        s
+        crate::fetch(&s)?;
+        Ok(s)
+    }
+
+    /// Run Terraform from `dir` instead of `./net`, e.g. for the infrastructure of a named environment
+    pub fn with_dir(mut self, dir: &str) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Limit plans & applies to the given resource or module addresses, e.g. `module.my_service_service`
+    pub fn with_targets(mut self, targets: Vec<String>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    fn target_args(&self) -> Vec<String> {
+        self.targets
+            .iter()
+            .map(|t| format!("-target={}", t))
+            .collect()
     }
 
     pub fn init(&self) {
-        let mut terraform_result = self.command()
-            .arg("-chdir=./net")
+        let mut terraform_result = self
+            .command()
+            .arg(format!("-chdir={}", self.dir))
             .arg("init")
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .spawn()
             .unwrap();
 
-        match terraform_result.wait() {
-            Ok(_) => {}
-            Err(_) => {}
-        }
+        let _ = terraform_result.wait();
     }
 
     pub fn plan(&self) {
-        let mut terraform_result = self.command()
-            .arg("-chdir=./net")
+        let mut terraform_result = self
+            .command()
+            .arg(format!("-chdir={}", self.dir))
             .arg("plan")
             .arg("-out=./plan")
-            .arg("-state=../terraform.tfstate")
+            .args(self.target_args())
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .spawn()
             .unwrap();
 
-        match terraform_result.wait() {
-            Ok(_) => {}
-            Err(_) => {}
-        }
+        let _ = terraform_result.wait();
     }
 
     pub fn apply(&self) {
-        let mut terraform_result = self.command()
-            .arg("-chdir=./net")
+        let mut terraform_result = self
+            .command()
+            .arg(format!("-chdir={}", self.dir))
             .arg("apply")
-            .arg("-state=../terraform.tfstate")
+            .args(self.target_args())
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .spawn()
             .unwrap();
 
-        match terraform_result.wait() {
-            Ok(_) => {}
-            Err(_) => {}
-        }
+        let _ = terraform_result.wait();
     }
 
     pub fn destroy(&self) {
-        let mut terraform_result = self.command()
-            .arg("-chdir=./net")
+        let mut terraform_result = self
+            .command()
+            .arg(format!("-chdir={}", self.dir))
             .arg("destroy")
-            .arg("-state=../terraform.tfstate")
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .spawn()
             .unwrap();
 
-        match terraform_result.wait() {
-            Ok(_) => {}
-            Err(_) => {}
-        }
+        let _ = terraform_result.wait();
     }
 }
 
@@ -114,4 +126,8 @@ impl Tool for Terraform {
         #[cfg(target_os = "freebsd")]
         return "https://releases.hashicorp.com/terraform/1.4.6/terraform_1.4.6_freebsd_amd64.zip";
     }
+
+    fn version(&self) -> &str {
+        "1.4.6"
+    }
 }